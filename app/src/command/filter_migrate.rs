@@ -0,0 +1,35 @@
+use anyhow::Result;
+use murack_core_domain::filter::filter_document;
+use sqlx::PgPool;
+
+use crate::cui::Cui;
+
+/// filter-migrateコマンド
+///
+/// DB に保存されている全てのフィルタを、現在の形式に書き換える
+pub struct CommandFilterMigrate<'cui, CUI>
+where
+    CUI: Cui,
+{
+    cui: &'cui CUI,
+}
+
+impl<'cui, CUI> CommandFilterMigrate<'cui, CUI>
+where
+    CUI: Cui,
+{
+    pub fn new(cui: &'cui CUI) -> Self {
+        Self { cui }
+    }
+
+    /// このコマンドを実行
+    pub async fn run(&self, db_pool: &PgPool) -> Result<()> {
+        let mut tx = db_pool.begin().await?;
+        let count = filter_document::migrate_stored_filters(&mut tx).await?;
+        tx.commit().await?;
+
+        cui_outln!(self.cui, "{}件のフィルタを書き換えました。", count)?;
+
+        Ok(())
+    }
+}
@@ -28,6 +28,13 @@ where
         cui_outln!(cui, "    DAPのプレイリストを更新")?;
         cui_outln!(cui)?;
 
+        cui_outln!(cui, "playlist-list")?;
+        cui_outln!(
+            cui,
+            "    フィルタプレイリストの一覧を、条件の説明と共に表示"
+        )?;
+        cui_outln!(cui)?;
+
         cui_outln!(cui, "add <ライブラリ内パス>")?;
         cui_outln!(cui, "    曲をライブラリに追加。")?;
         cui_outln!(cui, "    (DBにデータを追加し、PCからDAPにファイルをコピー)")?;
@@ -57,6 +64,13 @@ where
         cui_outln!(cui, "    オーディオファイルから画像を取得する。")?;
         cui_outln!(cui)?;
 
+        cui_outln!(cui, "filter-migrate")?;
+        cui_outln!(
+            cui,
+            "    DBに保存されたフィルタを、現在の形式に書き換える。"
+        )?;
+        cui_outln!(cui)?;
+
         cui_outln!(cui, "replace <音声絶対パス> <ライブラリ内パス>")?;
         cui_outln!(cui, "    ※未実装")?;
         cui_outln!(cui, "    オーディオファイルを置き換える。")?;
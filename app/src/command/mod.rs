@@ -14,12 +14,18 @@ pub use check::{
 pub mod cmd_move;
 pub use cmd_move::{CommandMove, CommandMoveArgs};
 
+pub mod filter_migrate;
+pub use filter_migrate::CommandFilterMigrate;
+
 pub mod help;
 pub use help::CommandHelp;
 
 pub mod playlist;
 pub use playlist::CommandPlaylist;
 
+pub mod playlist_list;
+pub use playlist_list::CommandPlaylistList;
+
 pub mod remove;
 pub use remove::{CommandRemove, CommandRemoveArgs};
 
@@ -2,14 +2,21 @@ mod command_playlist_model;
 mod dap_playlist_repository;
 mod file_name;
 
-use std::{collections::HashSet, path::Path};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
 
 use anyhow::Result;
 use async_recursion::async_recursion;
+use chrono::Utc;
+use futures_util::TryStreamExt;
 use murack_core_domain::{
     path::LibraryTrackPath,
     playlist::{PlaylistTree, playlist_sqls},
-    track_query::{SelectColumn, playlist_query::PlaylistQueryBuilder},
+    track_query::{PreparedTrackQuery, SelectColumn, playlist_query::PlaylistQueryBuilder},
 };
 use sqlx::{PgPool, PgTransaction};
 
@@ -64,6 +71,9 @@ where
 
         cui_outln!(self.cui, "プレイリストファイルの保存中...").unwrap();
 
+        //ランダムの並び順のプレイリストは、保存するたびに異なる順序とする
+        let random_seed = Utc::now().timestamp_subsec_nanos() as i32;
+
         //再帰的に保存を実行
         save_plists_recursive(
             &plist_trees,
@@ -71,6 +81,7 @@ where
             dap_plist_path,
             &mut FileNameContext::new(save_count),
             &mut existing_file_set,
+            random_seed,
         )
         .await?;
 
@@ -94,6 +105,7 @@ where
 /// - plist_trees: 保存する全プレイリストツリー
 /// - root_path: プレイリストファイルの保存先ディレクトリのパス
 /// - existingFileSet:  DAPに既に存在するファイルパスのset
+/// - random_seed: ランダムの並び順のプレイリストに使う seed
 #[async_recursion]
 async fn save_plists_recursive<'c, 'p>(
     plist_trees: &'p [PlaylistTree<CommandPlaylistModel>],
@@ -101,6 +113,7 @@ async fn save_plists_recursive<'c, 'p>(
     root_path: &Path,
     context: &mut FileNameContext<'p>,
     existing_file_set: &mut HashSet<String>,
+    random_seed: i32,
 ) -> Result<()> {
     for tree in plist_trees {
         //DAPに保存するプレイリストなら処理
@@ -109,18 +122,16 @@ async fn save_plists_recursive<'c, 'p>(
             let plist_file_name = file_name::build_file_name(&tree.value.name, context);
             context.offset_of_whole += 1;
 
-            //プレイリスト内の曲パスを取得
+            //プレイリストの曲をリストアップし、曲パスを取得する準備をする
+            //ランダムの並び順は、保存されている seed ではなく、保存ごとの seed で並べる
             let query = PlaylistQueryBuilder::new(tree.value.id)
                 .column(SelectColumn::Path)
-                .build();
-            let track_paths: Vec<LibraryTrackPath> = query
-                .fetch(tx)
-                .await?
-                .into_iter()
-                .map(|row| SelectColumn::row_path(&row))
-                .collect::<Result<Vec<_>, _>>()?;
+                .random_seed(random_seed)
+                .build()
+                .prepare(tx)
+                .await?;
 
-            //プレイリストの曲データ取得後に、リストに変更があったか確認
+            //リストアップ後に、リストに変更があったか確認
             let new_dap_changed = sqlx::query_scalar!(
                 "SELECT dap_changed FROM playlists WHERE id = $1",
                 tree.value.id
@@ -128,31 +139,39 @@ async fn save_plists_recursive<'c, 'p>(
             .fetch_one(&mut **tx)
             .await?;
 
-            if new_dap_changed {
-                //変更があった場合、保存処理へ進む。
+            let need_write = if new_dap_changed || tree.value.sort_spec.uses_random_order() {
+                //変更があった場合か、ランダムの並び順の場合、保存処理へ進む。
+                //既存のファイルは、書き込みが完了した時点で置き換える
 
                 //既存ファイルSetから削除
-                if existing_file_set.remove(&plist_file_name) {
-                    //見つかって削除できたなら、DAPからも削除
-                    dap_playlist_repository::delete_playlist_file(root_path, &plist_file_name)?;
-                }
+                existing_file_set.remove(&plist_file_name);
 
-                write_playlist_file(root_path, &plist_file_name, &track_paths)?;
+                true
             } else {
                 //変更がないなら、上書きする必要なし
 
                 //既存ファイルSetから削除
-                if !existing_file_set.remove(&plist_file_name) {
-                    //もしSetになければ不慮の何かで消えてるので、保存しなおす
-                    write_playlist_file(root_path, &plist_file_name, &track_paths)?;
-                }
+                //もしSetになければ不慮の何かで消えてるので、保存しなおす
+                !existing_file_set.remove(&plist_file_name)
+            };
+
+            if need_write {
+                write_playlist_file(root_path, &plist_file_name, &query, tx).await?;
             }
         }
 
         context.parent_names.push(&tree.value.name);
 
         //子プレイリストの保存
-        save_plists_recursive(&tree.children, tx, root_path, context, existing_file_set).await?;
+        save_plists_recursive(
+            &tree.children,
+            tx,
+            root_path,
+            context,
+            existing_file_set,
+            random_seed,
+        )
+        .await?;
 
         context.parent_names.pop();
     }
@@ -185,28 +204,92 @@ fn count_save_plists_recursive(trees: &[PlaylistTree<CommandPlaylistModel>]) ->
     count
 }
 
-/// プレイリストに曲パスリストを書き込み
+/// プレイリストファイルを作成し、検索結果の曲パスを書き込む
+///
+/// 曲パスは DB から届いた順に書き込むため、曲数が多くてもメモリ使用量が増えない。
 /// # Arguments
-/// - path: プレイリストファイルの保存先パス
-/// - track_path_list: プレイリストファイルに書き込む、曲ファイルパスの一覧
-fn write_playlist_file(
+/// - root_path: プレイリストファイルの保存先ディレクトリのパス
+/// - plist_file_name: プレイリストファイル名
+/// - query: 曲パスを取得する検索
+async fn write_playlist_file<'c>(
     root_path: &Path,
     plist_file_name: &str,
-    track_path_list: &[LibraryTrackPath],
+    query: &PreparedTrackQuery,
+    tx: &mut PgTransaction<'c>,
 ) -> Result<()> {
-    //プレイリストファイルに書き込むデータを作成
+    let mut writer = PlaylistFileWriter::create(root_path, plist_file_name)?;
+
+    let mut rows = query.fetch_stream(tx);
+    while let Some(row) = rows.try_next().await? {
+        writer.write_track(&SelectColumn::row_path(&row)?)?;
+    }
+
+    writer.finish()
+}
+
+/// プレイリストファイルに、曲パスを一件ずつ書き込む
+///
+/// 一時ファイルに書き込み、`finish` で完了した時点でプレイリストファイルを置き換える。
+/// 途中でエラーになった場合は、一時ファイルを削除し、既存のプレイリストファイルは変更しない。
+struct PlaylistFileWriter<'a> {
+    writer: BufWriter<File>,
+
+    /// プレイリストファイルの保存先ディレクトリのパス
+    root_path: &'a Path,
+
+    /// プレイリストファイル名
+    plist_file_name: &'a str,
+
+    /// 書き込み中の一時ファイルのパス (置き換えが完了したら None)
+    temp_path: Option<PathBuf>,
+}
+
+impl<'a> PlaylistFileWriter<'a> {
+    /// 一時ファイルを作成し、ヘッダを書き込む
+    fn create(root_path: &'a Path, plist_file_name: &'a str) -> Result<Self> {
+        let (temp_path, writer) =
+            dap_playlist_repository::create_temp_playlist_file(root_path, plist_file_name)?;
+
+        let mut plist_writer = Self {
+            writer,
+            root_path,
+            plist_file_name,
+            temp_path: Some(temp_path),
+        };
+        plist_writer.writer.write_all(b"#EXTM3U\n")?;
+
+        Ok(plist_writer)
+    }
+
+    /// 曲ファイルのパスを追記
+    fn write_track(&mut self, track_path: &LibraryTrackPath) -> Result<()> {
+        let track_path: &str = track_path.as_ref();
+        writeln!(self.writer, "#EXTINF:,\n{TRACK_PATH}{track_path}")?;
+        Ok(())
+    }
 
-    let mut file_data = String::from("#EXTM3U\n");
+    /// 書き込みを完了し、プレイリストファイルを置き換える
+    fn finish(mut self) -> Result<()> {
+        self.writer.flush()?;
 
-    for track_path in track_path_list {
-        file_data.push_str("#EXTINF:,\n");
-        file_data.push_str(TRACK_PATH);
-        file_data.push_str(track_path.as_ref());
-        file_data.push('\n')
+        if let Some(temp_path) = self.temp_path.take() {
+            dap_playlist_repository::commit_playlist_file(
+                self.root_path,
+                &temp_path,
+                self.plist_file_name,
+            )?;
+        }
+        Ok(())
     }
+}
 
-    //プレイリストファイルを作成する
-    dap_playlist_repository::make_playlist_file(root_path, plist_file_name, &file_data)
+impl Drop for PlaylistFileWriter<'_> {
+    fn drop(&mut self) {
+        //完了せずに破棄された場合は、書き込み途中の一時ファイルを削除する
+        if let Some(temp_path) = self.temp_path.take() {
+            let _ = fs::remove_file(temp_path);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -216,7 +299,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_write_playlist_file() -> anyhow::Result<()> {
+    fn test_playlist_file_writer() -> anyhow::Result<()> {
         let track_path_list = vec![
             LibraryTrackPath::from_str("test/hoge/track1.flac")?,
             LibraryTrackPath::from_str("test/track3.m4a")?,
@@ -227,7 +310,11 @@ mod tests {
 
         let temp_dir = tempfile::tempdir()?;
 
-        write_playlist_file(temp_dir.path(), FILE_NAME, &track_path_list)?;
+        let mut writer = PlaylistFileWriter::create(temp_dir.path(), FILE_NAME)?;
+        for track_path in &track_path_list {
+            writer.write_track(track_path)?;
+        }
+        writer.finish()?;
 
         // プレイリストファイルの内容が期待通りか確認
         let playlist_file_path = temp_dir.path().join(FILE_NAME);
@@ -238,4 +325,23 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_playlist_file_writer_not_finished() -> anyhow::Result<()> {
+        const FILE_NAME: &str = "playlist.m3u";
+
+        let temp_dir = tempfile::tempdir()?;
+        let playlist_file_path = temp_dir.path().join(FILE_NAME);
+        fs::write(&playlist_file_path, "old")?;
+
+        let mut writer = PlaylistFileWriter::create(temp_dir.path(), FILE_NAME)?;
+        writer.write_track(&LibraryTrackPath::from_str("test/track1.flac")?)?;
+        drop(writer);
+
+        // 完了しなければ、既存のプレイリストファイルは変更されず、一時ファイルも残らない
+        assert_eq!(fs::read_to_string(playlist_file_path)?, "old");
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+
+        Ok(())
+    }
 }
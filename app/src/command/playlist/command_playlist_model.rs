@@ -1,5 +1,5 @@
-use murack_core_domain::{NonEmptyString, playlist::PlaylistTreeValue};
-use sqlx::PgTransaction;
+use murack_core_domain::{NonEmptyString, SortSpec, playlist::PlaylistTreeValue};
+use sqlx::{PgTransaction, types::Json};
 
 /// playlist コマンド用のプレイリストデータモデル
 #[derive(Debug, PartialEq, Eq)]
@@ -18,12 +18,15 @@ pub struct CommandPlaylistModel {
 
     /// DAPにこのプレイリストを保存するか
     pub save_dap: bool,
+
+    /// 曲の並べ替えの指定
+    pub sort_spec: Json<SortSpec>,
 }
 
 impl CommandPlaylistModel {
     pub async fn get_all_from_db<'c>(tx: &mut PgTransaction<'c>) -> sqlx::Result<Vec<Self>> {
         sqlx::query_as!(Self,
-            r#"SELECT id, name AS "name: NonEmptyString", parent_id, in_folder_order, save_dap FROM playlists"#
+            r#"SELECT id, name AS "name: NonEmptyString", parent_id, in_folder_order, save_dap, sort_spec AS "sort_spec: Json<SortSpec>" FROM playlists"#
         )
         .fetch_all(&mut **tx)
         .await
@@ -1,8 +1,9 @@
 //! DAP内へファイルアクセスする処理 (旧 DapRepository)
 
 use std::{
-    fs::{self, Metadata},
-    path::Path,
+    fs::{self, File, Metadata},
+    io::BufWriter,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
@@ -49,15 +50,38 @@ pub fn listup_playlist_files(dap_plist_path: &Path) -> Result<Vec<String>> {
     Ok(ret_vec)
 }
 
-/// DAPにプレイリストファイルを作成
+/// DAPに、書き込み途中のプレイリストファイルを作成し、書き込み用に開く
+///
+/// プレイリストファイルと同じディレクトリに、拡張子の異なる一時ファイルとして作成する。
+/// 書き込みが完了したら `commit_playlist_file` で、プレイリストファイルに置き換える。
 /// # Arguments
 /// - dap_plist_path: DAPのプレイリスト保存パス
 /// - name: プレイリストファイル名
-/// - content: プレイリストファイルの内容
-pub fn make_playlist_file(dap_plist_path: &Path, file_name: &str, content: &str) -> Result<()> {
+/// # Returns
+/// 一時ファイルのパスと、書き込み用の Writer
+pub fn create_temp_playlist_file(
+    dap_plist_path: &Path,
+    file_name: &str,
+) -> Result<(PathBuf, BufWriter<File>)> {
+    let path = dap_plist_path.join(format!("{file_name}.{TEMP_EXT}"));
+
+    let file = File::create(&path).with_context(|| path.display().to_string())?;
+    Ok((path, BufWriter::new(file)))
+}
+
+/// 書き込みが完了した一時ファイルで、DAPのプレイリストファイルを置き換える
+/// # Arguments
+/// - dap_plist_path: DAPのプレイリスト保存パス
+/// - temp_path: `create_temp_playlist_file` で作成した一時ファイルのパス
+/// - name: プレイリストファイル名
+pub fn commit_playlist_file(
+    dap_plist_path: &Path,
+    temp_path: &Path,
+    file_name: &str,
+) -> Result<()> {
     let path = dap_plist_path.join(file_name);
 
-    fs::write(&path, content).with_context(|| path.display().to_string())
+    fs::rename(temp_path, &path).with_context(|| path.display().to_string())
 }
 
 /// DAPのプレイリストファイルを削除
@@ -76,6 +100,11 @@ pub fn delete_playlist_file(dap_plist_path: &Path, file_name: &str) -> Result<()
 /// Usecaseとも定義が重複
 const PLAYLIST_EXT: &str = "m3u";
 
+/// 書き込み途中のプレイリストファイルに付ける拡張子(ピリオドなし)
+///
+/// プレイリストファイルとして列挙されないよう、PLAYLIST_EXT と異なるものとする
+const TEMP_EXT: &str = "tmp";
+
 /// ディレクトリエントリがプレイリストか判別
 fn is_entry_playlist(path: &Path, metadata: &Metadata) -> bool {
     if !metadata.is_file() {
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use murack_core_domain::{
+    filter::{DescriptionLanguage, filter_description, filter_document},
+    playlist::PlaylistType,
+};
+use sqlx::PgPool;
+
+use crate::cui::Cui;
+
+/// playlist-listコマンド
+///
+/// フィルタプレイリストの一覧を、フィルタの説明文と共に表示する
+pub struct CommandPlaylistList<'cui, CUI>
+where
+    CUI: Cui,
+{
+    cui: &'cui CUI,
+}
+
+impl<'cui, CUI> CommandPlaylistList<'cui, CUI>
+where
+    CUI: Cui,
+{
+    pub fn new(cui: &'cui CUI) -> Self {
+        Self { cui }
+    }
+
+    /// このコマンドを実行
+    pub async fn run(&self, db_pool: &PgPool) -> Result<()> {
+        let mut tx = db_pool.begin().await?;
+
+        let playlists = sqlx::query!(
+            "SELECT id, name, filter_json FROM playlists WHERE playlist_type = $1 ORDER BY id",
+            PlaylistType::Filter as PlaylistType,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for plist in playlists {
+            cui_outln!(self.cui, "[{}] {}", plist.id, plist.name)?;
+
+            let Some(json) = plist.filter_json else {
+                cui_outln!(self.cui, "    (フィルタ未設定)")?;
+                continue;
+            };
+
+            //読み込めないフィルタがあっても、一覧の表示は続ける
+            match filter_document::from_json(json) {
+                Ok(filter) => {
+                    let description = filter_description::describe_with_db(
+                        &mut tx,
+                        &filter,
+                        DescriptionLanguage::Japanese,
+                    )
+                    .await?;
+                    cui_outln!(self.cui, "    {}", description)?;
+                }
+                Err(e) => {
+                    cui_outln!(self.cui, "    (フィルタを読み込めません: {})", e)?;
+                }
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
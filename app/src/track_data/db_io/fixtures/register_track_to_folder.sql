@@ -3,6 +3,6 @@
 -- - Empty database for track registration
 -- - Some playlists exist with listuped_flag = true to test reset functionality
 
-INSERT INTO playlists (id, playlist_type, name, parent_id, in_folder_order, filter_json, sort_type, sort_desc, save_dap, listuped_flag, dap_changed) VALUES 
-    (1, 'filter', 'Test Filter Playlist', NULL, 1, '{}', 'artist', false, true, true, false),
-    (2, 'folder', 'Test Folder Playlist', NULL, 2, NULL, 'artist', false, true, true, false);
\ No newline at end of file
+INSERT INTO playlists (id, playlist_type, name, parent_id, in_folder_order, filter_json, sort_spec, save_dap, listuped_flag, dap_changed) VALUES 
+    (1, 'filter', 'Test Filter Playlist', NULL, 1, '{}', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, true, false),
+    (2, 'folder', 'Test Folder Playlist', NULL, 2, NULL, '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, true, false);
\ No newline at end of file
@@ -1,6 +1,24 @@
+mod filter_context;
+pub use filter_context::{FilterContext, FilterTimeZone};
+
+pub mod filter_description;
+pub use filter_description::{DescriptionLanguage, FilterNames};
+
+pub mod filter_document;
+pub use filter_document::FILTER_SCHEMA_VERSION;
+
+mod filter_error;
+pub use filter_error::FilterError;
+
+pub mod filter_explain;
+pub use filter_explain::FilterExplanation;
+
 pub mod filter_target;
 pub use filter_target::FilterTarget;
 
+pub mod filter_validation;
+pub use filter_validation::{FilterValidation, FilterWarning, FilterWarningKind};
+
 pub mod range_artwork;
 pub use range_artwork::ArtworkFilterRange;
 
@@ -10,18 +28,28 @@ pub use range_bool::BoolFilterRange;
 pub mod range_date;
 pub use range_date::DateFilterRange;
 
+pub mod range_folder;
+pub use range_folder::FolderFilterRange;
+
 pub mod range_group;
 pub use range_group::GroupOperand;
 
 pub mod range_int;
 pub use range_int::IntFilterRange;
 
+pub mod range_playlist;
+pub use range_playlist::PlaylistFilterRange;
+
 pub mod range_string;
 pub use range_string::StringFilterRange;
 
 pub mod range_tags;
 pub use range_tags::TagsFilterRange;
 
+mod regex_pattern;
+
+pub mod text_query;
+
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,109 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+
+/// フィルタの日付の判定に使うタイムゾーン
+///
+/// 任意の IANA タイムゾーン (`Asia/Tokyo` など) の指定には対応していない。
+/// 夏時間のある地域では、`Local` を使用すること。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterTimeZone {
+    /// 固定のオフセット
+    ///
+    /// 夏時間の切り替えは考慮せず、常に同じオフセットで日付の境界を判定する
+    Fixed(FixedOffset),
+
+    /// 実行環境のローカルタイムゾーン
+    ///
+    /// 日時ごとに、夏時間を考慮したオフセットで日付の境界を判定する
+    Local,
+}
+
+impl From<FixedOffset> for FilterTimeZone {
+    fn from(offset: FixedOffset) -> Self {
+        Self::Fixed(offset)
+    }
+}
+
+/// フィルタの条件の判定に使う、検索時点の情報
+///
+/// 相対日付の条件 (過去 N 日以内など) の基準日と、
+/// 登録日時を日付として扱う際のタイムゾーンを保持する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilterContext {
+    /// 相対日付の基準日 (time_zone での今日)
+    pub today: NaiveDate,
+
+    /// 日時を日付に変換する際のタイムゾーン
+    pub time_zone: FilterTimeZone,
+}
+
+impl FilterContext {
+    /// 指定日時を基準とした情報を作成
+    pub fn new(now: DateTime<Utc>, time_zone: impl Into<FilterTimeZone>) -> Self {
+        let time_zone = time_zone.into();
+        Self {
+            today: date_in(time_zone, now),
+            time_zone,
+        }
+    }
+
+    /// 現在日時を基準とした情報を作成
+    pub fn now(time_zone: impl Into<FilterTimeZone>) -> Self {
+        Self::new(Utc::now(), time_zone)
+    }
+
+    /// 現在日時と、実行環境のローカルタイムゾーンの情報を作成
+    pub fn local() -> Self {
+        Self::now(FilterTimeZone::Local)
+    }
+
+    /// 現在日時と、指定したタイムゾーンの情報を作成
+    ///
+    /// タイムゾーンを指定しなければ、実行環境のローカルタイムゾーンを使用する
+    pub fn from_time_zone(time_zone: Option<FilterTimeZone>) -> Self {
+        Self::now(time_zone.unwrap_or(FilterTimeZone::Local))
+    }
+
+    /// time_zone での、指定日の 0 時の日時を取得
+    ///
+    /// 夏時間の切り替えで 0 時が存在しない日は、その日の最初の時刻とする。
+    pub fn day_start(&self, date: NaiveDate) -> DateTime<Utc> {
+        let naive = date.and_time(NaiveTime::MIN);
+        match self.time_zone {
+            FilterTimeZone::Fixed(offset) => local_to_utc(&offset, naive),
+            FilterTimeZone::Local => local_to_utc(&Local, naive),
+        }
+        //範囲外の日付は UTC として扱う
+        .unwrap_or_else(|| naive.and_utc())
+    }
+
+    /// 日時を、time_zone での日付に変換
+    pub fn date_of(&self, date_time: DateTime<Utc>) -> NaiveDate {
+        date_in(self.time_zone, date_time)
+    }
+}
+
+/// 日時を、タイムゾーンでの日付に変換
+fn date_in(time_zone: FilterTimeZone, date_time: DateTime<Utc>) -> NaiveDate {
+    match time_zone {
+        FilterTimeZone::Fixed(offset) => date_time.with_timezone(&offset).date_naive(),
+        FilterTimeZone::Local => date_time.with_timezone(&Local).date_naive(),
+    }
+}
+
+/// タイムゾーンでの日時を UTC に変換
+///
+/// 重複する時刻は早い方とし、存在しない時刻は 1 時間後の時刻を試す。
+fn local_to_utc<Tz: TimeZone>(
+    time_zone: &Tz,
+    naive: chrono::NaiveDateTime,
+) -> Option<DateTime<Utc>> {
+    time_zone
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            time_zone
+                .from_local_datetime(&(naive + TimeDelta::hours(1)))
+                .earliest()
+        })
+        .map(|d| d.to_utc())
+}
@@ -0,0 +1,587 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use sqlx::PgTransaction;
+
+use crate::filter::{
+    ArtworkFilterRange, BoolFilterRange, DateFilterRange, FilterError, FilterTarget,
+    FolderFilterRange, GroupOperand, IntFilterRange, PlaylistFilterRange, StringFilterRange,
+    TagsFilterRange, filter_validation::collect_conditions,
+};
+
+/// フィルタの説明文の言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescriptionLanguage {
+    Japanese,
+    English,
+}
+
+/// フィルタの説明文で使用する、ID に対応する名前
+///
+/// 名前が見つからない ID は `#ID` の形式で表示する。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterNames {
+    /// タグ ID に対応するタグ名
+    pub tags: HashMap<i32, String>,
+
+    /// タググループ ID に対応するグループ名
+    pub tag_groups: HashMap<i32, String>,
+
+    /// プレイリスト ID に対応するプレイリスト名
+    pub playlists: HashMap<i32, String>,
+}
+
+impl FilterNames {
+    /// フィルタで参照されている ID の名前を、DB から取得
+    pub async fn from_db<'c>(
+        tx: &mut PgTransaction<'c>,
+        filter: &FilterTarget,
+    ) -> Result<Self, FilterError> {
+        let tag_ids: Vec<i32> = collect_conditions(filter, &|target| match target {
+            FilterTarget::Tags { range } => Some(range.tag_ids().to_vec()),
+            _ => None,
+        })
+        .into_iter()
+        .flat_map(|(_, ids)| ids)
+        .collect();
+        let group_ids: Vec<i32> = collect_conditions(filter, &|target| match target {
+            FilterTarget::Tags {
+                range:
+                    TagsFilterRange::GroupAny { group_id } | TagsFilterRange::GroupNone { group_id },
+            } => Some(*group_id),
+            _ => None,
+        })
+        .into_iter()
+        .map(|(_, id)| id)
+        .collect();
+        let playlist_ids: Vec<i32> = collect_conditions(filter, &|target| match target {
+            FilterTarget::InPlaylist { range } => Some(range.playlist_id()),
+            _ => None,
+        })
+        .into_iter()
+        .map(|(_, id)| id)
+        .collect();
+
+        let mut names = Self::default();
+
+        if !tag_ids.is_empty() {
+            names.tags = sqlx::query!("SELECT id, name FROM tags WHERE id = ANY($1)", &tag_ids)
+                .fetch_all(&mut **tx)
+                .await?
+                .into_iter()
+                .map(|row| (row.id, row.name))
+                .collect();
+        }
+        if !group_ids.is_empty() {
+            names.tag_groups = sqlx::query!(
+                "SELECT id, name FROM tag_groups WHERE id = ANY($1)",
+                &group_ids
+            )
+            .fetch_all(&mut **tx)
+            .await?
+            .into_iter()
+            .map(|row| (row.id, row.name))
+            .collect();
+        }
+        if !playlist_ids.is_empty() {
+            names.playlists = sqlx::query!(
+                "SELECT id, name FROM playlists WHERE id = ANY($1)",
+                &playlist_ids
+            )
+            .fetch_all(&mut **tx)
+            .await?
+            .into_iter()
+            .map(|row| (row.id, row.name))
+            .collect();
+        }
+
+        Ok(names)
+    }
+}
+
+/// フィルタの説明文を、タグ名などを DB から取得して作成
+pub async fn describe_with_db<'c>(
+    tx: &mut PgTransaction<'c>,
+    filter: &FilterTarget,
+    language: DescriptionLanguage,
+) -> Result<String, FilterError> {
+    let names = FilterNames::from_db(tx, filter).await?;
+    Ok(describe(filter, language, &names))
+}
+
+/// フィルタの説明文を作成
+///
+/// 例: `アーティストが「X」を含む かつ レート 4以上`
+///
+/// グループ内の条件は `かつ` / `または` で連結し、入れ子のグループは括弧で囲む。
+pub fn describe(
+    filter: &FilterTarget,
+    language: DescriptionLanguage,
+    names: &FilterNames,
+) -> String {
+    let describer = Describer { language, names };
+
+    match filter {
+        FilterTarget::FilterGroup { op, children } => describer.children(op, children),
+        target => describer.clause(target),
+    }
+}
+
+struct Describer<'a> {
+    language: DescriptionLanguage,
+    names: &'a FilterNames,
+}
+
+impl Describer<'_> {
+    /// 言語に応じた文字列を選択
+    fn t<'s>(&self, ja: &'s str, en: &'s str) -> &'s str {
+        match self.language {
+            DescriptionLanguage::Japanese => ja,
+            DescriptionLanguage::English => en,
+        }
+    }
+
+    /// グループの子要素を連結した文字列
+    fn children(&self, op: &GroupOperand, children: &[FilterTarget]) -> String {
+        if children.is_empty() {
+            return self.t("条件なし", "no conditions").to_owned();
+        }
+
+        let separator = match op {
+            GroupOperand::And | GroupOperand::Not | GroupOperand::Nand => self.t(" かつ ", " and "),
+            GroupOperand::Or | GroupOperand::Nor => self.t(" または ", " or "),
+        };
+
+        let v: Vec<String> = children.iter().map(|c| self.clause(c)).collect();
+        let text = v.join(separator);
+
+        if !op.is_negated() {
+            return text;
+        }
+
+        //否定する範囲を明確にするため、複数の条件は括弧で囲む
+        let text = if children.len() > 1 {
+            format!("({text})")
+        } else {
+            text
+        };
+        match self.language {
+            DescriptionLanguage::Japanese => format!("{text} ではない"),
+            DescriptionLanguage::English => format!("not {text}"),
+        }
+    }
+
+    /// 条件の文字列
+    ///
+    /// グループは括弧で囲む
+    fn clause(&self, target: &FilterTarget) -> String {
+        match target {
+            FilterTarget::FilterGroup { op, children } => {
+                format!("({})", self.children(op, children))
+            }
+            FilterTarget::Tags { range } => self.tags(range),
+            FilterTarget::Rating { range } => {
+                self.int(self.t("レート", "Rating"), range, |v| v.to_string())
+            }
+            FilterTarget::Genre { range } => self.string(self.t("ジャンル", "Genre"), range),
+            FilterTarget::Artist { range } => self.string(self.t("アーティスト", "Artist"), range),
+            FilterTarget::AlbumArtist { range } => {
+                self.string(self.t("アルバムアーティスト", "Album artist"), range)
+            }
+            FilterTarget::Album { range } => self.string(self.t("アルバム", "Album"), range),
+            FilterTarget::Composer { range } => self.string(self.t("作曲者", "Composer"), range),
+            FilterTarget::Title { range } => self.string(self.t("曲名", "Title"), range),
+            FilterTarget::Artwork { range } => match range {
+                ArtworkFilterRange::Has => self.t("アートワークあり", "has artwork").to_owned(),
+                ArtworkFilterRange::None => self.t("アートワークなし", "has no artwork").to_owned(),
+            },
+            FilterTarget::Duration { range } => {
+                self.int(self.t("再生時間", "Duration"), range, duration_text)
+            }
+            FilterTarget::ReleaseDate { range } => {
+                self.date(self.t("リリース日", "Release date"), range)
+            }
+            FilterTarget::TrackNumber { range } => {
+                self.int(self.t("トラック番号", "Track number"), range, |v| {
+                    v.to_string()
+                })
+            }
+            FilterTarget::TrackMax { range } => {
+                self.int(self.t("トラック数", "Track count"), range, |v| {
+                    v.to_string()
+                })
+            }
+            FilterTarget::DiscNumber { range } => {
+                self.int(self.t("ディスク番号", "Disc number"), range, |v| {
+                    v.to_string()
+                })
+            }
+            FilterTarget::DiscMax { range } => {
+                self.int(self.t("ディスク数", "Disc count"), range, |v| {
+                    v.to_string()
+                })
+            }
+            FilterTarget::Memo { range } => self.string(self.t("メモ", "Memo"), range),
+            FilterTarget::MemoManage { range } => {
+                self.string(self.t("管理メモ", "Management memo"), range)
+            }
+            FilterTarget::EntryDate { range } => self.date(self.t("登録日", "Entry date"), range),
+            FilterTarget::OriginalTrack { range } => {
+                self.string(self.t("原曲", "Original track"), range)
+            }
+            FilterTarget::SuggestTarget { range } => match range {
+                BoolFilterRange::True => self.t("サジェスト対象", "is suggest target").to_owned(),
+                BoolFilterRange::False => self
+                    .t("サジェスト対象外", "is not suggest target")
+                    .to_owned(),
+            },
+            FilterTarget::Folder { range } => self.folder(range),
+            FilterTarget::InPlaylist { range } => self.playlist(range),
+        }
+    }
+
+    /// 値を括弧で囲む
+    fn quote(&self, value: &str) -> String {
+        match self.language {
+            DescriptionLanguage::Japanese => format!("「{value}」"),
+            DescriptionLanguage::English => format!("\"{value}\""),
+        }
+    }
+
+    fn string(&self, field: &str, range: &StringFilterRange) -> String {
+        let ja = |value: &str, op: &str, normalize: bool| {
+            let suffix = if normalize {
+                " (表記ゆれを無視)"
+            } else {
+                ""
+            };
+            format!("{field}が{}{op}{suffix}", self.quote(value))
+        };
+        let en = |op: &str, value: &str, normalize: bool| {
+            let suffix = if normalize {
+                " (ignoring variants)"
+            } else {
+                ""
+            };
+            format!("{field} {op} {}{suffix}", self.quote(value))
+        };
+
+        match (self.language, range) {
+            (DescriptionLanguage::Japanese, range) => match range {
+                StringFilterRange::Equal { value, normalize } => ja(value, "と等しい", *normalize),
+                StringFilterRange::NotEqual { value, normalize } => {
+                    ja(value, "と異なる", *normalize)
+                }
+                StringFilterRange::Contain { value, normalize } => ja(value, "を含む", *normalize),
+                StringFilterRange::NotContain { value, normalize } => {
+                    ja(value, "を含まない", *normalize)
+                }
+                StringFilterRange::Start { value, normalize } => ja(value, "で始まる", *normalize),
+                StringFilterRange::End { value, normalize } => ja(value, "で終わる", *normalize),
+                StringFilterRange::Regex { value } => {
+                    format!("{field}が正規表現{}に一致する", self.quote(value))
+                }
+                StringFilterRange::RegexIgnoreCase { value } => format!(
+                    "{field}が正規表現{}に一致する (大文字小文字を区別しない)",
+                    self.quote(value)
+                ),
+                StringFilterRange::NotRegex { value } => {
+                    format!("{field}が正規表現{}に一致しない", self.quote(value))
+                }
+                StringFilterRange::NotRegexIgnoreCase { value } => format!(
+                    "{field}が正規表現{}に一致しない (大文字小文字を区別しない)",
+                    self.quote(value)
+                ),
+            },
+            (DescriptionLanguage::English, range) => match range {
+                StringFilterRange::Equal { value, normalize } => en("is", value, *normalize),
+                StringFilterRange::NotEqual { value, normalize } => en("is not", value, *normalize),
+                StringFilterRange::Contain { value, normalize } => {
+                    en("contains", value, *normalize)
+                }
+                StringFilterRange::NotContain { value, normalize } => {
+                    en("does not contain", value, *normalize)
+                }
+                StringFilterRange::Start { value, normalize } => {
+                    en("starts with", value, *normalize)
+                }
+                StringFilterRange::End { value, normalize } => en("ends with", value, *normalize),
+                StringFilterRange::Regex { value } => format!("{field} matches /{value}/"),
+                StringFilterRange::RegexIgnoreCase { value } => {
+                    format!("{field} matches /{value}/ (case-insensitive)")
+                }
+                StringFilterRange::NotRegex { value } => {
+                    format!("{field} does not match /{value}/")
+                }
+                StringFilterRange::NotRegexIgnoreCase { value } => {
+                    format!("{field} does not match /{value}/ (case-insensitive)")
+                }
+            },
+        }
+    }
+
+    fn int(&self, field: &str, range: &IntFilterRange, value_text: fn(i32) -> String) -> String {
+        let include_null_text = |include_null: bool| {
+            if include_null {
+                self.t(" (値なしを含む)", " (including empty)")
+            } else {
+                ""
+            }
+        };
+
+        match self.language {
+            DescriptionLanguage::Japanese => match range {
+                IntFilterRange::Equal { value } => format!("{field}が{}", value_text(*value)),
+                IntFilterRange::NotEqual {
+                    value,
+                    include_null,
+                } => format!(
+                    "{field}が{}以外{}",
+                    value_text(*value),
+                    include_null_text(*include_null)
+                ),
+                IntFilterRange::LargeEqual { value } => {
+                    format!("{field} {}以上", value_text(*value))
+                }
+                IntFilterRange::SmallEqual { value } => {
+                    format!("{field} {}以下", value_text(*value))
+                }
+                IntFilterRange::RangeIn { min, max } => {
+                    format!("{field} {}〜{}", value_text(*min), value_text(*max))
+                }
+                IntFilterRange::RangeOut {
+                    min,
+                    max,
+                    include_null,
+                } => format!(
+                    "{field}が{}〜{}以外{}",
+                    value_text(*min),
+                    value_text(*max),
+                    include_null_text(*include_null)
+                ),
+                IntFilterRange::IsNull => format!("{field}なし"),
+                IntFilterRange::NotNull => format!("{field}あり"),
+            },
+            DescriptionLanguage::English => match range {
+                IntFilterRange::Equal { value } => format!("{field} is {}", value_text(*value)),
+                IntFilterRange::NotEqual {
+                    value,
+                    include_null,
+                } => format!(
+                    "{field} is not {}{}",
+                    value_text(*value),
+                    include_null_text(*include_null)
+                ),
+                IntFilterRange::LargeEqual { value } => {
+                    format!("{field} is at least {}", value_text(*value))
+                }
+                IntFilterRange::SmallEqual { value } => {
+                    format!("{field} is at most {}", value_text(*value))
+                }
+                IntFilterRange::RangeIn { min, max } => format!(
+                    "{field} is between {} and {}",
+                    value_text(*min),
+                    value_text(*max)
+                ),
+                IntFilterRange::RangeOut {
+                    min,
+                    max,
+                    include_null,
+                } => format!(
+                    "{field} is not between {} and {}{}",
+                    value_text(*min),
+                    value_text(*max),
+                    include_null_text(*include_null)
+                ),
+                IntFilterRange::IsNull => format!("{field} is empty"),
+                IntFilterRange::NotNull => format!("{field} is set"),
+            },
+        }
+    }
+
+    fn date(&self, field: &str, range: &DateFilterRange) -> String {
+        let value_text = |value: &NaiveDate| value.format("%Y-%m-%d").to_string();
+        let include_null_text = |include_null: bool| {
+            if include_null {
+                self.t(" (日付なしを含む)", " (including empty)")
+            } else {
+                ""
+            }
+        };
+
+        match self.language {
+            DescriptionLanguage::Japanese => match range {
+                DateFilterRange::Equal { value } => format!("{field}が{}", value_text(value)),
+                DateFilterRange::NotEqual {
+                    value,
+                    include_null,
+                } => format!(
+                    "{field}が{}以外{}",
+                    value_text(value),
+                    include_null_text(*include_null)
+                ),
+                DateFilterRange::Before { value } => {
+                    format!("{field}が{}以前", value_text(value))
+                }
+                DateFilterRange::After { value } => format!("{field}が{}以降", value_text(value)),
+                DateFilterRange::None => format!("{field}なし"),
+                DateFilterRange::NotNull => format!("{field}あり"),
+                DateFilterRange::WithinDays { value } => format!("{field}が{value}日以内"),
+                DateFilterRange::WithinMonths { value } => format!("{field}が{value}か月以内"),
+                DateFilterRange::OlderThanDays { value } => format!("{field}が{value}日より前"),
+                DateFilterRange::OlderThanMonths { value } => {
+                    format!("{field}が{value}か月より前")
+                }
+            },
+            DescriptionLanguage::English => match range {
+                DateFilterRange::Equal { value } => format!("{field} is {}", value_text(value)),
+                DateFilterRange::NotEqual {
+                    value,
+                    include_null,
+                } => format!(
+                    "{field} is not {}{}",
+                    value_text(value),
+                    include_null_text(*include_null)
+                ),
+                DateFilterRange::Before { value } => {
+                    format!("{field} is on or before {}", value_text(value))
+                }
+                DateFilterRange::After { value } => {
+                    format!("{field} is on or after {}", value_text(value))
+                }
+                DateFilterRange::None => format!("{field} is empty"),
+                DateFilterRange::NotNull => format!("{field} is set"),
+                DateFilterRange::WithinDays { value } => {
+                    format!("{field} is within the last {value} days")
+                }
+                DateFilterRange::WithinMonths { value } => {
+                    format!("{field} is within the last {value} months")
+                }
+                DateFilterRange::OlderThanDays { value } => {
+                    format!("{field} is more than {value} days ago")
+                }
+                DateFilterRange::OlderThanMonths { value } => {
+                    format!("{field} is more than {value} months ago")
+                }
+            },
+        }
+    }
+
+    fn tags(&self, range: &TagsFilterRange) -> String {
+        let tag = |id: &i32| self.name(&self.names.tags, *id);
+        let tag_list = |ids: &[i32]| {
+            let v: Vec<String> = ids.iter().map(tag).collect();
+            match self.language {
+                DescriptionLanguage::Japanese => v.concat(),
+                DescriptionLanguage::English => v.join(", "),
+            }
+        };
+        let group = |id: &i32| self.name(&self.names.tag_groups, *id);
+
+        match self.language {
+            DescriptionLanguage::Japanese => match range {
+                TagsFilterRange::Contain { value } => format!("タグ{}を含む", tag(value)),
+                TagsFilterRange::NotContain { value } => format!("タグ{}を含まない", tag(value)),
+                TagsFilterRange::None => "タグなし".to_owned(),
+                TagsFilterRange::ContainAny { values } => {
+                    format!("タグ{}のいずれかを含む", tag_list(values))
+                }
+                TagsFilterRange::ContainAll { values } => {
+                    format!("タグ{}を全て含む", tag_list(values))
+                }
+                TagsFilterRange::ContainNone { values } => {
+                    format!("タグ{}をいずれも含まない", tag_list(values))
+                }
+                TagsFilterRange::GroupAny { group_id } => {
+                    format!("タググループ{}のタグを含む", group(group_id))
+                }
+                TagsFilterRange::GroupNone { group_id } => {
+                    format!("タググループ{}のタグを含まない", group(group_id))
+                }
+            },
+            DescriptionLanguage::English => match range {
+                TagsFilterRange::Contain { value } => format!("has tag {}", tag(value)),
+                TagsFilterRange::NotContain { value } => {
+                    format!("does not have tag {}", tag(value))
+                }
+                TagsFilterRange::None => "has no tags".to_owned(),
+                TagsFilterRange::ContainAny { values } => {
+                    format!("has any of tags {}", tag_list(values))
+                }
+                TagsFilterRange::ContainAll { values } => {
+                    format!("has all of tags {}", tag_list(values))
+                }
+                TagsFilterRange::ContainNone { values } => {
+                    format!("has none of tags {}", tag_list(values))
+                }
+                TagsFilterRange::GroupAny { group_id } => {
+                    format!("has a tag in group {}", group(group_id))
+                }
+                TagsFilterRange::GroupNone { group_id } => {
+                    format!("has no tag in group {}", group(group_id))
+                }
+            },
+        }
+    }
+
+    fn folder(&self, range: &FolderFilterRange) -> String {
+        let (path, recursive, negated) = match range {
+            FolderFilterRange::In { value, recursive } => (value, *recursive, false),
+            FolderFilterRange::NotIn { value, recursive } => (value, *recursive, true),
+        };
+        let path = self.quote(path.as_ref());
+
+        match self.language {
+            DescriptionLanguage::Japanese => {
+                let scope = if recursive { "以下" } else { "直下" };
+                let not = if negated { "にない" } else { "にある" };
+                format!("フォルダ{path}{scope}{not}")
+            }
+            DescriptionLanguage::English => {
+                let not = if negated { "not " } else { "" };
+                let scope = if recursive { " or its subfolders" } else { "" };
+                format!("{not}in folder {path}{scope}")
+            }
+        }
+    }
+
+    fn playlist(&self, range: &PlaylistFilterRange) -> String {
+        let name = self.name(&self.names.playlists, range.playlist_id());
+
+        match (self.language, range) {
+            (DescriptionLanguage::Japanese, PlaylistFilterRange::In { .. }) => {
+                format!("プレイリスト{name}に含まれる")
+            }
+            (DescriptionLanguage::Japanese, PlaylistFilterRange::NotIn { .. }) => {
+                format!("プレイリスト{name}に含まれない")
+            }
+            (DescriptionLanguage::English, PlaylistFilterRange::In { .. }) => {
+                format!("in playlist {name}")
+            }
+            (DescriptionLanguage::English, PlaylistFilterRange::NotIn { .. }) => {
+                format!("not in playlist {name}")
+            }
+        }
+    }
+
+    /// ID に対応する名前を括弧で囲んだ文字列 (名前が無ければ `#ID`)
+    fn name(&self, names: &HashMap<i32, String>, id: i32) -> String {
+        match names.get(&id) {
+            Some(name) => self.quote(name),
+            None => format!("#{id}"),
+        }
+    }
+}
+
+/// ミリ秒の再生時間を `分:秒` 形式に変換
+fn duration_text(millis: i32) -> String {
+    let min = millis / 60_000;
+    let sec = millis % 60_000 / 1000;
+    let frac = millis % 1000;
+
+    if frac == 0 {
+        format!("{min}:{sec:02}")
+    } else {
+        format!("{min}:{sec:02}.{frac:03}")
+    }
+}
@@ -0,0 +1,121 @@
+use serde_json::{Map, Value, json};
+use sqlx::{PgTransaction, types::Json};
+
+use crate::filter::{FilterError, RootFilter};
+
+/// DB に保存するフィルタの形式の、現在のバージョン
+///
+/// 保存するフィルタの形式を変更する場合は、バージョンを上げ、
+/// `MIGRATIONS` に旧バージョンからの変換処理を追加する。
+pub const FILTER_SCHEMA_VERSION: u64 = 1;
+
+/// フィルタの形式の変換処理
+///
+/// `MIGRATIONS[n]` は、バージョン n のフィルタをバージョン n + 1 に変換する。
+/// 変換処理はバージョン情報を除いたフィルタ本体を受け取る。
+const MIGRATIONS: [fn(Value) -> Value; FILTER_SCHEMA_VERSION as usize] = [
+    // 0 -> 1: バージョン情報の追加のみで、フィルタ本体の形式は変わらない
+    |filter| filter,
+];
+
+/// フィルタを、DB に保存する JSON に変換
+///
+/// `{"version": バージョン, "filter": フィルタ}` の形式で保存する。
+pub fn to_json(filter: &RootFilter) -> Value {
+    json!({
+        "version": FILTER_SCHEMA_VERSION,
+        "filter": filter,
+    })
+}
+
+/// DB に保存された JSON から、フィルタを取得
+///
+/// 古いバージョンの形式であれば、現在の形式に変換してから deserialize する。
+/// バージョン情報の無い JSON は、フィルタ本体のみを保存していたバージョン 0 として扱う。
+pub fn from_json(value: Value) -> Result<RootFilter, FilterError> {
+    let (version, mut filter) = split_version(value)?;
+
+    if version > FILTER_SCHEMA_VERSION {
+        return Err(FilterError::UnsupportedSchemaVersion { version });
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        filter = migration(filter);
+    }
+
+    serde_json::from_value(filter).map_err(FilterError::FailedToDeserialize)
+}
+
+/// 保存された JSON が、現在のバージョンの形式か
+pub fn is_current(value: &Value) -> bool {
+    value.get("version").and_then(Value::as_u64) == Some(FILTER_SCHEMA_VERSION)
+}
+
+/// 保存された JSON を、バージョンとフィルタ本体に分割
+fn split_version(value: Value) -> Result<(u64, Value), FilterError> {
+    match value {
+        Value::Object(mut map) if map.contains_key("version") => {
+            let version = map
+                .remove("version")
+                .and_then(|v| v.as_u64())
+                .ok_or(FilterError::InvalidSchemaVersion)?;
+            let filter = map.remove("filter").unwrap_or(Value::Object(Map::new()));
+            Ok((version, filter))
+        }
+        value => Ok((0, value)),
+    }
+}
+
+/// 保存されている全てのフィルタを、現在のバージョンの形式に書き換える
+///
+/// プレイリストと検索プリセットのフィルタが対象。
+/// 変換できないフィルタがあればエラーとし、何も書き換えない (呼び出し元でロールバックする)。
+/// # Returns
+/// 書き換えたフィルタの数
+pub async fn migrate_stored_filters<'c>(tx: &mut PgTransaction<'c>) -> Result<u32, FilterError> {
+    let mut count = 0;
+
+    let playlists =
+        sqlx::query!("SELECT id, filter_json FROM playlists WHERE filter_json IS NOT NULL")
+            .fetch_all(&mut **tx)
+            .await?;
+    for row in playlists {
+        let Some(json) = row.filter_json else {
+            continue;
+        };
+        if is_current(&json) {
+            continue;
+        }
+
+        let filter = from_json(json)?;
+        sqlx::query!(
+            "UPDATE playlists SET filter_json = $1 WHERE id = $2",
+            Json(to_json(&filter)) as _,
+            row.id,
+        )
+        .execute(&mut **tx)
+        .await?;
+        count += 1;
+    }
+
+    let presets = sqlx::query!("SELECT id, filter_json FROM search_presets")
+        .fetch_all(&mut **tx)
+        .await?;
+    for row in presets {
+        if is_current(&row.filter_json) {
+            continue;
+        }
+
+        let filter = from_json(row.filter_json)?;
+        sqlx::query!(
+            "UPDATE search_presets SET filter_json = $1 WHERE id = $2",
+            Json(to_json(&filter)) as _,
+            row.id,
+        )
+        .execute(&mut **tx)
+        .await?;
+        count += 1;
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,21 @@
+/// フィルタ関連のエラー
+#[derive(thiserror::Error, Debug)]
+pub enum FilterError {
+    #[error("正規表現が不正です: {pattern}: {reason}")]
+    InvalidRegex { pattern: String, reason: String },
+
+    #[error("フィルタの deserialize に失敗しました: {}", .0)]
+    FailedToDeserialize(serde_json::Error),
+
+    #[error("対応していないフィルタの形式のバージョンです: version={version}")]
+    UnsupportedSchemaVersion { version: u64 },
+
+    #[error("フィルタの形式のバージョンが不正です")]
+    InvalidSchemaVersion,
+
+    #[error("曲が見つかりません: track_id={track_id}")]
+    TrackNotFound { track_id: i32 },
+
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+}
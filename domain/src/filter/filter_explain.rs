@@ -0,0 +1,126 @@
+use serde::Serialize;
+use sqlx::{PgTransaction, Postgres, QueryBuilder, Row};
+
+use crate::{
+    filter::{FilterContext, FilterError, FilterTarget, GroupOperand, RootFilter},
+    track_query::{TrackQueryError, playlist_query},
+};
+
+/// フィルタの各条件の判定結果
+///
+/// フィルタと同じ木構造で、条件・グループごとに曲が条件を満たしたかを保持する。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FilterExplanation {
+    /// グループの判定結果
+    Group {
+        op: GroupOperand,
+
+        /// グループの条件を満たしたか
+        ///
+        /// 条件を持たないグループは判定に使われないため None
+        matched: Option<bool>,
+
+        children: Vec<FilterExplanation>,
+    },
+
+    /// 単一の条件の判定結果
+    Clause {
+        target: FilterTarget,
+
+        /// 条件を満たしたか
+        matched: bool,
+    },
+}
+
+impl FilterExplanation {
+    /// 条件を満たしたか (条件を持たないグループは true)
+    pub fn matched(&self) -> bool {
+        match self {
+            FilterExplanation::Group { matched, .. } => matched.unwrap_or(true),
+            FilterExplanation::Clause { matched, .. } => *matched,
+        }
+    }
+}
+
+/// 曲がフィルタの各条件を満たすかを、DB の曲データで判定する
+///
+/// 検索時と同じ SQL の条件式を、条件・グループごとに1つのクエリで評価する。
+/// 結果が NULL になる条件は、検索時と同じく満たさないものとする。
+/// `InPlaylist` で参照しているプレイリストは、検索時と同じく先にリストアップしてから判定する。
+pub async fn explain<'c>(
+    tx: &mut PgTransaction<'c>,
+    filter: &RootFilter,
+    track_id: i32,
+    context: &FilterContext,
+) -> Result<FilterExplanation, TrackQueryError> {
+    filter.validate_regex()?;
+
+    playlist_query::prepare_referenced_playlists(tx, filter, *context).await?;
+
+    let mut targets = vec![];
+    collect_conditions(filter, &mut targets);
+
+    let mut builder = QueryBuilder::<Postgres>::new("SELECT tracks.id");
+    for target in &targets {
+        builder.push(", COALESCE(");
+        target.push_where_expression(&mut builder, context);
+        builder.push(", false)");
+    }
+    builder
+        .push(" FROM tracks WHERE tracks.id = ")
+        .push_bind(track_id);
+
+    let row = builder
+        .build()
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(FilterError::TrackNotFound { track_id })?;
+
+    let results = (1..=targets.len())
+        .map(|i| row.try_get::<bool, _>(i))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(build_explanation(filter, &mut results.into_iter()))
+}
+
+/// 条件を持つ条件・グループを、行きがけ順で取得
+fn collect_conditions<'a>(target: &'a FilterTarget, result: &mut Vec<&'a FilterTarget>) {
+    if target.has_condition() {
+        result.push(target);
+    }
+
+    if let FilterTarget::FilterGroup { children, .. } = target {
+        for child in children {
+            collect_conditions(child, result);
+        }
+    }
+}
+
+/// 行きがけ順の判定結果から、判定結果の木を組み立てる
+fn build_explanation(
+    target: &FilterTarget,
+    results: &mut impl Iterator<Item = bool>,
+) -> FilterExplanation {
+    //collect_conditions と同じ順で結果を取り出す
+    let matched = if target.has_condition() {
+        results.next()
+    } else {
+        None
+    };
+
+    match target {
+        FilterTarget::FilterGroup { op, children } => FilterExplanation::Group {
+            op: op.clone(),
+            matched,
+            children: children
+                .iter()
+                .map(|child| build_explanation(child, results))
+                .collect(),
+        },
+        target => FilterExplanation::Clause {
+            target: target.clone(),
+            matched: matched.unwrap_or(true),
+        },
+    }
+}
@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
 
 use crate::filter::{
-    ArtworkFilterRange, BoolFilterRange, DateFilterRange, GroupOperand, IntFilterRange,
-    StringFilterRange, TagsFilterRange, range_group,
+    ArtworkFilterRange, BoolFilterRange, DateFilterRange, FilterContext, FilterError,
+    FolderFilterRange, GroupOperand, IntFilterRange, PlaylistFilterRange, StringFilterRange,
+    TagsFilterRange, range_group,
 };
+use crate::track::TrackRecord;
 
 /// フィルタの対象の項目と、その項目に対応した条件情報
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -79,44 +82,257 @@ pub enum FilterTarget {
 
     /// サジェスト対象 (suggest_target)
     SuggestTarget { range: BoolFilterRange },
+
+    /// フォルダ (folder_paths.path)
+    Folder { range: FolderFilterRange },
+
+    /// プレイリストに含まれるか (playlist_tracks)
+    InPlaylist { range: PlaylistFilterRange },
 }
 
 impl FilterTarget {
-    /// SQL の WHERE で使用する条件式に変換
+    /// SQL の WHERE で使用する条件があるか
     ///
-    /// フィルタ条件が無い場合は None (空の Group しか無い場合)
-    pub fn where_expression(&self) -> Option<String> {
-        let some = match self {
+    /// 空の Group しか無い場合は false
+    pub fn has_condition(&self) -> bool {
+        match self {
+            FilterTarget::FilterGroup { children, .. } => {
+                children.iter().any(FilterTarget::has_condition)
+            }
+            _ => true,
+        }
+    }
+
+    /// フィルタ内の正規表現のパターンが正しいか確認
+    ///
+    /// SQL を実行する前に確認し、不正なパターンの場合はエラーを返す
+    pub fn validate_regex(&self) -> Result<(), FilterError> {
+        match self {
+            FilterTarget::FilterGroup { children, .. } => {
+                children.iter().try_for_each(FilterTarget::validate_regex)
+            }
+
+            FilterTarget::Genre { range }
+            | FilterTarget::Artist { range }
+            | FilterTarget::AlbumArtist { range }
+            | FilterTarget::Album { range }
+            | FilterTarget::Composer { range }
+            | FilterTarget::Title { range }
+            | FilterTarget::Memo { range }
+            | FilterTarget::MemoManage { range }
+            | FilterTarget::OriginalTrack { range } => range.validate_regex(),
+
+            _ => Ok(()),
+        }
+    }
+
+    /// フィルタ内の `InPlaylist` で参照しているプレイリストの ID
+    pub fn referenced_playlist_ids(&self) -> Vec<i32> {
+        match self {
+            FilterTarget::FilterGroup { children, .. } => children
+                .iter()
+                .flat_map(FilterTarget::referenced_playlist_ids)
+                .collect(),
+            FilterTarget::InPlaylist { range } => vec![range.playlist_id()],
+            _ => vec![],
+        }
+    }
+
+    /// 相対日付の条件を含むか
+    ///
+    /// 含む場合、日付が変わると検索結果が変わる
+    pub fn has_relative_date(&self) -> bool {
+        match self {
+            FilterTarget::FilterGroup { children, .. } => {
+                children.iter().any(FilterTarget::has_relative_date)
+            }
+            FilterTarget::ReleaseDate { range } | FilterTarget::EntryDate { range } => {
+                range.is_relative()
+            }
+            _ => false,
+        }
+    }
+
+    /// 条件を否定した条件を取得
+    ///
+    /// 否定の演算子が無い条件・グループは None
+    pub fn negate(self) -> Option<FilterTarget> {
+        Some(match self {
+            FilterTarget::FilterGroup { .. } => return None,
+
+            FilterTarget::Tags { range } => FilterTarget::Tags {
+                range: range.negate()?,
+            },
+            FilterTarget::Rating { range } => FilterTarget::Rating {
+                range: range.negate()?,
+            },
+            FilterTarget::Genre { range } => FilterTarget::Genre {
+                range: range.negate()?,
+            },
+            FilterTarget::Artist { range } => FilterTarget::Artist {
+                range: range.negate()?,
+            },
+            FilterTarget::AlbumArtist { range } => FilterTarget::AlbumArtist {
+                range: range.negate()?,
+            },
+            FilterTarget::Album { range } => FilterTarget::Album {
+                range: range.negate()?,
+            },
+            FilterTarget::Composer { range } => FilterTarget::Composer {
+                range: range.negate()?,
+            },
+            FilterTarget::Title { range } => FilterTarget::Title {
+                range: range.negate()?,
+            },
+            FilterTarget::Artwork { range } => FilterTarget::Artwork {
+                range: range.negate(),
+            },
+            FilterTarget::Duration { range } => FilterTarget::Duration {
+                range: range.negate()?,
+            },
+            FilterTarget::ReleaseDate { range } => FilterTarget::ReleaseDate {
+                range: range.negate()?,
+            },
+            FilterTarget::TrackNumber { range } => FilterTarget::TrackNumber {
+                range: range.negate()?,
+            },
+            FilterTarget::TrackMax { range } => FilterTarget::TrackMax {
+                range: range.negate()?,
+            },
+            FilterTarget::DiscNumber { range } => FilterTarget::DiscNumber {
+                range: range.negate()?,
+            },
+            FilterTarget::DiscMax { range } => FilterTarget::DiscMax {
+                range: range.negate()?,
+            },
+            FilterTarget::Memo { range } => FilterTarget::Memo {
+                range: range.negate()?,
+            },
+            FilterTarget::MemoManage { range } => FilterTarget::MemoManage {
+                range: range.negate()?,
+            },
+            FilterTarget::EntryDate { range } => FilterTarget::EntryDate {
+                range: range.negate()?,
+            },
+            FilterTarget::OriginalTrack { range } => FilterTarget::OriginalTrack {
+                range: range.negate()?,
+            },
+            FilterTarget::SuggestTarget { range } => FilterTarget::SuggestTarget {
+                range: range.negate(),
+            },
+            FilterTarget::Folder { range } => FilterTarget::Folder {
+                range: range.negate(),
+            },
+            FilterTarget::InPlaylist { range } => FilterTarget::InPlaylist {
+                range: range.negate(),
+            },
+        })
+    }
+
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    ///
+    /// フィルタの値は SQL 文に埋め込まず、bind 引数として追加する。
+    ///
+    /// フィルタ条件が無い場合 (`has_condition()` が false の場合) は何も追加しない
+    pub fn push_where_expression(
+        &self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        context: &FilterContext,
+    ) {
+        match self {
             FilterTarget::FilterGroup { op, children } => {
-                // Group の場合のみ Option を返すので early return
-                return range_group::group_where_expression(op, children);
-            }
-
-            FilterTarget::Tags { range } => range.where_expression(),
-            FilterTarget::Rating { range } => range.where_expression("rating"),
-            FilterTarget::Genre { range } => range.where_expression("genre"),
-            FilterTarget::Artist { range } => range.where_expression("artist"),
-            FilterTarget::AlbumArtist { range } => range.where_expression("album_artist"),
-            FilterTarget::Album { range } => range.where_expression("album"),
-            FilterTarget::Composer { range } => range.where_expression("composer"),
-            FilterTarget::Title { range } => range.where_expression("title"),
-            FilterTarget::Artwork { range } => range.where_expression(),
-            FilterTarget::Duration { range } => range.where_expression("duration"),
-            FilterTarget::ReleaseDate { range } => range.where_expression("release_date"),
-            FilterTarget::TrackNumber { range } => range.where_expression("track_number"),
-            FilterTarget::TrackMax { range } => range.where_expression("track_max"),
-            FilterTarget::DiscNumber { range } => range.where_expression("disc_number"),
-            FilterTarget::DiscMax { range } => range.where_expression("disc_max"),
-            FilterTarget::Memo { range } => range.where_expression("memo"),
-            FilterTarget::MemoManage { range } => range.where_expression("memo_manage"),
-
-            // TODO DateTime との比較は危なそう #17
-            FilterTarget::EntryDate { range } => range.where_expression("created_at"),
-
-            FilterTarget::OriginalTrack { range } => range.where_expression("original_track"),
-            FilterTarget::SuggestTarget { range } => range.where_expression("suggest_target"),
-        };
-
-        Some(some)
+                range_group::push_group_where_expression(builder, op, children, context)
+            }
+
+            FilterTarget::Tags { range } => range.push_where_expression(builder),
+            FilterTarget::Rating { range } => range.push_where_expression(builder, "rating"),
+            FilterTarget::Genre { range } => {
+                range.push_where_expression(builder, "genre", Some("genre_order"))
+            }
+            FilterTarget::Artist { range } => {
+                range.push_where_expression(builder, "artist", Some("artist_order"))
+            }
+            FilterTarget::AlbumArtist { range } => {
+                range.push_where_expression(builder, "album_artist", Some("album_artist_order"))
+            }
+            FilterTarget::Album { range } => {
+                range.push_where_expression(builder, "album", Some("album_order"))
+            }
+            FilterTarget::Composer { range } => {
+                range.push_where_expression(builder, "composer", Some("composer_order"))
+            }
+            FilterTarget::Title { range } => {
+                range.push_where_expression(builder, "title", Some("title_order"))
+            }
+            FilterTarget::Artwork { range } => range.push_where_expression(builder),
+            FilterTarget::Duration { range } => range.push_where_expression(builder, "duration"),
+            FilterTarget::ReleaseDate { range } => {
+                range.push_where_expression(builder, "release_date", context)
+            }
+            FilterTarget::TrackNumber { range } => {
+                range.push_where_expression(builder, "track_number")
+            }
+            FilterTarget::TrackMax { range } => range.push_where_expression(builder, "track_max"),
+            FilterTarget::DiscNumber { range } => {
+                range.push_where_expression(builder, "disc_number")
+            }
+            FilterTarget::DiscMax { range } => range.push_where_expression(builder, "disc_max"),
+            FilterTarget::Memo { range } => range.push_where_expression(builder, "memo", None),
+            FilterTarget::MemoManage { range } => {
+                range.push_where_expression(builder, "memo_manage", None)
+            }
+            FilterTarget::EntryDate { range } => {
+                range.push_date_time_where_expression(builder, "tracks.created_at", context)
+            }
+
+            FilterTarget::OriginalTrack { range } => {
+                range.push_where_expression(builder, "original_track", None)
+            }
+            FilterTarget::SuggestTarget { range } => {
+                range.push_where_expression(builder, "suggest_target")
+            }
+            FilterTarget::Folder { range } => range.push_where_expression(builder),
+            FilterTarget::InPlaylist { range } => range.push_where_expression(builder),
+        }
+    }
+
+    /// 曲データがフィルタ条件を満たすか判定
+    ///
+    /// `push_where_expression` で生成する SQL と同じ判定を、DB を使わずに行う。
+    pub fn matches(&self, track: &TrackRecord, context: &FilterContext) -> bool {
+        match self {
+            FilterTarget::FilterGroup { op, children } => {
+                range_group::group_matches(op, children, track, context)
+            }
+
+            FilterTarget::Tags { range } => range.matches(&track.tag_ids, &track.tag_group_ids),
+            FilterTarget::Rating { range } => range.matches(Some(track.rating.into())),
+            FilterTarget::Genre { range } => range.matches(&track.genre, Some(&track.genre_order)),
+            FilterTarget::Artist { range } => {
+                range.matches(&track.artist, Some(&track.artist_order))
+            }
+            FilterTarget::AlbumArtist { range } => {
+                range.matches(&track.album_artist, Some(&track.album_artist_order))
+            }
+            FilterTarget::Album { range } => range.matches(&track.album, Some(&track.album_order)),
+            FilterTarget::Composer { range } => {
+                range.matches(&track.composer, Some(&track.composer_order))
+            }
+            FilterTarget::Title { range } => range.matches(&track.title, Some(&track.title_order)),
+            FilterTarget::Artwork { range } => range.matches(track.has_artwork),
+            FilterTarget::Duration { range } => range.matches(track.duration.as_i32_millis().ok()),
+            FilterTarget::ReleaseDate { range } => range.matches(track.release_date, context),
+            FilterTarget::TrackNumber { range } => range.matches(track.track_number),
+            FilterTarget::TrackMax { range } => range.matches(track.track_max),
+            FilterTarget::DiscNumber { range } => range.matches(track.disc_number),
+            FilterTarget::DiscMax { range } => range.matches(track.disc_max),
+            FilterTarget::Memo { range } => range.matches(&track.memo, None),
+            FilterTarget::MemoManage { range } => range.matches(&track.memo_manage, None),
+            FilterTarget::EntryDate { range } => range.matches_date_time(track.created_at, context),
+            FilterTarget::OriginalTrack { range } => range.matches(&track.original_track, None),
+            FilterTarget::SuggestTarget { range } => range.matches(track.suggest_target),
+            FilterTarget::Folder { range } => range.matches(track.folder_path.as_ref()),
+            FilterTarget::InPlaylist { range } => range.matches(&track.playlist_ids),
+        }
     }
 }
@@ -0,0 +1,417 @@
+use std::fmt;
+
+use sqlx::PgTransaction;
+
+use crate::{
+    filter::{
+        FilterError, FilterTarget, FolderFilterRange, GroupOperand, IntFilterRange, RootFilter,
+        TagsFilterRange,
+    },
+    path::LibraryDirectoryPath,
+};
+
+/// フィルタの検証結果
+#[derive(Debug, PartialEq)]
+pub struct FilterValidation {
+    /// 正規化したフィルタ
+    pub filter: RootFilter,
+
+    /// 警告
+    ///
+    /// 警告の位置は、正規化後のフィルタでの位置
+    pub warnings: Vec<FilterWarning>,
+}
+
+/// フィルタの警告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterWarning {
+    /// 警告の対象の条件の位置
+    ///
+    /// 最上位から、FilterGroup の children のインデックスを順に並べたもの
+    pub path: Vec<usize>,
+
+    /// 警告の内容
+    pub kind: FilterWarningKind,
+}
+
+/// フィルタの警告の内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterWarningKind {
+    /// 範囲の min が max より大きい
+    ///
+    /// 検索時は min と max を入れ替えて扱われるため、正規化時に入れ替える
+    ReversedRange { min: i32, max: i32 },
+
+    /// 同じ AND グループ内に、この条件と同時に満たせない条件がある
+    ///
+    /// この条件を否定した条件や、範囲が重ならない数値・日付の条件など。
+    /// 条件を満たす曲は存在しない
+    Contradiction { other: Vec<usize> },
+
+    /// 存在しないタグ ID
+    UnknownTag { tag_id: i32 },
+
+    /// 存在しないタググループ ID
+    UnknownTagGroup { group_id: i32 },
+
+    /// 存在しないプレイリスト ID
+    UnknownPlaylist { playlist_id: i32 },
+
+    /// 存在しないフォルダ
+    UnknownFolder { path: LibraryDirectoryPath },
+}
+
+impl fmt::Display for FilterWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", display_path(&self.path), self.kind)
+    }
+}
+
+impl fmt::Display for FilterWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReversedRange { min, max } => {
+                write!(f, "範囲の最小値と最大値が逆です: {min}..{max}")
+            }
+            Self::Contradiction { other } => {
+                write!(
+                    f,
+                    "{} の条件と矛盾するため、条件を満たす曲がありません",
+                    display_path(other)
+                )
+            }
+            Self::UnknownTag { tag_id } => write!(f, "存在しないタグです: tag_id={tag_id}"),
+            Self::UnknownTagGroup { group_id } => {
+                write!(f, "存在しないタググループです: group_id={group_id}")
+            }
+            Self::UnknownPlaylist { playlist_id } => {
+                write!(f, "存在しないプレイリストです: playlist_id={playlist_id}")
+            }
+            Self::UnknownFolder { path } => write!(f, "存在しないフォルダです: {path}"),
+        }
+    }
+}
+
+fn display_path(path: &[usize]) -> String {
+    let v: Vec<String> = path.iter().map(|i| i.to_string()).collect();
+    format!("[{}]", v.join(", "))
+}
+
+/// フィルタを検証し、正規化する
+///
+/// 正規表現が不正な場合はエラーを返す。
+/// 条件の矛盾や存在しないタグなど、保存は可能だが意図通りに動作しない可能性がある内容は警告として返す。
+pub async fn validate<'c>(
+    tx: &mut PgTransaction<'c>,
+    filter: RootFilter,
+) -> Result<FilterValidation, FilterError> {
+    filter.validate_regex()?;
+
+    let (filter, mut warnings) = normalize(filter);
+
+    //タグ ID の存在確認
+    //複数のタグを指定する条件は、タグごとに確認する
+    let tag_conditions: Vec<_> = collect_conditions(&filter, &|target| match target {
+        FilterTarget::Tags { range } => Some(range.tag_ids().to_vec()),
+        _ => None,
+    })
+    .into_iter()
+    .flat_map(|(path, tag_ids)| tag_ids.into_iter().map(move |id| (path.clone(), id)))
+    .collect();
+    if !tag_conditions.is_empty() {
+        let tag_ids: Vec<i32> = tag_conditions.iter().map(|(_, id)| *id).collect();
+        let exist_ids = sqlx::query_scalar!("SELECT id FROM tags WHERE id = ANY($1)", &tag_ids)
+            .fetch_all(&mut **tx)
+            .await?;
+
+        warnings.extend(
+            tag_conditions
+                .into_iter()
+                .filter(|(_, tag_id)| !exist_ids.contains(tag_id))
+                .map(|(path, tag_id)| FilterWarning {
+                    path,
+                    kind: FilterWarningKind::UnknownTag { tag_id },
+                }),
+        );
+    }
+
+    //タググループ ID の存在確認
+    let group_conditions = collect_conditions(&filter, &|target| match target {
+        FilterTarget::Tags {
+            range: TagsFilterRange::GroupAny { group_id } | TagsFilterRange::GroupNone { group_id },
+        } => Some(*group_id),
+        _ => None,
+    });
+    if !group_conditions.is_empty() {
+        let group_ids: Vec<i32> = group_conditions.iter().map(|(_, id)| *id).collect();
+        let exist_ids =
+            sqlx::query_scalar!("SELECT id FROM tag_groups WHERE id = ANY($1)", &group_ids)
+                .fetch_all(&mut **tx)
+                .await?;
+
+        warnings.extend(
+            group_conditions
+                .into_iter()
+                .filter(|(_, group_id)| !exist_ids.contains(group_id))
+                .map(|(path, group_id)| FilterWarning {
+                    path,
+                    kind: FilterWarningKind::UnknownTagGroup { group_id },
+                }),
+        );
+    }
+
+    //プレイリスト ID の存在確認
+    let playlist_conditions = collect_conditions(&filter, &|target| match target {
+        FilterTarget::InPlaylist { range } => Some(range.playlist_id()),
+        _ => None,
+    });
+    if !playlist_conditions.is_empty() {
+        let playlist_ids: Vec<i32> = playlist_conditions.iter().map(|(_, id)| *id).collect();
+        let exist_ids =
+            sqlx::query_scalar!("SELECT id FROM playlists WHERE id = ANY($1)", &playlist_ids)
+                .fetch_all(&mut **tx)
+                .await?;
+
+        warnings.extend(
+            playlist_conditions
+                .into_iter()
+                .filter(|(_, playlist_id)| !exist_ids.contains(playlist_id))
+                .map(|(path, playlist_id)| FilterWarning {
+                    path,
+                    kind: FilterWarningKind::UnknownPlaylist { playlist_id },
+                }),
+        );
+    }
+
+    //フォルダの存在確認
+    let folder_conditions = collect_conditions(&filter, &|target| match target {
+        FilterTarget::Folder {
+            range: FolderFilterRange::In { value, .. } | FolderFilterRange::NotIn { value, .. },
+        } => Some(value.clone()),
+        _ => None,
+    });
+    if !folder_conditions.is_empty() {
+        let paths: Vec<String> = folder_conditions
+            .iter()
+            .map(|(_, path)| path.to_string())
+            .collect();
+        let exist_paths = sqlx::query_scalar!(
+            r#"SELECT path AS "path: LibraryDirectoryPath" FROM folder_paths WHERE path = ANY($1)"#,
+            &paths
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        warnings.extend(
+            folder_conditions
+                .into_iter()
+                .filter(|(_, path)| !exist_paths.contains(path))
+                .map(|(path, folder)| FilterWarning {
+                    path,
+                    kind: FilterWarningKind::UnknownFolder { path: folder },
+                }),
+        );
+    }
+
+    Ok(FilterValidation { filter, warnings })
+}
+
+/// フィルタを正規化する
+///
+/// - 空のグループを削除
+/// - 親と同じ演算子のグループを、親のグループに展開 (`And` / `Or` のみ)
+/// - 子要素が1つのグループを、その子要素に置き換え (`And` / `Or` のみ)
+/// - 範囲の min と max が逆の場合、入れ替え
+///
+/// 検索結果が変わらない範囲で変換する。
+/// 最上位がグループの場合、最上位はグループのまま残す。
+pub fn normalize(filter: RootFilter) -> (RootFilter, Vec<FilterWarning>) {
+    let mut filter = match filter {
+        FilterTarget::FilterGroup { op, children } => {
+            let mut children = normalize_children(&op, children);
+
+            //子要素がグループ1つだけなら、そのグループを最上位とする
+            if !op.is_negated()
+                && let [FilterTarget::FilterGroup { .. }] = children.as_slice()
+            {
+                children.pop().unwrap()
+            } else {
+                FilterTarget::FilterGroup { op, children }
+            }
+        }
+        target => target,
+    };
+
+    let mut warnings = vec![];
+    check_conditions(&mut filter, &mut vec![], &mut warnings);
+
+    (filter, warnings)
+}
+
+/// グループの子要素を正規化
+fn normalize_children(op: &GroupOperand, children: Vec<FilterTarget>) -> Vec<FilterTarget> {
+    let mut result = vec![];
+
+    for child in children {
+        match normalize_child(child) {
+            //空のグループは削除
+            None => {}
+            //同じ演算子のグループは展開
+            //否定のグループは、展開すると結果が変わるため展開しない
+            Some(FilterTarget::FilterGroup {
+                op: child_op,
+                children: grandchildren,
+            }) if child_op == *op && !op.is_negated() => result.extend(grandchildren),
+            Some(child) => result.push(child),
+        }
+    }
+
+    result
+}
+
+/// グループの子要素を正規化
+///
+/// 空のグループの場合は None
+fn normalize_child(target: FilterTarget) -> Option<FilterTarget> {
+    match target {
+        FilterTarget::FilterGroup { op, children } => {
+            let mut children = normalize_children(&op, children);
+            match children.len() {
+                0 => None,
+                1 if !op.is_negated() => children.pop(),
+                _ => Some(FilterTarget::FilterGroup { op, children }),
+            }
+        }
+        target => Some(target),
+    }
+}
+
+/// 範囲の修正と、条件の矛盾の確認
+fn check_conditions(
+    target: &mut FilterTarget,
+    path: &mut Vec<usize>,
+    warnings: &mut Vec<FilterWarning>,
+) {
+    match target {
+        FilterTarget::FilterGroup { op, children } => {
+            for (i, child) in children.iter_mut().enumerate() {
+                path.push(i);
+                check_conditions(child, path, warnings);
+                path.pop();
+            }
+
+            if *op == GroupOperand::And {
+                check_contradiction(children, path, warnings);
+            }
+        }
+
+        FilterTarget::Rating { range }
+        | FilterTarget::Duration { range }
+        | FilterTarget::TrackNumber { range }
+        | FilterTarget::TrackMax { range }
+        | FilterTarget::DiscNumber { range }
+        | FilterTarget::DiscMax { range } => {
+            if let IntFilterRange::RangeIn { min, max } | IntFilterRange::RangeOut { min, max, .. } =
+                range
+                && *min > *max
+            {
+                warnings.push(FilterWarning {
+                    path: path.clone(),
+                    kind: FilterWarningKind::ReversedRange {
+                        min: *min,
+                        max: *max,
+                    },
+                });
+                std::mem::swap(min, max);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// AND グループの子要素に、同時に満たせない条件の組があるか確認
+fn check_contradiction(
+    children: &[FilterTarget],
+    path: &[usize],
+    warnings: &mut Vec<FilterWarning>,
+) {
+    for (i, child) in children.iter().enumerate() {
+        for (j, other) in children.iter().enumerate().skip(i + 1) {
+            if is_contradiction(child, other) {
+                warnings.push(FilterWarning {
+                    path: [path, &[j]].concat(),
+                    kind: FilterWarningKind::Contradiction {
+                        other: [path, &[i]].concat(),
+                    },
+                });
+            }
+        }
+    }
+}
+
+/// 2つの条件を同時に満たす曲が存在しないか
+///
+/// - 一方が他方を否定した条件 (`include_null` の有無は問わない)
+/// - 同じ項目の数値・日付の範囲が重ならない
+fn is_contradiction(a: &FilterTarget, b: &FilterTarget) -> bool {
+    //否定した条件は include_null を含まないため、双方向に比較する
+    if a.clone().negate().as_ref() == Some(b) || b.clone().negate().as_ref() == Some(a) {
+        return true;
+    }
+
+    match (a, b) {
+        (FilterTarget::Rating { range: a }, FilterTarget::Rating { range: b })
+        | (FilterTarget::Duration { range: a }, FilterTarget::Duration { range: b })
+        | (FilterTarget::TrackNumber { range: a }, FilterTarget::TrackNumber { range: b })
+        | (FilterTarget::TrackMax { range: a }, FilterTarget::TrackMax { range: b })
+        | (FilterTarget::DiscNumber { range: a }, FilterTarget::DiscNumber { range: b })
+        | (FilterTarget::DiscMax { range: a }, FilterTarget::DiscMax { range: b }) => {
+            is_disjoint(a.bounds(), b.bounds())
+        }
+        (FilterTarget::ReleaseDate { range: a }, FilterTarget::ReleaseDate { range: b })
+        | (FilterTarget::EntryDate { range: a }, FilterTarget::EntryDate { range: b }) => {
+            is_disjoint(a.bounds(), b.bounds())
+        }
+        _ => false,
+    }
+}
+
+/// 2つの範囲 (両端を含む) が重ならないか
+///
+/// どちらかの範囲が求められない場合は false
+fn is_disjoint<T: Ord>(a: Option<(T, T)>, b: Option<(T, T)>) -> bool {
+    match (a, b) {
+        (Some((a_min, a_max)), Some((b_min, b_max))) => a_max < b_min || b_max < a_min,
+        _ => false,
+    }
+}
+
+/// フィルタ内の条件から値を取り出し、条件の位置と共に取得
+///
+/// f: 条件から値を取り出す関数。対象外の条件では None を返す
+pub(super) fn collect_conditions<T>(
+    filter: &FilterTarget,
+    f: &impl Fn(&FilterTarget) -> Option<T>,
+) -> Vec<(Vec<usize>, T)> {
+    fn collect<T>(
+        target: &FilterTarget,
+        f: &impl Fn(&FilterTarget) -> Option<T>,
+        path: &mut Vec<usize>,
+        result: &mut Vec<(Vec<usize>, T)>,
+    ) {
+        if let FilterTarget::FilterGroup { children, .. } = target {
+            for (i, child) in children.iter().enumerate() {
+                path.push(i);
+                collect(child, f, path, result);
+                path.pop();
+            }
+        } else if let Some(value) = f(target) {
+            result.push((path.clone(), value));
+        }
+    }
+
+    let mut result = vec![];
+    collect(filter, f, &mut vec![], &mut result);
+    result
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
 
 /// アートワークで絞り込み
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -12,16 +13,32 @@ pub enum ArtworkFilterRange {
 }
 
 impl ArtworkFilterRange {
-    /// SQL の WHERE で使用する条件式に変換
-    pub fn where_expression(&self) -> String {
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    pub fn push_where_expression(&self, builder: &mut QueryBuilder<'_, Postgres>) {
         //存在すればtrueのsql
         let base_sql = "EXISTS(SELECT * FROM track_artworks AS a WHERE a.track_id = tracks.id)";
 
         match self {
             //アートワーク：ある
-            ArtworkFilterRange::Has => base_sql.to_owned(),
+            ArtworkFilterRange::Has => builder.push(base_sql),
             //アートワーク：ない
-            ArtworkFilterRange::None => format!("NOT {base_sql}"),
+            ArtworkFilterRange::None => builder.push("NOT ").push(base_sql),
+        };
+    }
+
+    /// アートワークの有無が条件を満たすか判定
+    pub fn matches(&self, has_artwork: bool) -> bool {
+        match self {
+            ArtworkFilterRange::Has => has_artwork,
+            ArtworkFilterRange::None => !has_artwork,
+        }
+    }
+
+    /// 条件を否定した条件を取得
+    pub fn negate(self) -> Self {
+        match self {
+            ArtworkFilterRange::Has => ArtworkFilterRange::None,
+            ArtworkFilterRange::None => ArtworkFilterRange::Has,
         }
     }
 }
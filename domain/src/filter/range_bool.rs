@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
 
 /// bool で絞り込み
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -12,13 +13,33 @@ pub enum BoolFilterRange {
 }
 
 impl BoolFilterRange {
-    /// SQL の WHERE で使用する条件式に変換
-    pub fn where_expression(&self, column_name: &str) -> String {
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    pub fn push_where_expression(
+        &self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        column_name: &str,
+    ) {
         match self {
             //true
-            BoolFilterRange::True => format!("{column_name} = true"),
+            BoolFilterRange::True => builder.push(column_name).push(" = true"),
             //false
-            BoolFilterRange::False => format!("{column_name} = false"),
+            BoolFilterRange::False => builder.push(column_name).push(" = false"),
+        };
+    }
+
+    /// フラグが条件を満たすか判定
+    pub fn matches(&self, target: bool) -> bool {
+        match self {
+            BoolFilterRange::True => target,
+            BoolFilterRange::False => !target,
+        }
+    }
+
+    /// 条件を否定した条件を取得
+    pub fn negate(self) -> Self {
+        match self {
+            BoolFilterRange::True => BoolFilterRange::False,
+            BoolFilterRange::False => BoolFilterRange::True,
         }
     }
 }
@@ -1,9 +1,12 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, Days, Months, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
 
-use crate::db_utils::escs;
+use crate::filter::{FilterContext, range_int::push_or_null, range_string::is_false};
 
 /// 日付で絞り込み
+///
+/// `WithinDays` などの相対日付の条件は、検索時の `FilterContext::today` を基準に判定する。
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "op", rename_all = "snake_case")]
 pub enum DateFilterRange {
@@ -11,7 +14,13 @@ pub enum DateFilterRange {
     Equal { value: NaiveDate },
 
     /// 日付：指定値と異なる
-    NotEqual { value: NaiveDate },
+    ///
+    /// `include_null` を指定すると、日付がない曲も含む
+    NotEqual {
+        value: NaiveDate,
+        #[serde(default, skip_serializing_if = "is_false")]
+        include_null: bool,
+    },
 
     /// 日付：指定値以前
     Before { value: NaiveDate },
@@ -21,35 +30,261 @@ pub enum DateFilterRange {
 
     /// 日付：ない
     None,
+
+    /// 日付：ある
+    NotNull,
+
+    /// 日付：今日から指定日数前以後
+    WithinDays { value: u32 },
+
+    /// 日付：今日から指定月数前以後
+    WithinMonths { value: u32 },
+
+    /// 日付：今日から指定日数前より前
+    OlderThanDays { value: u32 },
+
+    /// 日付：今日から指定月数前より前
+    OlderThanMonths { value: u32 },
 }
 
 impl DateFilterRange {
-    /// SQL の WHERE で使用する条件式に変換
-    pub fn where_expression(&self, column_name: &str) -> String {
-        match self {
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    ///
+    /// 日付型のカラムを対象とする
+    pub fn push_where_expression(
+        &self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        column_name: &str,
+        context: &FilterContext,
+    ) {
+        match self.resolve(context.today) {
             //指定値と等しい
             DateFilterRange::Equal { value } => {
-                format!("{} = {}", column_name, escs(&date_to_str(value)))
+                builder.push(column_name).push(" = ").push_bind(value);
             }
             //指定値と等しくない
-            //※nullは含めない仕様(WalkBase1がそうなっていたので)
-            DateFilterRange::NotEqual { value } => {
-                format!("{} <> {}", column_name, escs(&date_to_str(value)))
+            //※nullは含めない仕様(WalkBase1がそうなっていたので)。include_null 指定時のみ含める
+            DateFilterRange::NotEqual {
+                value,
+                include_null,
+            } => {
+                builder
+                    .push("(")
+                    .push(column_name)
+                    .push(" <> ")
+                    .push_bind(value);
+                push_or_null(builder, column_name, include_null);
+                builder.push(")");
             }
             //指定値以前
             DateFilterRange::Before { value } => {
-                format!("{} <= {}", column_name, escs(&date_to_str(value)))
+                builder.push(column_name).push(" <= ").push_bind(value);
             }
             //指定値以後
             DateFilterRange::After { value } => {
-                format!("{} >= {}", column_name, escs(&date_to_str(value)))
+                builder.push(column_name).push(" >= ").push_bind(value);
             }
             //なし
-            DateFilterRange::None => format!("{column_name} is null"),
+            DateFilterRange::None => {
+                builder.push(column_name).push(" is null");
+            }
+            //あり
+            DateFilterRange::NotNull => {
+                builder.push(column_name).push(" is not null");
+            }
+            _ => unreachable!("resolve() で相対日付は変換済み"),
+        }
+    }
+
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    ///
+    /// 日時型 (timestamptz) のカラムを対象とし、`context.time_zone` での日付として比較する。
+    /// 日付の境界は、その日の 0 時の日時に変換して比較する。
+    pub fn push_date_time_where_expression(
+        &self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        column_name: &str,
+        context: &FilterContext,
+    ) {
+        //指定日の開始日時と、翌日の開始日時
+        let day_range = |date: NaiveDate| {
+            let next = date.succ_opt().unwrap_or(NaiveDate::MAX);
+            (context.day_start(date), context.day_start(next))
+        };
+
+        match self.resolve(context.today) {
+            DateFilterRange::Equal { value } => {
+                let (start, end) = day_range(value);
+                builder
+                    .push("(")
+                    .push(column_name)
+                    .push(" >= ")
+                    .push_bind(start)
+                    .push(" and ")
+                    .push(column_name)
+                    .push(" < ")
+                    .push_bind(end)
+                    .push(")");
+            }
+            DateFilterRange::NotEqual {
+                value,
+                include_null,
+            } => {
+                let (start, end) = day_range(value);
+                builder
+                    .push("(")
+                    .push(column_name)
+                    .push(" < ")
+                    .push_bind(start)
+                    .push(" or ")
+                    .push(column_name)
+                    .push(" >= ")
+                    .push_bind(end);
+                push_or_null(builder, column_name, include_null);
+                builder.push(")");
+            }
+            DateFilterRange::Before { value } => {
+                let (_, end) = day_range(value);
+                builder.push(column_name).push(" < ").push_bind(end);
+            }
+            DateFilterRange::After { value } => {
+                builder
+                    .push(column_name)
+                    .push(" >= ")
+                    .push_bind(context.day_start(value));
+            }
+            DateFilterRange::None => {
+                builder.push(column_name).push(" is null");
+            }
+            DateFilterRange::NotNull => {
+                builder.push(column_name).push(" is not null");
+            }
+            _ => unreachable!("resolve() で相対日付は変換済み"),
+        }
+    }
+
+    /// 日付が条件を満たすか判定
+    ///
+    /// `push_where_expression` で生成する SQL と同じく、値が NULL (None) の場合は
+    /// `DateFilterRange::None` と `include_null` を指定した条件のみ true
+    pub fn matches(&self, target: Option<NaiveDate>, context: &FilterContext) -> bool {
+        let range = self.resolve(context.today);
+
+        let Some(target) = target else {
+            return match range {
+                DateFilterRange::None => true,
+                DateFilterRange::NotEqual { include_null, .. } => include_null,
+                _ => false,
+            };
+        };
+
+        match range {
+            DateFilterRange::Equal { value } => target == value,
+            DateFilterRange::NotEqual { value, .. } => target != value,
+            DateFilterRange::Before { value } => target <= value,
+            DateFilterRange::After { value } => target >= value,
+            DateFilterRange::None => false,
+            DateFilterRange::NotNull => true,
+            _ => unreachable!("resolve() で相対日付は変換済み"),
+        }
+    }
+
+    /// 日時が条件を満たすか判定
+    ///
+    /// SQL と同じく、`context.time_zone` での日付として比較する。
+    pub fn matches_date_time(&self, target: DateTime<Utc>, context: &FilterContext) -> bool {
+        self.matches(Some(context.date_of(target)), context)
+    }
+
+    /// 相対日付の条件か
+    pub fn is_relative(&self) -> bool {
+        matches!(
+            self,
+            DateFilterRange::WithinDays { .. }
+                | DateFilterRange::WithinMonths { .. }
+                | DateFilterRange::OlderThanDays { .. }
+                | DateFilterRange::OlderThanMonths { .. }
+        )
+    }
+
+    /// 相対日付の条件を、today を基準とした絶対日付の条件に変換
+    ///
+    /// 相対日付以外の条件はそのまま返す
+    pub fn resolve(&self, today: NaiveDate) -> DateFilterRange {
+        let days_ago = |days: u32| {
+            today
+                .checked_sub_days(Days::new(days.into()))
+                .unwrap_or(NaiveDate::MIN)
+        };
+        let months_ago = |months: u32| {
+            today
+                .checked_sub_months(Months::new(months))
+                .unwrap_or(NaiveDate::MIN)
+        };
+        //指定日より前 = 前日以前
+        let before = |date: NaiveDate| DateFilterRange::Before {
+            value: date.pred_opt().unwrap_or(NaiveDate::MIN),
+        };
+
+        match self {
+            DateFilterRange::WithinDays { value } => DateFilterRange::After {
+                value: days_ago(*value),
+            },
+            DateFilterRange::WithinMonths { value } => DateFilterRange::After {
+                value: months_ago(*value),
+            },
+            DateFilterRange::OlderThanDays { value } => before(days_ago(*value)),
+            DateFilterRange::OlderThanMonths { value } => before(months_ago(*value)),
+            range => range.clone(),
+        }
+    }
+
+    /// 条件を満たす日付の範囲 (両端を含む)
+    ///
+    /// 検索時の基準日で変わる相対日付の条件や、連続した範囲にならない条件は None
+    pub fn bounds(&self) -> Option<(NaiveDate, NaiveDate)> {
+        match self {
+            DateFilterRange::Equal { value } => Some((*value, *value)),
+            DateFilterRange::Before { value } => Some((NaiveDate::MIN, *value)),
+            DateFilterRange::After { value } => Some((*value, NaiveDate::MAX)),
+            _ => None,
+        }
+    }
+
+    /// 条件を否定した条件を取得
+    ///
+    /// NULL の扱いが変わってしまうため、前後の比較と相対日付は否定できない (None)
+    pub fn negate(self) -> Option<Self> {
+        match self {
+            DateFilterRange::Equal { value } => Some(DateFilterRange::NotEqual {
+                value,
+                include_null: false,
+            }),
+            DateFilterRange::NotEqual { value, .. } => Some(DateFilterRange::Equal { value }),
+            DateFilterRange::None => Some(DateFilterRange::NotNull),
+            DateFilterRange::NotNull => Some(DateFilterRange::None),
+            _ => None,
         }
     }
 }
 
-fn date_to_str(date: &NaiveDate) -> String {
-    date.format("%Y-%m-%d").to_string()
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use test_case::test_case;
+
+    use super::DateFilterRange;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test_case(DateFilterRange::WithinDays { value: 7 }, DateFilterRange::After { value: date(2024, 3, 24) } ; "within_days")]
+    #[test_case(DateFilterRange::WithinMonths { value: 1 }, DateFilterRange::After { value: date(2024, 2, 29) } ; "within_months_end_of_month")]
+    #[test_case(DateFilterRange::OlderThanDays { value: 0 }, DateFilterRange::Before { value: date(2024, 3, 30) } ; "older_than_today")]
+    #[test_case(DateFilterRange::OlderThanMonths { value: 12 }, DateFilterRange::Before { value: date(2023, 3, 30) } ; "older_than_months")]
+    #[test_case(DateFilterRange::None, DateFilterRange::None ; "absolute")]
+    fn test_resolve(range: DateFilterRange, expected: DateFilterRange) {
+        assert_eq!(range.resolve(date(2024, 3, 31)), expected);
+    }
 }
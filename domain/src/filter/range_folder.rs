@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::{db_utils::like_esc, filter::range_string::is_false, path::LibraryDirectoryPath};
+
+/// ライブラリ内のフォルダで絞り込み
+///
+/// `recursive` を指定すると、サブフォルダ内の曲も対象とする。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FolderFilterRange {
+    /// 指定フォルダ内の曲
+    In {
+        value: LibraryDirectoryPath,
+        #[serde(default, skip_serializing_if = "is_false")]
+        recursive: bool,
+    },
+
+    /// 指定フォルダ外の曲
+    NotIn {
+        value: LibraryDirectoryPath,
+        #[serde(default, skip_serializing_if = "is_false")]
+        recursive: bool,
+    },
+}
+
+impl FolderFilterRange {
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    pub fn push_where_expression(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        //フォルダで検索するクエリを追加する関数
+        fn push_query_where_by_folder(
+            builder: &mut QueryBuilder<'_, Postgres>,
+            path: &LibraryDirectoryPath,
+            recursive: bool,
+        ) {
+            builder.push(
+                "EXISTS(SELECT * FROM folder_paths AS f WHERE f.id = tracks.folder_id AND f.path ",
+            );
+
+            if recursive {
+                //ディレクトリのパスは / で終わるため、前方一致でサブフォルダも対象になる
+                let path: &str = path.as_ref();
+                builder
+                    .push("LIKE ")
+                    .push_bind(format!("{}%", like_esc::escape(path)))
+                    .push(" escape '$'");
+            } else {
+                builder.push("= ").push_bind(path.clone());
+            }
+
+            builder.push(")");
+        }
+
+        match self {
+            FolderFilterRange::In { value, recursive } => {
+                push_query_where_by_folder(builder, value, *recursive)
+            }
+            FolderFilterRange::NotIn { value, recursive } => {
+                builder.push("NOT ");
+                push_query_where_by_folder(builder, value, *recursive);
+            }
+        }
+    }
+
+    /// 曲のフォルダのパスが条件を満たすか判定
+    ///
+    /// folder_path: 曲のフォルダのパス (ライブラリ直下の曲の場合は None)
+    pub fn matches(&self, folder_path: Option<&LibraryDirectoryPath>) -> bool {
+        fn is_in(
+            folder_path: Option<&LibraryDirectoryPath>,
+            path: &LibraryDirectoryPath,
+            recursive: bool,
+        ) -> bool {
+            let Some(folder_path) = folder_path else {
+                return false;
+            };
+
+            if recursive {
+                let folder_path: &str = folder_path.as_ref();
+                folder_path.starts_with::<&str>(path.as_ref())
+            } else {
+                folder_path == path
+            }
+        }
+
+        match self {
+            FolderFilterRange::In { value, recursive } => is_in(folder_path, value, *recursive),
+            FolderFilterRange::NotIn { value, recursive } => !is_in(folder_path, value, *recursive),
+        }
+    }
+
+    /// 条件を否定した条件を取得
+    pub fn negate(self) -> Self {
+        match self {
+            FolderFilterRange::In { value, recursive } => {
+                FolderFilterRange::NotIn { value, recursive }
+            }
+            FolderFilterRange::NotIn { value, recursive } => {
+                FolderFilterRange::In { value, recursive }
+            }
+        }
+    }
+}
@@ -1,31 +1,80 @@
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
 
-use crate::filter::FilterTarget;
+use crate::{
+    filter::{FilterContext, FilterTarget},
+    track::TrackRecord,
+};
 
-/// `FilterTarget::FilterGroup` を、SQL の WHERE で使用する条件式に変換
+/// `FilterTarget::FilterGroup` の、SQL の WHERE で使用する条件式を QueryBuilder に追加
 ///
-/// フィルタ条件が無い場合は None (空の Group しか無い場合)
-pub fn group_where_expression(op: &GroupOperand, children: &[FilterTarget]) -> Option<String> {
-    //各フィルタのクエリを連結
-    let expressions = children
-        .iter()
-        .filter_map(FilterTarget::where_expression)
-        .collect::<Vec<String>>();
-
-    if expressions.is_empty() {
-        return None;
+/// フィルタ条件が無い場合 (空の Group しか無い場合) は何も追加しない
+pub fn push_group_where_expression(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    op: &GroupOperand,
+    children: &[FilterTarget],
+    context: &FilterContext,
+) {
+    //条件を持つ子フィルタのみ対象とする
+    let mut children = children.iter().filter(|c| c.has_condition()).peekable();
+
+    if children.peek().is_none() {
+        return;
     }
 
     let ope = match op {
-        GroupOperand::And => " and ",
-        GroupOperand::Or => " or ",
+        GroupOperand::And | GroupOperand::Not | GroupOperand::Nand => " and ",
+        GroupOperand::Or | GroupOperand::Nor => " or ",
     };
 
+    //否定する場合、NULL を偽として扱ってから否定する
+    //(group_matches と同じく、NULL になる条件は満たさないものとする)
+    if op.is_negated() {
+        builder.push("not coalesce(");
+    }
+
     //クエリ文字列は()で囲む
-    Some(format!("({})", expressions.join(ope)))
+    builder.push("(");
+    for (i, child) in children.enumerate() {
+        if i > 0 {
+            builder.push(ope);
+        }
+        child.push_where_expression(builder, context);
+    }
+    builder.push(")");
+
+    if op.is_negated() {
+        builder.push(", false)");
+    }
+}
+
+/// `FilterTarget::FilterGroup` の条件を、曲データが満たすか判定
+///
+/// SQL と同じく、条件を持たない子フィルタは無視する。
+/// フィルタ条件が無い場合 (空の Group しか無い場合) は true
+pub fn group_matches(
+    op: &GroupOperand,
+    children: &[FilterTarget],
+    track: &TrackRecord,
+    context: &FilterContext,
+) -> bool {
+    let mut children = children.iter().filter(|c| c.has_condition()).peekable();
+
+    if children.peek().is_none() {
+        return true;
+    }
+
+    match op {
+        GroupOperand::And => children.all(|c| c.matches(track, context)),
+        GroupOperand::Or => children.any(|c| c.matches(track, context)),
+        GroupOperand::Not | GroupOperand::Nand => !children.all(|c| c.matches(track, context)),
+        GroupOperand::Nor => !children.any(|c| c.matches(track, context)),
+    }
 }
 
 /// 集合フィルタの条件指定方法
+///
+/// 条件を持つ子フィルタが無いグループは、否定の演算子でも条件無しとして扱う。
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum GroupOperand {
@@ -34,4 +83,25 @@ pub enum GroupOperand {
 
     /// いずれかを満たす
     Or,
+
+    /// 満たさない
+    ///
+    /// 子フィルタが複数の場合は、全てを満たすことを否定する (`Nand` と同じ)
+    Not,
+
+    /// いずれも満たさない
+    Nor,
+
+    /// 全ては満たさない
+    Nand,
+}
+
+impl GroupOperand {
+    /// 子フィルタの結果を否定する演算子か
+    pub fn is_negated(&self) -> bool {
+        matches!(
+            self,
+            GroupOperand::Not | GroupOperand::Nor | GroupOperand::Nand
+        )
+    }
 }
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::filter::range_string::is_false;
 
 /// 数値で絞り込み
+///
+/// 値が NULL の曲は、`IsNull` か、`include_null` を指定した否定の条件でのみ対象となる。
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "op", rename_all = "snake_case")]
 pub enum IntFilterRange {
@@ -8,7 +13,13 @@ pub enum IntFilterRange {
     Equal { value: i32 },
 
     /// 指定値と異なる
-    NotEqual { value: i32 },
+    ///
+    /// `include_null` を指定すると、値が NULL の曲も含む
+    NotEqual {
+        value: i32,
+        #[serde(default, skip_serializing_if = "is_false")]
+        include_null: bool,
+    },
 
     /// 指定値以上
     LargeEqual { value: i32 },
@@ -20,34 +31,180 @@ pub enum IntFilterRange {
     RangeIn { min: i32, max: i32 },
 
     /// 指定範囲外
-    RangeOut { min: i32, max: i32 },
+    ///
+    /// `include_null` を指定すると、値が NULL の曲も含む
+    RangeOut {
+        min: i32,
+        max: i32,
+        #[serde(default, skip_serializing_if = "is_false")]
+        include_null: bool,
+    },
+
+    /// 値がない
+    IsNull,
+
+    /// 値がある
+    NotNull,
 }
 
 impl IntFilterRange {
-    /// SQL の WHERE で使用する条件式に変換
-    pub fn where_expression(&self, column_name: &str) -> String {
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    pub fn push_where_expression(
+        &self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        column_name: &str,
+    ) {
         match self {
             //指定値と等しい
-            IntFilterRange::Equal { value } => format!("{column_name} = {value}"),
+            IntFilterRange::Equal { value } => {
+                builder.push(column_name).push(" = ").push_bind(*value);
+            }
             //指定値と等しくない
-            //※nullは含めない仕様(WalkBase1がそうなっていたので)
-            IntFilterRange::NotEqual { value } => format!("{column_name} <> {value}"),
+            //※nullは含めない仕様(WalkBase1がそうなっていたので)。include_null 指定時のみ含める
+            IntFilterRange::NotEqual {
+                value,
+                include_null,
+            } => {
+                builder
+                    .push("(")
+                    .push(column_name)
+                    .push(" <> ")
+                    .push_bind(*value);
+                push_or_null(builder, column_name, *include_null);
+                builder.push(")");
+            }
             //指定値以上
-            IntFilterRange::LargeEqual { value } => format!("{column_name} >= {value}"),
+            IntFilterRange::LargeEqual { value } => {
+                builder.push(column_name).push(" >= ").push_bind(*value);
+            }
             //指定値以下
-            IntFilterRange::SmallEqual { value } => format!("{column_name} <= {value}"),
+            IntFilterRange::SmallEqual { value } => {
+                builder.push(column_name).push(" <= ").push_bind(*value);
+            }
             //指定範囲内
             IntFilterRange::RangeIn { min, max } => {
                 let (small, large) = get_ordered_int(*min, *max);
-                format!("({column_name} >= {small} and {column_name} <= {large})")
+                builder
+                    .push("(")
+                    .push(column_name)
+                    .push(" >= ")
+                    .push_bind(small)
+                    .push(" and ")
+                    .push(column_name)
+                    .push(" <= ")
+                    .push_bind(large)
+                    .push(")");
             }
             //指定範囲外
-            IntFilterRange::RangeOut { min, max } => {
+            IntFilterRange::RangeOut {
+                min,
+                max,
+                include_null,
+            } => {
+                let (small, large) = get_ordered_int(*min, *max);
+                builder
+                    .push("(")
+                    .push(column_name)
+                    .push(" < ")
+                    .push_bind(small)
+                    .push(" or ")
+                    .push(column_name)
+                    .push(" > ")
+                    .push_bind(large);
+                push_or_null(builder, column_name, *include_null);
+                builder.push(")");
+            }
+            //値がない
+            IntFilterRange::IsNull => {
+                builder.push(column_name).push(" is null");
+            }
+            //値がある
+            IntFilterRange::NotNull => {
+                builder.push(column_name).push(" is not null");
+            }
+        }
+    }
+
+    /// 値が条件を満たすか判定
+    ///
+    /// `push_where_expression` で生成する SQL と同じく、値が NULL (None) の場合は
+    /// `IsNull` と `include_null` を指定した条件のみ true
+    pub fn matches(&self, target: Option<i32>) -> bool {
+        let Some(target) = target else {
+            return match self {
+                IntFilterRange::IsNull => true,
+                IntFilterRange::NotEqual { include_null, .. }
+                | IntFilterRange::RangeOut { include_null, .. } => *include_null,
+                _ => false,
+            };
+        };
+
+        match self {
+            IntFilterRange::Equal { value } => target == *value,
+            IntFilterRange::NotEqual { value, .. } => target != *value,
+            IntFilterRange::LargeEqual { value } => target >= *value,
+            IntFilterRange::SmallEqual { value } => target <= *value,
+            IntFilterRange::RangeIn { min, max } => {
+                let (small, large) = get_ordered_int(*min, *max);
+                small <= target && target <= large
+            }
+            IntFilterRange::RangeOut { min, max, .. } => {
                 let (small, large) = get_ordered_int(*min, *max);
-                format!("({column_name} < {small} or {column_name} > {large})")
+                target < small || large < target
             }
+            IntFilterRange::IsNull => false,
+            IntFilterRange::NotNull => true,
+        }
+    }
+
+    /// 条件を満たす値の範囲 (両端を含む)
+    ///
+    /// 値が連続した範囲にならない条件や、NULL の判定は None
+    pub fn bounds(&self) -> Option<(i32, i32)> {
+        match self {
+            IntFilterRange::Equal { value } => Some((*value, *value)),
+            IntFilterRange::LargeEqual { value } => Some((*value, i32::MAX)),
+            IntFilterRange::SmallEqual { value } => Some((i32::MIN, *value)),
+            IntFilterRange::RangeIn { min, max } => Some(get_ordered_int(*min, *max)),
+            IntFilterRange::NotEqual { .. }
+            | IntFilterRange::RangeOut { .. }
+            | IntFilterRange::IsNull
+            | IntFilterRange::NotNull => None,
         }
     }
+
+    /// 条件を否定した条件を取得
+    ///
+    /// NULL の扱いが変わってしまうため、大小比較は否定できない (None)
+    pub fn negate(self) -> Option<Self> {
+        match self {
+            IntFilterRange::Equal { value } => Some(IntFilterRange::NotEqual {
+                value,
+                include_null: false,
+            }),
+            IntFilterRange::NotEqual { value, .. } => Some(IntFilterRange::Equal { value }),
+            IntFilterRange::RangeIn { min, max } => Some(IntFilterRange::RangeOut {
+                min,
+                max,
+                include_null: false,
+            }),
+            IntFilterRange::RangeOut { min, max, .. } => Some(IntFilterRange::RangeIn { min, max }),
+            IntFilterRange::IsNull => Some(IntFilterRange::NotNull),
+            IntFilterRange::NotNull => Some(IntFilterRange::IsNull),
+            IntFilterRange::LargeEqual { .. } | IntFilterRange::SmallEqual { .. } => None,
+        }
+    }
+}
+
+/// include_null が指定されていれば、`or カラム is null` を QueryBuilder に追加
+pub(super) fn push_or_null(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    column_name: &str,
+    include_null: bool,
+) {
+    if include_null {
+        builder.push(" or ").push(column_name).push(" is null");
+    }
 }
 
 /// IntFilterRange の min と max を念のため大小比較
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+
+/// プレイリストに含まれるかで絞り込み
+///
+/// 検索前に、対象プレイリストの playlist_tracks がリストアップ済みであるか、
+/// 検索クエリの `WITH` 句で playlist_tracks の内容が指定されている必要がある。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PlaylistFilterRange {
+    /// 指定されたプレイリストに含まれる
+    In { value: i32 },
+
+    /// 指定されたプレイリストに含まれない
+    NotIn { value: i32 },
+}
+
+impl PlaylistFilterRange {
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    pub fn push_where_expression(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        //プレイリストで検索するクエリを追加する関数
+        fn push_query_where_by_playlist(builder: &mut QueryBuilder<'_, Postgres>, plist_id: i32) {
+            builder
+                .push("EXISTS(SELECT * FROM playlist_tracks AS p WHERE p.track_id = tracks.id AND p.playlist_id = ")
+                .push_bind(plist_id)
+                .push(")");
+        }
+
+        match self {
+            PlaylistFilterRange::In { value } => push_query_where_by_playlist(builder, *value),
+            PlaylistFilterRange::NotIn { value } => {
+                builder.push("NOT ");
+                push_query_where_by_playlist(builder, *value);
+            }
+        }
+    }
+
+    /// 曲が含まれるプレイリストの ID 一覧が条件を満たすか判定
+    pub fn matches(&self, playlist_ids: &[i32]) -> bool {
+        match self {
+            PlaylistFilterRange::In { value } => playlist_ids.contains(value),
+            PlaylistFilterRange::NotIn { value } => !playlist_ids.contains(value),
+        }
+    }
+
+    /// 参照しているプレイリストの ID
+    pub fn playlist_id(&self) -> i32 {
+        match self {
+            PlaylistFilterRange::In { value } | PlaylistFilterRange::NotIn { value } => *value,
+        }
+    }
+
+    /// 条件を否定した条件を取得
+    pub fn negate(self) -> Self {
+        match self {
+            PlaylistFilterRange::In { value } => PlaylistFilterRange::NotIn { value },
+            PlaylistFilterRange::NotIn { value } => PlaylistFilterRange::In { value },
+        }
+    }
+}
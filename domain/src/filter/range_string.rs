@@ -1,61 +1,368 @@
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
 
-use crate::db_utils::{escs, like_esc};
+use crate::{
+    db_utils::like_esc,
+    filter::{FilterError, regex_pattern},
+    string_order_cnv,
+};
 
 /// 文字列で絞り込み
+///
+/// 正規表現以外の条件では `normalize` を指定すると、表記ゆれ (大文字・小文字、カタカナ・ひらがな、半角カナ等) を無視して比較する。
+/// (比較には `string_order_cnv::cnv` で変換した値を使う)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "op", rename_all = "snake_case")]
 pub enum StringFilterRange {
     /// 指定文字列と等しい
-    Equal { value: String },
+    Equal {
+        value: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        normalize: bool,
+    },
 
     /// 指定文字列と異なる
-    NotEqual { value: String },
+    NotEqual {
+        value: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        normalize: bool,
+    },
 
     /// 指定文字列を含む
-    Contain { value: String },
+    Contain {
+        value: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        normalize: bool,
+    },
 
     /// 指定文字列を含まない
-    NotContain { value: String },
+    NotContain {
+        value: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        normalize: bool,
+    },
 
     /// 指定文字列から始まる
-    Start { value: String },
+    Start {
+        value: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        normalize: bool,
+    },
 
     /// 指定文字列で終わる
-    End { value: String },
+    End {
+        value: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        normalize: bool,
+    },
+
+    /// 正規表現に一致する (大文字小文字を区別)
+    Regex { value: String },
+
+    /// 正規表現に一致する (大文字小文字を区別しない)
+    RegexIgnoreCase { value: String },
+
+    /// 正規表現に一致しない (大文字小文字を区別)
+    NotRegex { value: String },
+
+    /// 正規表現に一致しない (大文字小文字を区別しない)
+    NotRegexIgnoreCase { value: String },
 }
 
 impl StringFilterRange {
-    /// SQL の WHERE で使用する条件式に変換
-    pub fn where_expression(&self, column_name: &str) -> String {
-        //演算子〜値の左側、値の右側、LIKE区を使うか
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    ///
+    /// 比較する文字列は bind 引数として渡す
+    ///
+    /// # Arguments
+    /// - column_name: 比較対象のカラム名
+    /// - order_column_name: column_name に対応する並べ替え用カラム (`*_order`) の名前。
+    ///   無い場合は、`order_cnv()` SQL 関数で変換した値と比較する
+    pub fn push_where_expression(
+        &self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        column_name: &str,
+        order_column_name: Option<&str>,
+    ) {
+        //演算子〜値の左側、値の右側、LIKE区を使うか、表記ゆれを無視するか
         //範囲指定により分岐
-        let (left, right, use_like, filter_value) = match self {
+        let (left, right, use_like, filter_value, normalize) = match self {
             //指定文字列と等しい
-            StringFilterRange::Equal { value } => (" = ", "", false, value),
+            StringFilterRange::Equal { value, normalize } => (" = ", "", false, value, *normalize),
             //指定文字列と等しくない
-            StringFilterRange::NotEqual { value } => (" != ", "", false, value),
+            StringFilterRange::NotEqual { value, normalize } => {
+                (" != ", "", false, value, *normalize)
+            }
             //指定文字列を含む
-            StringFilterRange::Contain { value } => (" like '%' || ", " || '%'", true, value),
+            StringFilterRange::Contain { value, normalize } => {
+                (" like '%' || ", " || '%'", true, value, *normalize)
+            }
             //指定文字列を含まない
-            StringFilterRange::NotContain { value } => {
-                (" not like '%' || ", " || '%'", true, value)
+            StringFilterRange::NotContain { value, normalize } => {
+                (" not like '%' || ", " || '%'", true, value, *normalize)
             }
             //指定文字列から始まる
-            StringFilterRange::Start { value } => (" like ", " || '%'", true, value),
+            StringFilterRange::Start { value, normalize } => {
+                (" like ", " || '%'", true, value, *normalize)
+            }
             //指定文字列で終わる
-            StringFilterRange::End { value } => (" like '%' || ", "", true, value),
+            StringFilterRange::End { value, normalize } => {
+                (" like '%' || ", "", true, value, *normalize)
+            }
+            //正規表現に一致する
+            StringFilterRange::Regex { value } => (" ~ ", "", false, value, false),
+            StringFilterRange::RegexIgnoreCase { value } => (" ~* ", "", false, value, false),
+            //正規表現に一致しない
+            StringFilterRange::NotRegex { value } => (" !~ ", "", false, value, false),
+            StringFilterRange::NotRegexIgnoreCase { value } => (" !~* ", "", false, value, false),
         };
+
+        //表記ゆれを無視する場合は、並べ替え用の値同士で比較する
+        let (column, filter_value) = if normalize {
+            let column = match order_column_name {
+                Some(order_column) => order_column.to_owned(),
+                None => format!("order_cnv({column_name})"),
+            };
+            (column, string_order_cnv::cnv(filter_value))
+        } else {
+            (column_name.to_owned(), filter_value.to_owned())
+        };
+
         let mut r_string = right.to_owned();
 
         //必要ならlike文のエスケープ処理
-        let cmp_value = if use_like && like_esc::is_need(filter_value) {
+        let cmp_value = if use_like && like_esc::is_need(&filter_value) {
             r_string = format!("{r_string} escape '$'");
-            like_esc::escape(filter_value)
+            like_esc::escape(&filter_value)
         } else {
-            filter_value.to_owned()
+            filter_value
+        };
+
+        builder
+            .push(column)
+            .push(left)
+            .push_bind(cmp_value)
+            .push(r_string);
+    }
+
+    /// 正規表現の指定であれば、パターンが正しいか確認する
+    ///
+    /// DB (PostgreSQL) と `matches` (Rust の regex クレート) の両方で同じ意味となる構文のみ受け付ける。
+    /// 受け付ける構文は `regex_pattern` モジュールを参照。
+    pub fn validate_regex(&self) -> Result<(), FilterError> {
+        let pattern = match self {
+            StringFilterRange::Regex { value }
+            | StringFilterRange::RegexIgnoreCase { value }
+            | StringFilterRange::NotRegex { value }
+            | StringFilterRange::NotRegexIgnoreCase { value } => value,
+            _ => return Ok(()),
         };
 
-        format!("{column_name}{left}{}{r_string}", escs(&cmp_value))
+        regex_pattern::check(pattern)
+            .and_then(|_| build_regex(pattern, false).map_err(|e| e.to_string()))
+            .map(|_| ())
+            .map_err(|reason| FilterError::InvalidRegex {
+                pattern: pattern.to_owned(),
+                reason,
+            })
+    }
+
+    /// 文字列が条件を満たすか判定
+    ///
+    /// `push_where_expression` で生成する SQL と同じ判定を、DB を使わずに行う。
+    /// 正規表現が不正な場合は false を返す (事前に `validate_regex` で確認すること)
+    ///
+    /// # Arguments
+    /// - target: 比較対象の値
+    /// - order_target: target に対応する並べ替え用の値 (`*_order` カラムの値)。
+    ///   無い場合は、target を `string_order_cnv::cnv` で変換した値と比較する
+    pub fn matches(&self, target: &str, order_target: Option<&str>) -> bool {
+        match self {
+            //指定文字列と等しい
+            StringFilterRange::Equal { value, normalize } => {
+                compare_str(target, order_target, value, *normalize, |t, v| t == v)
+            }
+            //指定文字列と等しくない
+            StringFilterRange::NotEqual { value, normalize } => {
+                compare_str(target, order_target, value, *normalize, |t, v| t != v)
+            }
+            //指定文字列を含む
+            StringFilterRange::Contain { value, normalize } => {
+                compare_str(target, order_target, value, *normalize, |t, v| {
+                    t.contains(v)
+                })
+            }
+            //指定文字列を含まない
+            StringFilterRange::NotContain { value, normalize } => {
+                compare_str(target, order_target, value, *normalize, |t, v| {
+                    !t.contains(v)
+                })
+            }
+            //指定文字列から始まる
+            StringFilterRange::Start { value, normalize } => {
+                compare_str(target, order_target, value, *normalize, |t, v| {
+                    t.starts_with(v)
+                })
+            }
+            //指定文字列で終わる
+            StringFilterRange::End { value, normalize } => {
+                compare_str(target, order_target, value, *normalize, |t, v| {
+                    t.ends_with(v)
+                })
+            }
+            //正規表現に一致する
+            StringFilterRange::Regex { value } => {
+                regex_is_match(value, false, target).unwrap_or(false)
+            }
+            StringFilterRange::RegexIgnoreCase { value } => {
+                regex_is_match(value, true, target).unwrap_or(false)
+            }
+            //正規表現に一致しない
+            StringFilterRange::NotRegex { value } => {
+                regex_is_match(value, false, target).is_some_and(|m| !m)
+            }
+            StringFilterRange::NotRegexIgnoreCase { value } => {
+                regex_is_match(value, true, target).is_some_and(|m| !m)
+            }
+        }
+    }
+
+    /// 条件を否定した条件を取得
+    ///
+    /// 否定の演算子が無い場合 (Start / End) は None
+    pub fn negate(self) -> Option<Self> {
+        Some(match self {
+            StringFilterRange::Equal { value, normalize } => {
+                StringFilterRange::NotEqual { value, normalize }
+            }
+            StringFilterRange::NotEqual { value, normalize } => {
+                StringFilterRange::Equal { value, normalize }
+            }
+            StringFilterRange::Contain { value, normalize } => {
+                StringFilterRange::NotContain { value, normalize }
+            }
+            StringFilterRange::NotContain { value, normalize } => {
+                StringFilterRange::Contain { value, normalize }
+            }
+            StringFilterRange::Regex { value } => StringFilterRange::NotRegex { value },
+            StringFilterRange::RegexIgnoreCase { value } => {
+                StringFilterRange::NotRegexIgnoreCase { value }
+            }
+            StringFilterRange::NotRegex { value } => StringFilterRange::Regex { value },
+            StringFilterRange::NotRegexIgnoreCase { value } => {
+                StringFilterRange::RegexIgnoreCase { value }
+            }
+            StringFilterRange::Start { .. } | StringFilterRange::End { .. } => return None,
+        })
+    }
+}
+
+/// 表記ゆれを無視する場合は並べ替え用の値に変換してから、文字列を比較する
+fn compare_str(
+    target: &str,
+    order_target: Option<&str>,
+    value: &str,
+    normalize: bool,
+    cmp: fn(&str, &str) -> bool,
+) -> bool {
+    if normalize {
+        let order_target = match order_target {
+            Some(t) => t.to_owned(),
+            None => string_order_cnv::cnv(target),
+        };
+        cmp(&order_target, &string_order_cnv::cnv(value))
+    } else {
+        cmp(target, value)
+    }
+}
+
+/// 正規表現に一致するか判定
+///
+/// 正規表現が不正な場合は None
+fn regex_is_match(pattern: &str, ignore_case: bool, target: &str) -> Option<bool> {
+    regex_pattern::check(pattern).ok()?;
+    let regex = build_regex(pattern, ignore_case).ok()?;
+
+    Some(regex.is_match(target))
+}
+
+/// PostgreSQL の正規表現と同じ動作になるよう、正規表現を構築
+///
+/// PostgreSQL では `.` が改行にも一致する
+fn build_regex(pattern: &str, ignore_case: bool) -> Result<regex::Regex, regex::Error> {
+    regex::RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .dot_matches_new_line(true)
+        .build()
+}
+
+pub(super) fn is_false(b: &bool) -> bool {
+    !b
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(r"\(.*remix.*\)" ; "paren")]
+    #[test_case("^te.st$" ; "anchor")]
+    #[test_case("" ; "empty")]
+    #[test_case("[a-z0-9_]+" ; "class_range")]
+    #[test_case("a{2,255}?" ; "lazy_repeat")]
+    #[test_case("(?:ab|cd)$" ; "non_capturing_group")]
+    #[test_case("[-a][a-]" ; "class_hyphen")]
+    #[test_case(r"[^\]\-]\{\}" ; "escape")]
+    fn test_validate_regex_ok(pattern: &str) {
+        let range = StringFilterRange::Regex {
+            value: pattern.to_owned(),
+        };
+        assert!(range.validate_regex().is_ok());
+    }
+
+    #[test_case("(abc" ; "unclosed_paren")]
+    #[test_case("[a-" ; "unclosed_class")]
+    #[test_case("a{2,1}" ; "invalid_repeat")]
+    // Rust の regex クレートでのみ有効な構文
+    #[test_case(r"\p{L}" ; "unicode_class")]
+    #[test_case("(?P<n>a)" ; "named_group")]
+    #[test_case(r"\Qa\E" ; "quote")]
+    #[test_case("(?i)a" ; "inline_flag")]
+    #[test_case("[a&&b]" ; "class_intersection")]
+    #[test_case("[a[b]]" ; "nested_class")]
+    #[test_case("[!--]" ; "class_difference")]
+    // PostgreSQL でのみ有効な構文
+    #[test_case(r"\mabc\M" ; "word_boundary_pg")]
+    #[test_case(r"a\y" ; "word_boundary_y")]
+    #[test_case("[[:<:]]a" ; "word_start_class")]
+    #[test_case("[[:alpha:]]" ; "posix_class")]
+    #[test_case("(?=a)" ; "lookahead")]
+    #[test_case(r"(a)\1" ; "back_reference")]
+    // どちらかでのみエラーとなる構文
+    #[test_case("a{256}" ; "repeat_over_pg_limit")]
+    #[test_case("a**" ; "nested_quantifier")]
+    #[test_case("a{2}{3}" ; "nested_repeat")]
+    #[test_case("*a" ; "quantifier_without_operand")]
+    #[test_case("a}" ; "stray_brace")]
+    #[test_case(r"\d" ; "class_escape")]
+    fn test_validate_regex_err(pattern: &str) {
+        let range = StringFilterRange::NotRegexIgnoreCase {
+            value: pattern.to_owned(),
+        };
+        assert!(matches!(
+            range.validate_regex(),
+            Err(FilterError::InvalidRegex { pattern: p, .. }) if p == pattern
+        ));
+    }
+
+    #[test]
+    fn test_validate_regex_not_regex_range() {
+        // 正規表現以外の条件では、値が正規表現として不正でも確認しない
+        let range = StringFilterRange::Contain {
+            value: "(abc".to_owned(),
+            normalize: false,
+        };
+        assert!(range.validate_regex().is_ok());
     }
 }
@@ -1,4 +1,7 @@
+use std::collections::BTreeSet;
+
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
 
 /// タグで絞り込み
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -10,29 +13,128 @@ pub enum TagsFilterRange {
     NotContain { value: i32 },
     /// タグを持たない
     None,
+    /// 指定されたタグのいずれかを含む
+    ContainAny { values: Vec<i32> },
+    /// 指定されたタグを全て含む
+    ContainAll { values: Vec<i32> },
+    /// 指定されたタグをいずれも含まない
+    ContainNone { values: Vec<i32> },
+    /// 指定されたタググループのタグのいずれかを含む
+    GroupAny { group_id: i32 },
+    /// 指定されたタググループのタグをいずれも含まない
+    GroupNone { group_id: i32 },
 }
 
 impl TagsFilterRange {
-    /// SQL の WHERE で使用する条件式に変換
-    pub fn where_expression(&self) -> String {
-        //タグで検索するクエリを取得する関数
-        fn get_query_where_by_tag(tag_id: i32) -> String {
-            format!(
-                "EXISTS(SELECT * FROM track_tags AS t WHERE t.track_id = tracks.id AND t.tag_id = {tag_id})"
-            )
+    /// SQL の WHERE で使用する条件式を、QueryBuilder に追加
+    ///
+    /// 複数のタグを対象とする条件も、1つのサブクエリで判定する
+    pub fn push_where_expression(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        //タグで検索するクエリを追加する関数
+        fn push_query_where_by_tag(builder: &mut QueryBuilder<'_, Postgres>, tag_id: i32) {
+            builder
+                .push("EXISTS(SELECT * FROM track_tags AS t WHERE t.track_id = tracks.id AND t.tag_id = ")
+                .push_bind(tag_id)
+                .push(")");
+        }
+
+        //いずれかのタグで検索するクエリを追加する関数
+        fn push_query_where_by_any_tag(builder: &mut QueryBuilder<'_, Postgres>, tag_ids: &[i32]) {
+            builder
+                .push("EXISTS(SELECT * FROM track_tags AS t WHERE t.track_id = tracks.id AND t.tag_id = ANY(")
+                .push_bind(tag_ids.to_vec())
+                .push("))");
+        }
+
+        //タググループで検索するクエリを追加する関数
+        fn push_query_where_by_group(builder: &mut QueryBuilder<'_, Postgres>, group_id: i32) {
+            builder
+                .push("EXISTS(SELECT * FROM track_tags AS t JOIN tags ON tags.id = t.tag_id WHERE t.track_id = tracks.id AND tags.group_id = ")
+                .push_bind(group_id)
+                .push(")");
         }
 
         match self {
             //タグ：含む
-            TagsFilterRange::Contain { value } => get_query_where_by_tag(*value),
+            TagsFilterRange::Contain { value } => push_query_where_by_tag(builder, *value),
             //タグ：含まない
             TagsFilterRange::NotContain { value } => {
-                format!("NOT {}", get_query_where_by_tag(*value))
+                builder.push("NOT ");
+                push_query_where_by_tag(builder, *value);
             }
             //タグ：タグを持たない
             TagsFilterRange::None => {
-                "NOT EXISTS(SELECT * FROM track_tags AS t WHERE t.track_id = tracks.id)".to_owned()
+                builder
+                    .push("NOT EXISTS(SELECT * FROM track_tags AS t WHERE t.track_id = tracks.id)");
+            }
+            //タグ：いずれかを含む
+            TagsFilterRange::ContainAny { values } => push_query_where_by_any_tag(builder, values),
+            //タグ：全て含む
+            //指定されたタグのうち、曲に付いているタグの数を数えて比較する
+            TagsFilterRange::ContainAll { values } => {
+                let tag_ids: BTreeSet<i32> = values.iter().copied().collect();
+                builder
+                    .push("(SELECT COUNT(*) FROM track_tags AS t WHERE t.track_id = tracks.id AND t.tag_id = ANY(")
+                    .push_bind(tag_ids.iter().copied().collect::<Vec<_>>())
+                    .push(")) = ")
+                    .push_bind(tag_ids.len() as i64);
             }
+            //タグ：いずれも含まない
+            TagsFilterRange::ContainNone { values } => {
+                builder.push("NOT ");
+                push_query_where_by_any_tag(builder, values);
+            }
+            //タググループ：いずれかを含む
+            TagsFilterRange::GroupAny { group_id } => push_query_where_by_group(builder, *group_id),
+            //タググループ：いずれも含まない
+            TagsFilterRange::GroupNone { group_id } => {
+                builder.push("NOT ");
+                push_query_where_by_group(builder, *group_id);
+            }
+        }
+    }
+
+    /// 曲のタグ ID 一覧・タググループ ID 一覧が条件を満たすか判定
+    pub fn matches(&self, tag_ids: &[i32], tag_group_ids: &[i32]) -> bool {
+        match self {
+            TagsFilterRange::Contain { value } => tag_ids.contains(value),
+            TagsFilterRange::NotContain { value } => !tag_ids.contains(value),
+            TagsFilterRange::None => tag_ids.is_empty(),
+            TagsFilterRange::ContainAny { values } => values.iter().any(|v| tag_ids.contains(v)),
+            TagsFilterRange::ContainAll { values } => values.iter().all(|v| tag_ids.contains(v)),
+            TagsFilterRange::ContainNone { values } => !values.iter().any(|v| tag_ids.contains(v)),
+            TagsFilterRange::GroupAny { group_id } => tag_group_ids.contains(group_id),
+            TagsFilterRange::GroupNone { group_id } => !tag_group_ids.contains(group_id),
+        }
+    }
+
+    /// 条件で指定されたタグ ID の一覧
+    pub fn tag_ids(&self) -> &[i32] {
+        match self {
+            TagsFilterRange::Contain { value } | TagsFilterRange::NotContain { value } => {
+                std::slice::from_ref(value)
+            }
+            TagsFilterRange::ContainAny { values }
+            | TagsFilterRange::ContainAll { values }
+            | TagsFilterRange::ContainNone { values } => values,
+            TagsFilterRange::None
+            | TagsFilterRange::GroupAny { .. }
+            | TagsFilterRange::GroupNone { .. } => &[],
+        }
+    }
+
+    /// 条件を否定した条件を取得
+    ///
+    /// タグを持たない条件と、全て含む条件は否定できない (None)
+    pub fn negate(self) -> Option<Self> {
+        match self {
+            TagsFilterRange::Contain { value } => Some(TagsFilterRange::NotContain { value }),
+            TagsFilterRange::NotContain { value } => Some(TagsFilterRange::Contain { value }),
+            TagsFilterRange::ContainAny { values } => Some(TagsFilterRange::ContainNone { values }),
+            TagsFilterRange::ContainNone { values } => Some(TagsFilterRange::ContainAny { values }),
+            TagsFilterRange::GroupAny { group_id } => Some(TagsFilterRange::GroupNone { group_id }),
+            TagsFilterRange::GroupNone { group_id } => Some(TagsFilterRange::GroupAny { group_id }),
+            TagsFilterRange::None | TagsFilterRange::ContainAll { .. } => None,
         }
     }
 }
@@ -0,0 +1,226 @@
+//! 正規表現のパターンの構文の確認
+//!
+//! 正規表現の条件は、DB での検索では PostgreSQL の正規表現 (ARE) で、
+//! `FilterTarget::matches` では Rust の regex クレートで評価する。
+//! 両者で構文や意味が異なる部分があるため、どちらでも同じ意味となる以下の構文のみ受け付ける。
+//!
+//! | 構文 | 意味 |
+//! | --- | --- |
+//! | メタ文字 (`\ . ^ $ \| ( ) [ ] { } * + ?`) 以外の文字 | その文字 |
+//! | `\` + メタ文字か `-` | その文字 |
+//! | `.` | 改行を含む任意の1文字 |
+//! | `^` / `$` | 文字列の先頭 / 末尾 |
+//! | `a\|b` | 選択 |
+//! | `( )` / `(?: )` | グループ |
+//! | `*` `+` `?` `{n}` `{n,}` `{n,m}` | 繰り返し (n, m は 255 以下)。後に `?` を付けると最短一致 |
+//! | `[abc]` `[^abc]` `[a-z]` | 文字クラス (`-` は先頭か末尾なら文字として扱う) |
+//!
+//! `\d` `\b` `\m` などの英字のエスケープ、先読み・後読み、名前付きグループ、
+//! 埋め込みオプション (`(?i)` など)、`[:alpha:]` などの文字クラス名、
+//! 文字クラスの集合演算 (`&&` `--` `~~`) は受け付けない。
+
+/// メタ文字 (エスケープすると、その文字自体を表す)
+const META_CHARS: &str = r"\.^$|()[]{}*+?";
+
+/// 繰り返し回数の上限 (PostgreSQL の制限)
+const MAX_REPEAT: u32 = 255;
+
+/// パターンが、対応している構文のみで書かれているか確認
+///
+/// # Returns
+/// 対応していない場合は、その理由
+pub(super) fn check(pattern: &str) -> Result<(), String> {
+    let chars: Vec<char> = pattern.chars().collect();
+
+    let mut i = 0;
+    //閉じていない `(` の数
+    let mut depth = 0;
+    //直前の要素に繰り返しを付けられるか
+    let mut quantifiable = false;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                escaped(&chars, i)?;
+                i += 2;
+                quantifiable = true;
+            }
+            '[' => {
+                i = class_end(&chars, i)?;
+                quantifiable = true;
+            }
+            '(' => {
+                if chars.get(i + 1) == Some(&'?') {
+                    if chars.get(i + 2) != Some(&':') {
+                        return Err(unsupported(i, "(?"));
+                    }
+                    i += 2;
+                }
+                i += 1;
+                depth += 1;
+                quantifiable = false;
+            }
+            ')' => {
+                if depth == 0 {
+                    return Err(format!("{}文字目の `)` に対応する `(` がありません", i + 1));
+                }
+                i += 1;
+                depth -= 1;
+                quantifiable = true;
+            }
+            c @ ('*' | '+' | '?' | '{') => {
+                if !quantifiable {
+                    return Err(format!(
+                        "{}文字目の `{c}` の前に、繰り返す対象がありません",
+                        i + 1
+                    ));
+                }
+                i = if c == '{' {
+                    repeat_end(&chars, i)?
+                } else {
+                    i + 1
+                };
+                //最短一致
+                if chars.get(i) == Some(&'?') {
+                    i += 1;
+                }
+                quantifiable = false;
+            }
+            c @ (']' | '}') => {
+                return Err(format!(
+                    "{}文字目の `{c}` は、`\\{c}` とエスケープしてください",
+                    i + 1
+                ));
+            }
+            '^' | '$' | '|' => {
+                i += 1;
+                quantifiable = false;
+            }
+            _ => {
+                i += 1;
+                quantifiable = true;
+            }
+        }
+    }
+
+    if depth > 0 {
+        return Err("閉じていない `(` があります".to_owned());
+    }
+
+    Ok(())
+}
+
+/// `\` に続く文字を取得
+///
+/// - i: `\` の位置
+fn escaped(chars: &[char], i: usize) -> Result<char, String> {
+    match chars.get(i + 1) {
+        Some(&c) if c == '-' || META_CHARS.contains(c) => Ok(c),
+        Some(&c) => Err(unsupported(i, &format!("\\{c}"))),
+        None => Err("末尾の `\\` の後に文字がありません".to_owned()),
+    }
+}
+
+/// 文字クラスの終わりを取得
+///
+/// - start: `[` の位置
+///
+/// # Returns
+/// `]` の次の位置
+fn class_end(chars: &[char], start: usize) -> Result<usize, String> {
+    let mut i = start + 1;
+    if chars.get(i) == Some(&'^') {
+        i += 1;
+    }
+
+    let first = i;
+    loop {
+        match chars.get(i) {
+            None => return Err(format!("{}文字目の `[` が閉じていません", start + 1)),
+            Some(']') if i == first => {
+                return Err(format!("{}文字目の `[` の文字クラスが空です", start + 1));
+            }
+            Some(']') => return Ok(i + 1),
+            _ => {}
+        }
+
+        let (from, next) = class_char(chars, i, i == first)?;
+        i = next;
+
+        //範囲の指定 (`-` の後が `]` なら、`-` は文字として扱う)
+        if chars.get(i) == Some(&'-') && chars.get(i + 1).is_some_and(|c| *c != ']') {
+            //範囲の終わりを `-` にすると、Rust では集合演算 (`--`) となる
+            if chars.get(i + 1) == Some(&'-') {
+                return Err(unsupported(i, "--"));
+            }
+            let (to, next) = class_char(chars, i + 1, false)?;
+            if from > to {
+                return Err(format!(
+                    "{}文字目の範囲 `{from}-{to}` の順序が逆です",
+                    i + 1
+                ));
+            }
+            i = next;
+        }
+    }
+}
+
+/// 文字クラス内の1文字を取得
+///
+/// - i: 文字の位置
+/// - first: 文字クラスの最初の文字か
+///
+/// # Returns
+/// 文字と、次の位置
+fn class_char(chars: &[char], i: usize, first: bool) -> Result<(char, usize), String> {
+    match chars.get(i) {
+        None => Err("閉じていない `[` があります".to_owned()),
+        Some('\\') => Ok((escaped(chars, i)?, i + 2)),
+        Some('[') => Err(unsupported(i, "[")),
+        //Rust では文字クラスの集合演算となる
+        Some(&c @ ('&' | '~' | '-')) if chars.get(i + 1) == Some(&c) => {
+            Err(unsupported(i, &format!("{c}{c}")))
+        }
+        //`-` は先頭か末尾のみ文字として扱う
+        Some('-') if !first && chars.get(i + 1) != Some(&']') => Err(unsupported(i, "-")),
+        Some(&c) => Ok((c, i + 1)),
+    }
+}
+
+/// 繰り返し回数の指定 `{n}` `{n,}` `{n,m}` の終わりを取得
+///
+/// - start: `{` の位置
+///
+/// # Returns
+/// `}` の次の位置
+fn repeat_end(chars: &[char], start: usize) -> Result<usize, String> {
+    let invalid = || format!("{}文字目の繰り返し回数の指定が不正です", start + 1);
+
+    let end = chars[start..]
+        .iter()
+        .position(|c| *c == '}')
+        .map(|p| start + p)
+        .ok_or_else(invalid)?;
+    let body: String = chars[start + 1..end].iter().collect();
+
+    let parse = |s: &str| {
+        if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        s.parse::<u32>().ok().filter(|n| *n <= MAX_REPEAT)
+    };
+    let (min, max) = match body.split_once(',') {
+        Some((min, "")) => (parse(min), Some(MAX_REPEAT)),
+        Some((min, max)) => (parse(min), parse(max)),
+        None => (parse(&body), parse(&body)),
+    };
+
+    match (min, max) {
+        (Some(min), Some(max)) if min <= max => Ok(end + 1),
+        _ => Err(invalid()),
+    }
+}
+
+fn unsupported(i: usize, syntax: &str) -> String {
+    format!("{}文字目の `{syntax}` は対応していない構文です", i + 1)
+}
@@ -1,2 +1,7 @@
 mod test_db;
+mod test_description;
+mod test_document;
+mod test_explain;
 mod test_json;
+mod test_matches;
+mod test_validation;
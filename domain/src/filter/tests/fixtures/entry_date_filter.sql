@@ -0,0 +1,9 @@
+-- Test fixture for entry date and relative date filter tests
+-- The filter context is 2024-03-20, UTC+9 (see test_db::test_context)
+
+INSERT INTO tracks (id, duration, path, title, release_date, created_at) VALUES 
+    (1, 180, 'track1.mp3', 'Entry 2024-03-10 23:59:59 JST', '2024-03-13', '2024-03-10 14:59:59+00'),
+    (2, 180, 'track2.mp3', 'Entry 2024-03-11 00:00:00 JST', '2024-03-12', '2024-03-10 15:00:00+00'),
+    (3, 180, 'track3.mp3', 'Entry 2024-03-20 00:00:00 JST', '2023-12-20', '2024-03-19 15:00:00+00'),
+    (4, 180, 'track4.mp3', 'Entry 2023-12-19 23:59:59 JST', '2023-12-19', '2023-12-19 14:59:59+00'),
+    (5, 180, 'track5.mp3', 'Entry 2024-03-20 23:59:59 JST', NULL, '2024-03-20 14:59:59+00');
@@ -0,0 +1,14 @@
+-- 未リストアップのフィルタプレイリストを参照する、判定結果の説明のテスト用データ
+
+INSERT INTO tracks (id, duration, path, title, rating) VALUES
+    (1, 180, 'track1.mp3', 'High', 5),
+    (2, 180, 'track2.mp3', 'Low', 1);
+
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order, filter_json) VALUES
+    -- レート 4 以上 (未リストアップ)
+    (1, 'filter', 'High Rating', '[{"field": "title"}]', false, 0,
+        '{"target": "rating", "range": {"op": "large_equal", "value": 4}}');
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    -- 以前のリストアップ時点の古い内容
+    (1, 0, 2);
@@ -0,0 +1,13 @@
+-- フィルタの保存形式の変換のテスト用データ
+
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order, filter_json) VALUES
+    -- バージョン情報の無い、旧形式のフィルタ
+    (1, 'filter', 'Legacy Filter', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, 0,
+        '{"target": "rating", "range": {"op": "large_equal", "value": 4}}'),
+    -- 現在の形式のフィルタ
+    (2, 'filter', 'Current Filter', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, 1,
+        '{"version": 1, "filter": {"target": "tags", "range": {"op": "contain", "value": 3}}}'),
+    (3, 'normal', 'Normal Playlist', '[{"field": "playlist"}]', true, 2, NULL);
+
+INSERT INTO search_presets (id, order_index, name, filter_json) VALUES
+    (1, 0, 'legacy', '{"target": "group", "op": "and", "children": []}');
@@ -0,0 +1,24 @@
+-- フィルタの検証・保存のテスト用データ
+
+INSERT INTO tag_groups (id, name, order_index) VALUES
+    (1, 'group1', 0);
+
+INSERT INTO tags (id, name, group_id, order_index) VALUES
+    (1, 'tag1', 0, 0),
+    (2, 'tag2', 0, 1);
+
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order, filter_json) VALUES
+    (1, 'filter', 'Filter Playlist', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, 0, NULL),
+    (2, 'normal', 'Normal Playlist', '[{"field": "playlist"}]', true, 1, NULL),
+    (3, 'folder', 'Folder', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, 2, NULL);
+
+-- フォルダ 3 の子で、プレイリスト 1 を参照する
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, parent_id, in_folder_order, filter_json) VALUES
+    (4, 'filter', 'Child Filter', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, 3, 0,
+        '{"target": "in_playlist", "range": {"op": "in", "value": 1}}');
+
+INSERT INTO search_presets (id, order_index, name, filter_json) VALUES
+    (1, 0, 'preset1', '{"target": "group", "op": "and", "children": []}');
+
+INSERT INTO folder_paths (id, path, name, parent_id) VALUES
+    (1, 'Artist/', 'Artist', NULL);
@@ -0,0 +1,16 @@
+-- Test fixture for folder filter tests
+
+INSERT INTO folder_paths (id, path, name, parent_id) VALUES
+    (1, 'Artist/', 'Artist', NULL),
+    (2, 'Artist/Album/', 'Album', 1),
+    (3, 'Art_ist/', 'Art_ist', NULL),
+    (4, 'ArtistX/', 'ArtistX', NULL),
+    (5, 'ArtXist/', 'ArtXist', NULL);
+
+INSERT INTO tracks (id, duration, path, title, folder_id) VALUES
+    (1, 180, 'Artist/track1.mp3', 'Artist Direct', 1),
+    (2, 180, 'Artist/Album/track2.mp3', 'Artist Album', 2),
+    (3, 180, 'Art_ist/track3.mp3', 'Underscore', 3),
+    (4, 180, 'ArtistX/track4.mp3', 'Similar Name', 4),
+    (5, 180, 'track5.mp3', 'Root', NULL),
+    (6, 180, 'ArtXist/track6.mp3', 'Like Wildcard', 5);
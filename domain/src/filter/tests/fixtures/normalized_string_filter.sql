@@ -0,0 +1,9 @@
+-- Test fixture for normalized string filter tests
+-- Sets up tracks whose *_order columns are filled by string_order_cnv::cnv
+
+INSERT INTO tracks (id, duration, path, title, title_order, memo) VALUES 
+    (1, 180, 'track1.mp3', 'ガラス', 'がらす', 'ｶﾞﾗｽ'),
+    (2, 180, 'track2.mp3', 'ｶﾞﾗｽの靴', 'がらすの靴', 'がらすの靴'),
+    (3, 180, 'track3.mp3', 'ABC', 'abc', 'abc'),
+    (4, 180, 'track4.mp3', 'abcdef', 'abcdef', 'ABCDEF'),
+    (5, 180, 'track5.mp3', 'カラス', 'からす', 'カラス');
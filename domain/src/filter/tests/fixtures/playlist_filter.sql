@@ -0,0 +1,19 @@
+-- Test fixture for in_playlist filter tests
+
+INSERT INTO tracks (id, duration, path, title) VALUES
+    (1, 180, 'track1.mp3', 'In 1'),
+    (2, 180, 'track2.mp3', 'In 1 and 2'),
+    (3, 180, 'track3.mp3', 'In 2'),
+    (4, 180, 'track4.mp3', 'No Playlist');
+
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order) VALUES
+    (1, 'normal', 'Playlist 1', '[{"field": "playlist"}]', true, 0),
+    (2, 'normal', 'Playlist 2', '[{"field": "playlist"}]', true, 1);
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    (1, 0, 1),
+    (1, 1, 2),
+    (2, 0, 3),
+    (2, 1, 2),
+    -- 同じ曲を複数回含む
+    (2, 2, 3);
@@ -0,0 +1,12 @@
+-- Test fixture for regex filter tests
+-- Sets up tracks with titles containing regex meta characters and line breaks
+
+INSERT INTO tracks (id, duration, path, title) VALUES 
+    (1, 180, 'track1.mp3', 'Song (Remix)'),
+    (2, 180, 'track2.mp3', 'song [live]'),
+    (3, 180, 'track3.mp3', E'first line\nsecond line'),
+    (4, 180, 'track4.mp3', 'a-b{2}'),
+    (5, 180, 'track5.mp3', 'AAA^$'),
+    (6, 180, 'track6.mp3', 'word_123'),
+    (7, 180, 'track7.mp3', 'Ébène'),
+    (8, 180, 'track8.mp3', '');
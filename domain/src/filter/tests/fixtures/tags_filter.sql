@@ -7,6 +7,12 @@ INSERT INTO tracks (id, duration, path, title) VALUES
     (3, 180, 'track3.mp3', 'Tags 4 and 83'),
     (4, 180, 'track4.mp3', 'Tags 8 and 83');
 
+-- Insert tags (group 1: tag 4, group 2: tags 8 and 83)
+INSERT INTO tags (id, name, group_id, order_index) VALUES
+    (4, 'tag4', 1, 0),
+    (8, 'tag8', 2, 0),
+    (83, 'tag83', 2, 1);
+
 -- Insert tag relationships
 INSERT INTO track_tags (track_id, tag_id) VALUES 
     (2, 4),
@@ -1,25 +1,36 @@
 //! フィルタ条件を使用して DB から曲を検索するテスト
 
-use sqlx::{PgPool, PgTransaction};
+use sqlx::{PgPool, PgTransaction, QueryBuilder};
+
+use chrono::{FixedOffset, NaiveDate};
 
 use crate::{
-    filter::{FilterTarget, RootFilter},
+    filter::{FilterContext, FilterTarget, RootFilter},
     test_utils::assert_eq_not_orderd,
 };
 
+/// テストで使用する、フィルタの判定の基準 (2024-03-20, UTC+9)
+pub(super) fn test_context() -> FilterContext {
+    FilterContext {
+        today: NaiveDate::from_ymd_opt(2024, 3, 20).unwrap(),
+        time_zone: FixedOffset::east_opt(9 * 3600).unwrap().into(),
+    }
+}
+
 /// フィルタを使用して曲 ID を列挙
-async fn get_track_ids<'c>(
+pub(super) async fn get_track_ids<'c>(
     tx: &mut PgTransaction<'c>,
     filter: &RootFilter,
 ) -> sqlx::Result<Vec<i32>> {
-    let mut query_base = "SELECT tracks.id FROM tracks".to_owned();
+    let mut query = QueryBuilder::new("SELECT tracks.id FROM tracks");
 
     //フィルタから条件を取得して追加
-    if let Some(query_where) = filter.where_expression() {
-        query_base = format!("{query_base} WHERE {query_where}");
+    if filter.has_condition() {
+        query.push(" WHERE ");
+        filter.push_where_expression(&mut query, &test_context());
     }
 
-    let list = sqlx::query_scalar(&query_base).fetch_all(&mut **tx).await?;
+    let list = query.build_query_scalar().fetch_all(&mut **tx).await?;
 
     Ok(list)
 }
@@ -44,6 +55,7 @@ mod test_group_filter {
                 FilterTarget::Artist {
                     range: StringFilterRange::Contain {
                         value: "taro".to_owned(),
+                        normalize: false,
                     },
                 },
                 FilterTarget::FilterGroup {
@@ -72,6 +84,52 @@ mod test_group_filter {
 
         Ok(())
     }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("group_filter"))]
+    async fn negated_groups(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let artist_taro = FilterTarget::Artist {
+            range: StringFilterRange::Contain {
+                value: "taro".to_owned(),
+                normalize: false,
+            },
+        };
+        let release_2021 = FilterTarget::ReleaseDate {
+            range: DateFilterRange::Equal {
+                value: NaiveDate::from_ymd_opt(2021, 9, 25).unwrap(),
+            },
+        };
+        let group = |op: GroupOperand| FilterTarget::FilterGroup {
+            op,
+            children: vec![artist_taro.clone(), release_2021.clone()],
+        };
+
+        // リリース日が NULL の曲も、条件を満たさないものとして否定される
+        let not = FilterTarget::FilterGroup {
+            op: GroupOperand::Not,
+            children: vec![release_2021.clone()],
+        };
+        assert_eq_not_orderd(&get_track_ids(&mut tx, &not).await?, &[1, 3, 4, 5]);
+
+        let nand = group(GroupOperand::Nand);
+        assert_eq_not_orderd(&get_track_ids(&mut tx, &nand).await?, &[1, 2, 3, 4, 5]);
+
+        let nor = group(GroupOperand::Nor);
+        assert_eq_not_orderd(&get_track_ids(&mut tx, &nor).await?, &[5]);
+
+        // 条件の無い否定のグループは、条件なしとして扱われる
+        let empty = FilterTarget::FilterGroup {
+            op: GroupOperand::Nor,
+            children: vec![],
+        };
+        assert_eq_not_orderd(
+            &get_track_ids(&mut tx, &empty).await?,
+            &[1, 2, 3, 4, 5, 6, 7],
+        );
+
+        Ok(())
+    }
 }
 
 // 文字列フィルタのテスト
@@ -92,6 +150,7 @@ mod test_string_filter {
             &mut tx,
             &filter(StringFilterRange::Equal {
                 value: "test".to_owned(),
+                normalize: false,
             }),
         )
         .await?;
@@ -108,6 +167,7 @@ mod test_string_filter {
             &mut tx,
             &filter(StringFilterRange::NotEqual {
                 value: "test".to_owned(),
+                normalize: false,
             }),
         )
         .await?;
@@ -124,6 +184,7 @@ mod test_string_filter {
             &mut tx,
             &filter(StringFilterRange::Start {
                 value: "test".to_owned(),
+                normalize: false,
             }),
         )
         .await?;
@@ -140,6 +201,7 @@ mod test_string_filter {
             &mut tx,
             &filter(StringFilterRange::End {
                 value: "test".to_owned(),
+                normalize: false,
             }),
         )
         .await?;
@@ -156,6 +218,7 @@ mod test_string_filter {
             &mut tx,
             &filter(StringFilterRange::Contain {
                 value: "test".to_owned(),
+                normalize: false,
             }),
         )
         .await?;
@@ -172,6 +235,7 @@ mod test_string_filter {
             &mut tx,
             &filter(StringFilterRange::NotContain {
                 value: "test".to_owned(),
+                normalize: false,
             }),
         )
         .await?;
@@ -188,6 +252,7 @@ mod test_string_filter {
             &mut tx,
             &filter(StringFilterRange::Equal {
                 value: "te%st".to_owned(),
+                normalize: false,
             }),
         )
         .await?;
@@ -204,6 +269,7 @@ mod test_string_filter {
             &mut tx,
             &filter(StringFilterRange::Contain {
                 value: "te%st".to_owned(),
+                normalize: false,
             }),
         )
         .await?;
@@ -211,6 +277,228 @@ mod test_string_filter {
         assert_eq_not_orderd(&result, &[9, 10]);
         Ok(())
     }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("string_filter"))]
+    async fn regex(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(StringFilterRange::Regex {
+                value: "^te.*st$".to_owned(),
+            }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[1, 5, 6, 9]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("string_filter"))]
+    async fn regex_case_sensitive(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(StringFilterRange::Regex {
+                value: "^aa".to_owned(),
+            }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("string_filter"))]
+    async fn regex_ignore_case(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(StringFilterRange::RegexIgnoreCase {
+                value: "^aa".to_owned(),
+            }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[2, 4, 7, 10]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("string_filter"))]
+    async fn not_regex(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(StringFilterRange::NotRegex {
+                value: "^te".to_owned(),
+            }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[2, 4, 7, 8, 10]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("string_filter"))]
+    async fn not_regex_ignore_case(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(StringFilterRange::NotRegexIgnoreCase {
+                value: "a".to_owned(),
+            }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[1, 8, 9]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("string_filter"))]
+    async fn equal_with_quote_injection(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(StringFilterRange::Equal {
+                value: "' or ''='".to_owned(),
+                normalize: false,
+            }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("string_filter"))]
+    async fn contain_with_quote_injection(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(StringFilterRange::Contain {
+                value: "'; DELETE FROM tracks; --".to_owned(),
+                normalize: false,
+            }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[]);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tracks")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert_eq!(count, 10);
+
+        Ok(())
+    }
+}
+
+// 表記ゆれを無視する文字列フィルタのテスト
+mod test_normalized_string_filter {
+    use crate::filter::StringFilterRange;
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("normalized_string_filter"))]
+    async fn title_contain_han_kana(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &FilterTarget::Title {
+                range: StringFilterRange::Contain {
+                    value: "ｶﾞﾗ".to_owned(),
+                    normalize: true,
+                },
+            },
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[1, 2]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("normalized_string_filter"))]
+    async fn title_equal_ignore_case(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &FilterTarget::Title {
+                range: StringFilterRange::Equal {
+                    value: "abc".to_owned(),
+                    normalize: true,
+                },
+            },
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[3]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("normalized_string_filter"))]
+    async fn title_not_normalized(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &FilterTarget::Title {
+                range: StringFilterRange::Start {
+                    value: "ABC".to_owned(),
+                    normalize: false,
+                },
+            },
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[3]);
+        Ok(())
+    }
+
+    /// 並べ替え用カラムが無い項目は、order_cnv() 関数で変換して比較する
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("normalized_string_filter"))]
+    async fn memo_start(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &FilterTarget::Memo {
+                range: StringFilterRange::Start {
+                    value: "ガラス".to_owned(),
+                    normalize: true,
+                },
+            },
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[1, 2]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("normalized_string_filter"))]
+    async fn memo_not_contain(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &FilterTarget::Memo {
+                range: StringFilterRange::NotContain {
+                    value: "Abc".to_owned(),
+                    normalize: true,
+                },
+            },
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[1, 2, 5]);
+        Ok(())
+    }
 }
 
 // 整数フィルタのテスト
@@ -248,8 +536,14 @@ mod test_int_filter {
         let mut tx = pool.begin().await?;
 
         // ※nullは含めない仕様(WalkBase1がそうなっていたので)
-        let result =
-            get_track_ids(&mut tx, &filter(IntFilterRange::NotEqual { value: 25 })).await?;
+        let result = get_track_ids(
+            &mut tx,
+            &filter(IntFilterRange::NotEqual {
+                value: 25,
+                include_null: false,
+            }),
+        )
+        .await?;
 
         assert_eq_not_orderd(&result, &[2, 3, 4, 5, 7]);
         Ok(())
@@ -297,13 +591,65 @@ mod test_int_filter {
 
         let result = get_track_ids(
             &mut tx,
-            &filter(IntFilterRange::RangeOut { min: 5, max: 10 }),
+            &filter(IntFilterRange::RangeOut {
+                min: 5,
+                max: 10,
+                include_null: false,
+            }),
         )
         .await?;
 
         assert_eq_not_orderd(&result, &[2, 6, 7]);
         Ok(())
     }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("int_filter"))]
+    async fn include_null(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(IntFilterRange::NotEqual {
+                value: 25,
+                include_null: true,
+            }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[1, 2, 3, 4, 5, 7]);
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(IntFilterRange::RangeOut {
+                min: 5,
+                max: 10,
+                include_null: true,
+            }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[1, 2, 6, 7]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("int_filter"))]
+    async fn is_null(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &filter(IntFilterRange::IsNull)).await?;
+
+        assert_eq_not_orderd(&result, &[1]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("int_filter"))]
+    async fn not_null(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &filter(IntFilterRange::NotNull)).await?;
+
+        assert_eq_not_orderd(&result, &[2, 3, 4, 5, 6, 7]);
+        Ok(())
+    }
 }
 
 // タグフィルタのテスト
@@ -389,6 +735,97 @@ mod test_tags_filter {
         assert_eq_not_orderd(&result, &[1]);
         Ok(())
     }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("tags_filter"))]
+    async fn contain_any(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(TagsFilterRange::ContainAny {
+                values: vec![8, 4, 5],
+            }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[2, 3, 4]);
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(TagsFilterRange::ContainAny { values: vec![] }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("tags_filter"))]
+    async fn contain_all(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // 重複した ID は1つとして扱う
+        let result = get_track_ids(
+            &mut tx,
+            &filter(TagsFilterRange::ContainAll {
+                values: vec![83, 4, 83],
+            }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[3]);
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(TagsFilterRange::ContainAll { values: vec![4, 5] }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[]);
+
+        // 空の場合は全ての曲
+        let result = get_track_ids(
+            &mut tx,
+            &filter(TagsFilterRange::ContainAll { values: vec![] }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[1, 2, 3, 4]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("tags_filter"))]
+    async fn contain_none(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(TagsFilterRange::ContainNone { values: vec![4, 8] }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[1]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("tags_filter"))]
+    async fn group_any(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result =
+            get_track_ids(&mut tx, &filter(TagsFilterRange::GroupAny { group_id: 2 })).await?;
+
+        assert_eq_not_orderd(&result, &[3, 4]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("tags_filter"))]
+    async fn group_none(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result =
+            get_track_ids(&mut tx, &filter(TagsFilterRange::GroupNone { group_id: 1 })).await?;
+
+        assert_eq_not_orderd(&result, &[1, 4]);
+        Ok(())
+    }
 }
 
 // ブールフィルタのテスト
@@ -489,6 +926,7 @@ mod test_date_filter {
             &mut tx,
             &filter(DateFilterRange::NotEqual {
                 value: NaiveDate::from_ymd_opt(2012, 4, 5).unwrap(),
+                include_null: false,
             }),
         )
         .await?;
@@ -538,4 +976,294 @@ mod test_date_filter {
         assert_eq_not_orderd(&result, &[1]);
         Ok(())
     }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("date_filter"))]
+    async fn not_null(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &filter(DateFilterRange::NotNull)).await?;
+
+        assert_eq_not_orderd(&result, &[2, 3, 4]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("date_filter"))]
+    async fn not_equal_include_null(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &filter(DateFilterRange::NotEqual {
+                value: NaiveDate::from_ymd_opt(2012, 4, 5).unwrap(),
+                include_null: true,
+            }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[1, 2, 4]);
+        Ok(())
+    }
+}
+
+// 登録日・相対日付のフィルタのテスト
+//
+// 基準日は 2024-03-20、タイムゾーンは UTC+9
+mod test_entry_date_filter {
+    use crate::filter::DateFilterRange;
+
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn entry(range: DateFilterRange) -> FilterTarget {
+        FilterTarget::EntryDate { range }
+    }
+
+    fn release(range: DateFilterRange) -> FilterTarget {
+        FilterTarget::ReleaseDate { range }
+    }
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, m, d).unwrap()
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("entry_date_filter"))]
+    async fn entry_equal(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // UTC では 3/10 でも、UTC+9 では 3/11 になる曲は含まない
+        let result = get_track_ids(
+            &mut tx,
+            &entry(DateFilterRange::Equal { value: date(3, 10) }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[1]);
+
+        let result = get_track_ids(
+            &mut tx,
+            &entry(DateFilterRange::Equal { value: date(3, 20) }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[3, 5]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("entry_date_filter"))]
+    async fn entry_not_equal(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &entry(DateFilterRange::NotEqual {
+                value: date(3, 10),
+                include_null: false,
+            }),
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[2, 3, 4, 5]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("entry_date_filter"))]
+    async fn entry_before_after(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &entry(DateFilterRange::Before { value: date(3, 10) }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[1, 4]);
+
+        let result = get_track_ids(
+            &mut tx,
+            &entry(DateFilterRange::After { value: date(3, 11) }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[2, 3, 5]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("entry_date_filter"))]
+    async fn entry_relative(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // 3/11 以後
+        let result =
+            get_track_ids(&mut tx, &entry(DateFilterRange::WithinDays { value: 9 })).await?;
+        assert_eq_not_orderd(&result, &[2, 3, 5]);
+
+        // 3/10 以前
+        let result =
+            get_track_ids(&mut tx, &entry(DateFilterRange::OlderThanDays { value: 9 })).await?;
+        assert_eq_not_orderd(&result, &[1, 4]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("entry_date_filter"))]
+    async fn release_within(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // 3/13 以後
+        let result =
+            get_track_ids(&mut tx, &release(DateFilterRange::WithinDays { value: 7 })).await?;
+        assert_eq_not_orderd(&result, &[1]);
+
+        // 2023/12/20 以後
+        let result = get_track_ids(
+            &mut tx,
+            &release(DateFilterRange::WithinMonths { value: 3 }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[1, 2, 3]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("entry_date_filter"))]
+    async fn release_older_than(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // 3/12 以前 (日付がない曲は含まない)
+        let result = get_track_ids(
+            &mut tx,
+            &release(DateFilterRange::OlderThanDays { value: 7 }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[2, 3, 4]);
+
+        // 2023/12/19 以前
+        let result = get_track_ids(
+            &mut tx,
+            &release(DateFilterRange::OlderThanMonths { value: 3 }),
+        )
+        .await?;
+        assert_eq_not_orderd(&result, &[4]);
+
+        Ok(())
+    }
+}
+
+// フォルダフィルタのテスト
+mod test_folder_filter {
+    use crate::filter::FolderFilterRange;
+
+    use super::*;
+
+    fn in_folder(path: &str, recursive: bool) -> FilterTarget {
+        FilterTarget::Folder {
+            range: FolderFilterRange::In {
+                value: path.parse().unwrap(),
+                recursive,
+            },
+        }
+    }
+
+    fn not_in_folder(path: &str, recursive: bool) -> FilterTarget {
+        FilterTarget::Folder {
+            range: FolderFilterRange::NotIn {
+                value: path.parse().unwrap(),
+                recursive,
+            },
+        }
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("folder_filter"))]
+    async fn in_folder_direct(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &in_folder("Artist/", false)).await?;
+
+        assert_eq_not_orderd(&result, &[1]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("folder_filter"))]
+    async fn in_folder_recursive(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &in_folder("Artist/", true)).await?;
+        assert_eq_not_orderd(&result, &[1, 2]);
+
+        // 末尾の / は省略可
+        let result = get_track_ids(&mut tx, &in_folder("Artist/Album", true)).await?;
+        assert_eq_not_orderd(&result, &[2]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("folder_filter"))]
+    async fn not_in_folder_direct(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &not_in_folder("Artist/", false)).await?;
+
+        // ライブラリ直下の曲も含む
+        assert_eq_not_orderd(&result, &[2, 3, 4, 5, 6]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("folder_filter"))]
+    async fn not_in_folder_recursive(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &not_in_folder("Artist/", true)).await?;
+
+        assert_eq_not_orderd(&result, &[3, 4, 5, 6]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("folder_filter"))]
+    async fn in_folder_like_escape(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &in_folder("Art_ist/", true)).await?;
+
+        assert_eq_not_orderd(&result, &[3]);
+        Ok(())
+    }
+}
+
+// プレイリストフィルタのテスト
+mod test_playlist_filter {
+    use crate::filter::PlaylistFilterRange;
+
+    use super::*;
+
+    fn filter(range: PlaylistFilterRange) -> FilterTarget {
+        FilterTarget::InPlaylist { range }
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("playlist_filter"))]
+    async fn in_playlist(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(&mut tx, &filter(PlaylistFilterRange::In { value: 2 })).await?;
+
+        assert_eq_not_orderd(&result, &[2, 3]);
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("playlist_filter"))]
+    async fn in_and_not_in_playlist(pool: PgPool) -> anyhow::Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = get_track_ids(
+            &mut tx,
+            &FilterTarget::FilterGroup {
+                op: crate::filter::GroupOperand::And,
+                children: vec![
+                    filter(PlaylistFilterRange::In { value: 1 }),
+                    filter(PlaylistFilterRange::NotIn { value: 2 }),
+                ],
+            },
+        )
+        .await?;
+
+        assert_eq_not_orderd(&result, &[1]);
+        Ok(())
+    }
 }
@@ -0,0 +1,187 @@
+//! フィルタの説明文のテスト
+
+use sqlx::PgPool;
+
+use crate::filter::{
+    DateFilterRange, DescriptionLanguage, FilterNames, FilterTarget, GroupOperand, IntFilterRange,
+    PlaylistFilterRange, StringFilterRange, TagsFilterRange,
+    filter_description::{describe, describe_with_db},
+};
+
+fn artist_contain(value: &str) -> FilterTarget {
+    FilterTarget::Artist {
+        range: StringFilterRange::Contain {
+            value: value.to_owned(),
+            normalize: false,
+        },
+    }
+}
+
+fn rating_4() -> FilterTarget {
+    FilterTarget::Rating {
+        range: IntFilterRange::LargeEqual { value: 4 },
+    }
+}
+
+fn tag(value: i32) -> FilterTarget {
+    FilterTarget::Tags {
+        range: TagsFilterRange::Contain { value },
+    }
+}
+
+/// artist contains X and (tag:1 or rating >= 4)
+fn nested_filter() -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::And,
+        children: vec![
+            artist_contain("X"),
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Or,
+                children: vec![tag(1), rating_4()],
+            },
+        ],
+    }
+}
+
+fn names() -> FilterNames {
+    FilterNames {
+        tags: [(1, "お気に入り".to_owned())].into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_describe_nested_group() {
+    assert_eq!(
+        describe(&nested_filter(), DescriptionLanguage::Japanese, &names()),
+        "アーティストが「X」を含む かつ (タグ「お気に入り」を含む または レート 4以上)"
+    );
+    assert_eq!(
+        describe(&nested_filter(), DescriptionLanguage::English, &names()),
+        "Artist contains \"X\" and (has tag \"お気に入り\" or Rating is at least 4)"
+    );
+}
+
+#[test]
+fn test_describe_negated_group() {
+    let filter = FilterTarget::FilterGroup {
+        op: GroupOperand::And,
+        children: vec![
+            rating_4(),
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Nor,
+                children: vec![artist_contain("X"), tag(1)],
+            },
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Not,
+                children: vec![tag(2)],
+            },
+        ],
+    };
+
+    assert_eq!(
+        describe(&filter, DescriptionLanguage::Japanese, &names()),
+        "レート 4以上 かつ ((アーティストが「X」を含む または タグ「お気に入り」を含む) ではない) かつ (タグ#2を含む ではない)"
+    );
+    assert_eq!(
+        describe(&filter, DescriptionLanguage::English, &names()),
+        "Rating is at least 4 and (not (Artist contains \"X\" or has tag \"お気に入り\")) and (not has tag #2)"
+    );
+}
+
+#[test]
+fn test_describe_unknown_name() {
+    let filter = FilterTarget::Tags {
+        range: TagsFilterRange::ContainAny { values: vec![1, 9] },
+    };
+
+    assert_eq!(
+        describe(&filter, DescriptionLanguage::Japanese, &names()),
+        "タグ「お気に入り」#9のいずれかを含む"
+    );
+    assert_eq!(
+        describe(&filter, DescriptionLanguage::English, &names()),
+        "has any of tags \"お気に入り\", #9"
+    );
+}
+
+#[test]
+fn test_describe_empty_group() {
+    let filter = FilterTarget::FilterGroup {
+        op: GroupOperand::And,
+        children: vec![],
+    };
+
+    assert_eq!(
+        describe(&filter, DescriptionLanguage::Japanese, &names()),
+        "条件なし"
+    );
+    assert_eq!(
+        describe(&filter, DescriptionLanguage::English, &names()),
+        "no conditions"
+    );
+}
+
+#[test]
+fn test_describe_values() {
+    let filter = FilterTarget::FilterGroup {
+        op: GroupOperand::Or,
+        children: vec![
+            FilterTarget::Duration {
+                range: IntFilterRange::RangeOut {
+                    min: 90_000,
+                    max: 300_500,
+                    include_null: true,
+                },
+            },
+            FilterTarget::EntryDate {
+                range: DateFilterRange::WithinDays { value: 7 },
+            },
+            FilterTarget::Title {
+                range: StringFilterRange::Equal {
+                    value: "abc".to_owned(),
+                    normalize: true,
+                },
+            },
+        ],
+    };
+
+    assert_eq!(
+        describe(&filter, DescriptionLanguage::Japanese, &names()),
+        "再生時間が1:30〜5:00.500以外 (値なしを含む) または 登録日が7日以内 または 曲名が「abc」と等しい (表記ゆれを無視)"
+    );
+    assert_eq!(
+        describe(&filter, DescriptionLanguage::English, &names()),
+        "Duration is not between 1:30 and 5:00.500 (including empty) or Entry date is within the last 7 days or Title is \"abc\" (ignoring variants)"
+    );
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_validation"))]
+async fn test_describe_with_db(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let filter = FilterTarget::FilterGroup {
+        op: GroupOperand::And,
+        children: vec![
+            tag(2),
+            FilterTarget::Tags {
+                range: TagsFilterRange::GroupNone { group_id: 1 },
+            },
+            FilterTarget::InPlaylist {
+                range: PlaylistFilterRange::NotIn { value: 2 },
+            },
+        ],
+    };
+
+    let names = FilterNames::from_db(&mut tx, &filter).await?;
+    assert_eq!(names.tags, [(2, "tag2".to_owned())].into());
+    assert_eq!(names.tag_groups, [(1, "group1".to_owned())].into());
+    assert_eq!(names.playlists, [(2, "Normal Playlist".to_owned())].into());
+
+    assert_eq!(
+        describe_with_db(&mut tx, &filter, DescriptionLanguage::Japanese).await?,
+        "タグ「tag2」を含む かつ タググループ「group1」のタグを含まない かつ プレイリスト「Normal Playlist」に含まれない"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! フィルタの保存形式のテスト
+
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::filter::{
+    FILTER_SCHEMA_VERSION, FilterError, FilterTarget, GroupOperand, IntFilterRange,
+    TagsFilterRange, filter_document,
+};
+
+fn rating_filter() -> FilterTarget {
+    FilterTarget::Rating {
+        range: IntFilterRange::LargeEqual { value: 4 },
+    }
+}
+
+#[test]
+fn test_to_json() {
+    assert_eq!(
+        filter_document::to_json(&rating_filter()),
+        json!({
+            "version": FILTER_SCHEMA_VERSION,
+            "filter": {
+                "target": "rating",
+                "range": {"op": "large_equal", "value": 4},
+            },
+        })
+    );
+}
+
+#[test]
+fn test_round_trip() -> anyhow::Result<()> {
+    let json = filter_document::to_json(&rating_filter());
+
+    assert!(filter_document::is_current(&json));
+    assert_eq!(filter_document::from_json(json)?, rating_filter());
+    Ok(())
+}
+
+#[test]
+fn test_from_legacy_json() -> anyhow::Result<()> {
+    let json = json!({
+        "target": "rating",
+        "range": {"op": "large_equal", "value": 4},
+    });
+
+    assert!(!filter_document::is_current(&json));
+    assert_eq!(filter_document::from_json(json)?, rating_filter());
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_version() {
+    let json = json!({
+        "version": FILTER_SCHEMA_VERSION + 1,
+        "filter": {"target": "group", "op": "and", "children": []},
+    });
+
+    assert!(matches!(
+        filter_document::from_json(json),
+        Err(FilterError::UnsupportedSchemaVersion { version }) if version == FILTER_SCHEMA_VERSION + 1
+    ));
+}
+
+#[test]
+fn test_invalid_version() {
+    let json = json!({"version": "1", "filter": {}});
+
+    assert!(matches!(
+        filter_document::from_json(json),
+        Err(FilterError::InvalidSchemaVersion)
+    ));
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_document"))]
+async fn test_migrate_stored_filters(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let count = filter_document::migrate_stored_filters(&mut tx).await?;
+    assert_eq!(count, 2);
+
+    let playlists = sqlx::query!("SELECT filter_json FROM playlists ORDER BY id")
+        .fetch_all(&mut *tx)
+        .await?;
+    assert_eq!(
+        playlists[0].filter_json,
+        Some(filter_document::to_json(&rating_filter()))
+    );
+    assert_eq!(
+        playlists[1].filter_json,
+        Some(filter_document::to_json(&FilterTarget::Tags {
+            range: TagsFilterRange::Contain { value: 3 },
+        }))
+    );
+    assert_eq!(playlists[2].filter_json, None);
+
+    let preset = sqlx::query_scalar!("SELECT filter_json FROM search_presets WHERE id = 1")
+        .fetch_one(&mut *tx)
+        .await?;
+    assert_eq!(
+        preset,
+        filter_document::to_json(&FilterTarget::FilterGroup {
+            op: GroupOperand::And,
+            children: vec![],
+        })
+    );
+
+    // 全て現在の形式になっていれば、書き換えない
+    let count = filter_document::migrate_stored_filters(&mut tx).await?;
+    assert_eq!(count, 0);
+
+    Ok(())
+}
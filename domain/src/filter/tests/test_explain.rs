@@ -0,0 +1,158 @@
+//! フィルタの判定結果の説明のテスト
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use super::test_db::test_context;
+use crate::filter::{
+    DateFilterRange, FilterError, FilterExplanation, FilterTarget, GroupOperand, IntFilterRange,
+    PlaylistFilterRange, StringFilterRange, TagsFilterRange, filter_explain::explain,
+};
+use crate::track_query::TrackQueryError;
+
+fn artist_taro() -> FilterTarget {
+    FilterTarget::Artist {
+        range: StringFilterRange::Equal {
+            value: "taro".to_owned(),
+            normalize: false,
+        },
+    }
+}
+
+fn tag_45() -> FilterTarget {
+    FilterTarget::Tags {
+        range: TagsFilterRange::Contain { value: 45 },
+    }
+}
+
+fn rating_4() -> FilterTarget {
+    FilterTarget::Rating {
+        range: IntFilterRange::LargeEqual { value: 4 },
+    }
+}
+
+fn empty_group() -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::Or,
+        children: vec![],
+    }
+}
+
+/// artist = taro and (tag:45 or rating >= 4 or ())
+fn filter() -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::And,
+        children: vec![
+            artist_taro(),
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Or,
+                children: vec![tag_45(), rating_4(), empty_group()],
+            },
+        ],
+    }
+}
+
+fn clause(target: FilterTarget, matched: bool) -> FilterExplanation {
+    FilterExplanation::Clause { target, matched }
+}
+
+fn expected(artist: bool, tag: bool, rating: bool, or: bool, and: bool) -> FilterExplanation {
+    FilterExplanation::Group {
+        op: GroupOperand::And,
+        matched: Some(and),
+        children: vec![
+            clause(artist_taro(), artist),
+            FilterExplanation::Group {
+                op: GroupOperand::Or,
+                matched: Some(or),
+                children: vec![
+                    clause(tag_45(), tag),
+                    clause(rating_4(), rating),
+                    FilterExplanation::Group {
+                        op: GroupOperand::Or,
+                        matched: None,
+                        children: vec![],
+                    },
+                ],
+            },
+        ],
+    }
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("group_filter"))]
+async fn test_explain_matched(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    // taro, rating 3, tag 45
+    let result = explain(&mut tx, &filter(), 1, &test_context()).await?;
+
+    assert_eq!(result, expected(true, true, false, true, true));
+    assert!(result.matched());
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("group_filter"))]
+async fn test_explain_not_matched(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    // taro, rating 0, タグ 45 無し
+    let result = explain(&mut tx, &filter(), 4, &test_context()).await?;
+    assert_eq!(result, expected(true, false, false, false, false));
+    assert!(!result.matched());
+
+    // jiro, rating 4, tag 45
+    let result = explain(&mut tx, &filter(), 2, &test_context()).await?;
+    assert_eq!(result, expected(false, true, true, true, false));
+
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("group_filter"))]
+async fn test_explain_null_is_false(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    // release_date が NULL の曲は、!= の条件を満たさない
+    let target = FilterTarget::ReleaseDate {
+        range: DateFilterRange::NotEqual {
+            value: NaiveDate::from_ymd_opt(2021, 9, 25).unwrap(),
+            include_null: false,
+        },
+    };
+    let result = explain(&mut tx, &target, 1, &test_context()).await?;
+
+    assert_eq!(result, clause(target, false));
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("group_filter"))]
+async fn test_explain_track_not_found(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let result = explain(&mut tx, &filter(), 99, &test_context()).await;
+
+    assert!(matches!(
+        result,
+        Err(TrackQueryError::Filter(FilterError::TrackNotFound {
+            track_id: 99
+        }))
+    ));
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("explain_in_playlist"))]
+async fn test_explain_lists_up_referenced_playlist(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    // 検索時と同じく、未リストアップのプレイリストをリストアップしてから判定する
+    let target = FilterTarget::InPlaylist {
+        range: PlaylistFilterRange::In { value: 1 },
+    };
+
+    let result = explain(&mut tx, &target, 1, &test_context()).await?;
+    assert_eq!(result, clause(target.clone(), true));
+
+    let result = explain(&mut tx, &target, 2, &test_context()).await?;
+    assert_eq!(result, clause(target, false));
+
+    Ok(())
+}
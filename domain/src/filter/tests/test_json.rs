@@ -33,6 +33,7 @@ fn test_complex_and_or_combination() {
                 FilterTarget::Artist {
                     range: StringFilterRange::Contain {
                         value: "taro".to_owned(),
+                        normalize: false,
                     },
                 },
                 FilterTarget::FilterGroup {
@@ -108,11 +109,13 @@ mod test_group_operand {
                     FilterTarget::Artist {
                         range: StringFilterRange::Equal {
                             value: "Artist1".to_string(),
+                            normalize: false,
                         },
                     },
                     FilterTarget::Title {
                         range: StringFilterRange::Equal {
                             value: "Title1".to_string(),
+                            normalize: false,
                         },
                     },
                 ],
@@ -149,11 +152,13 @@ mod test_group_operand {
                     FilterTarget::Artist {
                         range: StringFilterRange::Equal {
                             value: "Artist1".to_string(),
+                            normalize: false,
                         },
                     },
                     FilterTarget::Title {
                         range: StringFilterRange::Equal {
                             value: "Title1".to_string(),
+                            normalize: false,
                         },
                     },
                 ],
@@ -182,6 +187,52 @@ mod test_group_operand {
     }
 }
 
+mod test_negated_group_operand {
+    use crate::filter::{FilterTarget, GroupOperand, TagsFilterRange};
+
+    use super::*;
+
+    fn group(op: GroupOperand) -> FilterTarget {
+        FilterTarget::FilterGroup {
+            op,
+            children: vec![FilterTarget::Tags {
+                range: TagsFilterRange::Contain { value: 1 },
+            }],
+        }
+    }
+
+    fn json(op: &str) -> serde_json::Value {
+        serde_json::json!({
+            "target": "group",
+            "op": op,
+            "children": [
+                {
+                    "target": "tags",
+                    "range": {
+                        "op": "contain",
+                        "value": 1,
+                    }
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn not() {
+        assert_serde(group(GroupOperand::Not), json("not"));
+    }
+
+    #[test]
+    fn nor() {
+        assert_serde(group(GroupOperand::Nor), json("nor"));
+    }
+
+    #[test]
+    fn nand() {
+        assert_serde(group(GroupOperand::Nand), json("nand"));
+    }
+}
+
 mod test_filter_target {
     use crate::filter::{
         ArtworkFilterRange, BoolFilterRange, DateFilterRange, FilterTarget, IntFilterRange,
@@ -229,6 +280,7 @@ mod test_filter_target {
             FilterTarget::Genre {
                 range: StringFilterRange::Equal {
                     value: "Rock".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -247,6 +299,7 @@ mod test_filter_target {
             FilterTarget::Artist {
                 range: StringFilterRange::Equal {
                     value: "Test Artist".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -265,6 +318,7 @@ mod test_filter_target {
             FilterTarget::AlbumArtist {
                 range: StringFilterRange::Equal {
                     value: "Test Album Artist".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -283,6 +337,7 @@ mod test_filter_target {
             FilterTarget::Album {
                 range: StringFilterRange::Equal {
                     value: "Test Album".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -301,6 +356,7 @@ mod test_filter_target {
             FilterTarget::Composer {
                 range: StringFilterRange::Equal {
                     value: "Test Composer".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -319,6 +375,7 @@ mod test_filter_target {
             FilterTarget::Title {
                 range: StringFilterRange::Equal {
                     value: "Test Title".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -449,6 +506,7 @@ mod test_filter_target {
             FilterTarget::Memo {
                 range: StringFilterRange::Equal {
                     value: "Test memo".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -467,6 +525,7 @@ mod test_filter_target {
             FilterTarget::MemoManage {
                 range: StringFilterRange::Equal {
                     value: "Test manage memo".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -502,6 +561,7 @@ mod test_filter_target {
             FilterTarget::OriginalTrack {
                 range: StringFilterRange::Equal {
                     value: "Original Track".to_string(),
+                    normalize: false,
                 },
             },
             serde_json::json!({
@@ -604,7 +664,10 @@ mod test_date_range {
     fn not_equal() {
         let date = NaiveDate::from_ymd_opt(2023, 5, 15).unwrap();
         assert_serde(
-            DateFilterRange::NotEqual { value: date },
+            DateFilterRange::NotEqual {
+                value: date,
+                include_null: false,
+            },
             serde_json::json!({
                 "op": "not_equal",
                 "value": date,
@@ -645,6 +708,32 @@ mod test_date_range {
             }),
         );
     }
+
+    #[test]
+    fn not_null() {
+        assert_serde(
+            DateFilterRange::NotNull,
+            serde_json::json!({
+                "op": "not_null",
+            }),
+        );
+    }
+
+    #[test]
+    fn not_equal_include_null() {
+        let date = NaiveDate::from_ymd_opt(2023, 5, 15).unwrap();
+        assert_serde(
+            DateFilterRange::NotEqual {
+                value: date,
+                include_null: true,
+            },
+            serde_json::json!({
+                "op": "not_equal",
+                "value": date,
+                "include_null": true,
+            }),
+        );
+    }
 }
 
 mod test_int_range {
@@ -666,7 +755,10 @@ mod test_int_range {
     #[test]
     fn not_equal() {
         assert_serde(
-            IntFilterRange::NotEqual { value: 42 },
+            IntFilterRange::NotEqual {
+                value: 42,
+                include_null: false,
+            },
             serde_json::json!({
                 "op": "not_equal",
                 "value": 42,
@@ -711,7 +803,11 @@ mod test_int_range {
     #[test]
     fn range_out() {
         assert_serde(
-            IntFilterRange::RangeOut { min: 10, max: 50 },
+            IntFilterRange::RangeOut {
+                min: 10,
+                max: 50,
+                include_null: false,
+            },
             serde_json::json!({
                 "op": "range_out",
                 "min": 10,
@@ -719,6 +815,43 @@ mod test_int_range {
             }),
         );
     }
+
+    #[test]
+    fn range_out_include_null() {
+        assert_serde(
+            IntFilterRange::RangeOut {
+                min: 10,
+                max: 50,
+                include_null: true,
+            },
+            serde_json::json!({
+                "op": "range_out",
+                "min": 10,
+                "max": 50,
+                "include_null": true,
+            }),
+        );
+    }
+
+    #[test]
+    fn is_null() {
+        assert_serde(
+            IntFilterRange::IsNull,
+            serde_json::json!({
+                "op": "is_null",
+            }),
+        );
+    }
+
+    #[test]
+    fn not_null() {
+        assert_serde(
+            IntFilterRange::NotNull,
+            serde_json::json!({
+                "op": "not_null",
+            }),
+        );
+    }
 }
 
 mod test_string_range {
@@ -731,6 +864,7 @@ mod test_string_range {
         assert_serde(
             StringFilterRange::Equal {
                 value: "test".to_string(),
+                normalize: false,
             },
             serde_json::json!({
                 "op": "equal",
@@ -744,6 +878,7 @@ mod test_string_range {
         assert_serde(
             StringFilterRange::NotEqual {
                 value: "test".to_string(),
+                normalize: false,
             },
             serde_json::json!({
                 "op": "not_equal",
@@ -757,6 +892,7 @@ mod test_string_range {
         assert_serde(
             StringFilterRange::Contain {
                 value: "test".to_string(),
+                normalize: false,
             },
             serde_json::json!({
                 "op": "contain",
@@ -770,6 +906,7 @@ mod test_string_range {
         assert_serde(
             StringFilterRange::NotContain {
                 value: "test".to_string(),
+                normalize: false,
             },
             serde_json::json!({
                 "op": "not_contain",
@@ -783,6 +920,7 @@ mod test_string_range {
         assert_serde(
             StringFilterRange::Start {
                 value: "test".to_string(),
+                normalize: false,
             },
             serde_json::json!({
                 "op": "start",
@@ -796,6 +934,7 @@ mod test_string_range {
         assert_serde(
             StringFilterRange::End {
                 value: "test".to_string(),
+                normalize: false,
             },
             serde_json::json!({
                 "op": "end",
@@ -803,6 +942,91 @@ mod test_string_range {
             }),
         );
     }
+
+    #[test]
+    fn normalize() {
+        assert_serde(
+            StringFilterRange::Contain {
+                value: "test".to_string(),
+                normalize: true,
+            },
+            serde_json::json!({
+                "op": "contain",
+                "value": "test",
+                "normalize": true,
+            }),
+        );
+    }
+
+    #[test]
+    fn normalize_omitted() {
+        // normalize が無い JSON は、false として扱う
+        let range: StringFilterRange = serde_json::from_value(serde_json::json!({
+            "op": "start",
+            "value": "test",
+        }))
+        .unwrap();
+
+        assert_eq!(
+            range,
+            StringFilterRange::Start {
+                value: "test".to_string(),
+                normalize: false,
+            }
+        );
+    }
+
+    #[test]
+    fn regex() {
+        assert_serde(
+            StringFilterRange::Regex {
+                value: "^te.*st$".to_string(),
+            },
+            serde_json::json!({
+                "op": "regex",
+                "value": "^te.*st$",
+            }),
+        );
+    }
+
+    #[test]
+    fn regex_ignore_case() {
+        assert_serde(
+            StringFilterRange::RegexIgnoreCase {
+                value: "^te.*st$".to_string(),
+            },
+            serde_json::json!({
+                "op": "regex_ignore_case",
+                "value": "^te.*st$",
+            }),
+        );
+    }
+
+    #[test]
+    fn not_regex() {
+        assert_serde(
+            StringFilterRange::NotRegex {
+                value: "^te.*st$".to_string(),
+            },
+            serde_json::json!({
+                "op": "not_regex",
+                "value": "^te.*st$",
+            }),
+        );
+    }
+
+    #[test]
+    fn not_regex_ignore_case() {
+        assert_serde(
+            StringFilterRange::NotRegexIgnoreCase {
+                value: "^te.*st$".to_string(),
+            },
+            serde_json::json!({
+                "op": "not_regex_ignore_case",
+                "value": "^te.*st$",
+            }),
+        );
+    }
 }
 
 mod test_tags_range {
@@ -0,0 +1,486 @@
+//! `FilterTarget::matches` の判定結果が、DB での検索結果と一致するかのテスト
+
+use chrono::NaiveDate;
+use sqlx::{PgPool, PgTransaction};
+
+use super::test_db::{get_track_ids, test_context};
+use crate::{
+    filter::{
+        ArtworkFilterRange, BoolFilterRange, DateFilterRange, FilterTarget, FolderFilterRange,
+        GroupOperand, IntFilterRange, PlaylistFilterRange, StringFilterRange, TagsFilterRange,
+    },
+    path::LibraryDirectoryPath,
+    track::{TrackDuration, TrackRecord},
+};
+
+/// DB の全ての曲データを取得
+async fn get_all_tracks<'c>(tx: &mut PgTransaction<'c>) -> sqlx::Result<Vec<TrackRecord>> {
+    sqlx::query_as!(
+        TrackRecord,
+        r#"
+        SELECT
+          id,
+          title,
+          artist,
+          album_artist,
+          album,
+          composer,
+          genre,
+          duration AS "duration: TrackDuration",
+          track_number,
+          track_max,
+          disc_number,
+          disc_max,
+          release_date,
+          rating,
+          original_track,
+          suggest_target,
+          memo,
+          memo_manage,
+          title_order,
+          artist_order,
+          album_artist_order,
+          album_order,
+          composer_order,
+          genre_order,
+          created_at,
+          ARRAY(SELECT tag_id FROM track_tags WHERE track_id = tracks.id) AS "tag_ids!",
+          ARRAY(
+            SELECT DISTINCT tags.group_id FROM track_tags JOIN tags ON tags.id = track_tags.tag_id
+            WHERE track_id = tracks.id
+          ) AS "tag_group_ids!",
+          EXISTS(SELECT * FROM track_artworks WHERE track_id = tracks.id) AS "has_artwork!",
+          (SELECT path FROM folder_paths WHERE id = tracks.folder_id) AS "folder_path: LibraryDirectoryPath",
+          ARRAY(SELECT DISTINCT playlist_id FROM playlist_tracks WHERE track_id = tracks.id) AS "playlist_ids!"
+        FROM tracks
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await
+}
+
+/// 各フィルタについて、DB での検索結果と matches() の結果が一致するか確認
+async fn assert_matches_same_as_db(pool: &PgPool, filters: &[FilterTarget]) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let tracks = get_all_tracks(&mut tx).await?;
+
+    for filter in filters {
+        let mut expected = get_track_ids(&mut tx, filter).await?;
+        expected.sort();
+
+        let mut actual: Vec<i32> = tracks
+            .iter()
+            .filter(|track| filter.matches(track, &test_context()))
+            .map(|track| track.id)
+            .collect();
+        actual.sort();
+
+        assert_eq!(actual, expected, "filter: {filter:?}");
+    }
+
+    Ok(())
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("group_filter"))]
+async fn group_filter(pool: PgPool) -> anyhow::Result<()> {
+    let artist_taro = FilterTarget::Artist {
+        range: StringFilterRange::Contain {
+            value: "taro".to_owned(),
+            normalize: false,
+        },
+    };
+    let or_group = FilterTarget::FilterGroup {
+        op: GroupOperand::Or,
+        children: vec![
+            FilterTarget::Tags {
+                range: TagsFilterRange::Contain { value: 45 },
+            },
+            FilterTarget::Rating {
+                range: IntFilterRange::LargeEqual { value: 4 },
+            },
+            FilterTarget::ReleaseDate {
+                range: DateFilterRange::Equal {
+                    value: date(2021, 9, 25),
+                },
+            },
+        ],
+    };
+    let empty_group = FilterTarget::FilterGroup {
+        op: GroupOperand::Or,
+        children: vec![],
+    };
+
+    assert_matches_same_as_db(
+        &pool,
+        &[
+            FilterTarget::FilterGroup {
+                op: GroupOperand::And,
+                children: vec![artist_taro.clone(), or_group.clone()],
+            },
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Or,
+                children: vec![artist_taro.clone(), or_group.clone()],
+            },
+            // 空のグループは条件なしとして扱われる
+            empty_group.clone(),
+            FilterTarget::FilterGroup {
+                op: GroupOperand::And,
+                children: vec![empty_group.clone(), artist_taro.clone()],
+            },
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Or,
+                children: vec![empty_group.clone()],
+            },
+            // 否定のグループは、NULL になる条件を満たさないものとして否定する
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Not,
+                children: vec![or_group.clone()],
+            },
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Nor,
+                children: vec![artist_taro.clone(), or_group.clone()],
+            },
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Nand,
+                children: vec![artist_taro.clone(), or_group],
+            },
+            FilterTarget::FilterGroup {
+                op: GroupOperand::Not,
+                children: vec![empty_group],
+            },
+        ],
+    )
+    .await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("string_filter"))]
+async fn string_filter(pool: PgPool) -> anyhow::Result<()> {
+    let ranges = ["test", "te%st", "AA", ""].into_iter().flat_map(|value| {
+        let value = value.to_owned();
+        [
+            StringFilterRange::Equal {
+                value: value.clone(),
+                normalize: false,
+            },
+            StringFilterRange::NotEqual {
+                value: value.clone(),
+                normalize: false,
+            },
+            StringFilterRange::Contain {
+                value: value.clone(),
+                normalize: false,
+            },
+            StringFilterRange::NotContain {
+                value: value.clone(),
+                normalize: false,
+            },
+            StringFilterRange::Start {
+                value: value.clone(),
+                normalize: false,
+            },
+            StringFilterRange::End {
+                value: value.clone(),
+                normalize: true,
+            },
+            StringFilterRange::Regex {
+                value: value.clone(),
+            },
+            StringFilterRange::RegexIgnoreCase {
+                value: value.clone(),
+            },
+            StringFilterRange::NotRegex {
+                value: value.clone(),
+            },
+            StringFilterRange::NotRegexIgnoreCase { value },
+        ]
+    });
+
+    let filters: Vec<_> = ranges.map(|range| FilterTarget::Artist { range }).collect();
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("regex_filter"))]
+async fn regex_filter(pool: PgPool) -> anyhow::Result<()> {
+    // validate_regex で受け付ける構文は、PostgreSQL でも同じ意味となること
+    let patterns = [
+        "^song",
+        r"\(remix\)$",
+        "first.second",
+        "line$",
+        "^$",
+        "[a-z]+_[0-9]{3}",
+        r"[\[\]]",
+        r"a-b\{2\}",
+        r"A{2,}?\^\$",
+        "(?:live|remix)",
+        "[^a-z ]",
+        "[-_]",
+        "^.{5,255}$",
+        "ébène",
+    ];
+
+    let mut filters = Vec::new();
+    for pattern in patterns {
+        let value = pattern.to_owned();
+        let ranges = [
+            StringFilterRange::Regex {
+                value: value.clone(),
+            },
+            StringFilterRange::RegexIgnoreCase {
+                value: value.clone(),
+            },
+            StringFilterRange::NotRegex {
+                value: value.clone(),
+            },
+            StringFilterRange::NotRegexIgnoreCase { value },
+        ];
+        for range in ranges {
+            range.validate_regex()?;
+            filters.push(FilterTarget::Title { range });
+        }
+    }
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("normalized_string_filter"))]
+async fn normalized_string_filter(pool: PgPool) -> anyhow::Result<()> {
+    let mut filters = Vec::new();
+    for value in ["ｶﾞﾗ", "ガラス", "abc", "Abc"] {
+        filters.push(FilterTarget::Title {
+            range: StringFilterRange::Contain {
+                value: value.to_owned(),
+                normalize: true,
+            },
+        });
+        filters.push(FilterTarget::Memo {
+            range: StringFilterRange::Start {
+                value: value.to_owned(),
+                normalize: true,
+            },
+        });
+        filters.push(FilterTarget::Memo {
+            range: StringFilterRange::NotEqual {
+                value: value.to_owned(),
+                normalize: true,
+            },
+        });
+    }
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("int_filter"))]
+async fn int_filter(pool: PgPool) -> anyhow::Result<()> {
+    let ranges = [
+        IntFilterRange::Equal { value: 9 },
+        IntFilterRange::NotEqual {
+            value: 25,
+            include_null: false,
+        },
+        IntFilterRange::LargeEqual { value: 10 },
+        IntFilterRange::SmallEqual { value: 5 },
+        IntFilterRange::RangeIn { min: 9, max: 25 },
+        IntFilterRange::RangeIn { min: 25, max: 9 },
+        IntFilterRange::RangeOut {
+            min: 5,
+            max: 10,
+            include_null: false,
+        },
+        IntFilterRange::NotEqual {
+            value: 25,
+            include_null: true,
+        },
+        IntFilterRange::RangeOut {
+            min: 5,
+            max: 10,
+            include_null: true,
+        },
+        IntFilterRange::IsNull,
+        IntFilterRange::NotNull,
+    ];
+
+    let mut filters = Vec::new();
+    for range in ranges {
+        filters.push(FilterTarget::TrackNumber {
+            range: range.clone(),
+        });
+        filters.push(FilterTarget::Duration {
+            range: range.clone(),
+        });
+        filters.push(FilterTarget::Rating { range });
+    }
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("tags_filter"))]
+async fn tags_filter(pool: PgPool) -> anyhow::Result<()> {
+    let filters: Vec<_> = [
+        TagsFilterRange::Contain { value: 4 },
+        TagsFilterRange::NotContain { value: 4 },
+        TagsFilterRange::Contain { value: 5 },
+        TagsFilterRange::NotContain { value: 83 },
+        TagsFilterRange::None,
+        TagsFilterRange::ContainAny {
+            values: vec![8, 4, 5],
+        },
+        TagsFilterRange::ContainAny { values: vec![] },
+        TagsFilterRange::ContainAll {
+            values: vec![83, 4, 83],
+        },
+        TagsFilterRange::ContainAll { values: vec![] },
+        TagsFilterRange::ContainNone { values: vec![4, 8] },
+        TagsFilterRange::GroupAny { group_id: 2 },
+        TagsFilterRange::GroupNone { group_id: 1 },
+        TagsFilterRange::GroupAny { group_id: 3 },
+    ]
+    .into_iter()
+    .map(|range| FilterTarget::Tags { range })
+    .collect();
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("bool_filter"))]
+async fn bool_filter(pool: PgPool) -> anyhow::Result<()> {
+    assert_matches_same_as_db(
+        &pool,
+        &[
+            FilterTarget::SuggestTarget {
+                range: BoolFilterRange::True,
+            },
+            FilterTarget::SuggestTarget {
+                range: BoolFilterRange::False,
+            },
+        ],
+    )
+    .await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("artwork_filter"))]
+async fn artwork_filter(pool: PgPool) -> anyhow::Result<()> {
+    assert_matches_same_as_db(
+        &pool,
+        &[
+            FilterTarget::Artwork {
+                range: ArtworkFilterRange::Has,
+            },
+            FilterTarget::Artwork {
+                range: ArtworkFilterRange::None,
+            },
+        ],
+    )
+    .await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("date_filter"))]
+async fn date_filter(pool: PgPool) -> anyhow::Result<()> {
+    let ranges = [
+        DateFilterRange::Equal {
+            value: date(2012, 4, 5),
+        },
+        DateFilterRange::NotEqual {
+            value: date(2012, 4, 5),
+            include_null: false,
+        },
+        DateFilterRange::Before {
+            value: date(2012, 11, 12),
+        },
+        DateFilterRange::After {
+            value: date(2012, 4, 5),
+        },
+        DateFilterRange::None,
+        DateFilterRange::NotNull,
+        DateFilterRange::NotEqual {
+            value: date(2012, 4, 5),
+            include_null: true,
+        },
+    ];
+
+    let mut filters = Vec::new();
+    for range in ranges {
+        filters.push(FilterTarget::ReleaseDate {
+            range: range.clone(),
+        });
+        filters.push(FilterTarget::EntryDate { range });
+    }
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("entry_date_filter"))]
+async fn entry_date_filter(pool: PgPool) -> anyhow::Result<()> {
+    let ranges = [
+        DateFilterRange::Equal {
+            value: date(2024, 3, 10),
+        },
+        DateFilterRange::NotEqual {
+            value: date(2024, 3, 11),
+            include_null: false,
+        },
+        DateFilterRange::Before {
+            value: date(2024, 3, 10),
+        },
+        DateFilterRange::After {
+            value: date(2024, 3, 20),
+        },
+        DateFilterRange::WithinDays { value: 9 },
+        DateFilterRange::WithinMonths { value: 3 },
+        DateFilterRange::OlderThanDays { value: 7 },
+        DateFilterRange::OlderThanMonths { value: 3 },
+    ];
+
+    let mut filters = Vec::new();
+    for range in ranges {
+        filters.push(FilterTarget::ReleaseDate {
+            range: range.clone(),
+        });
+        filters.push(FilterTarget::EntryDate { range });
+    }
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("folder_filter"))]
+async fn folder_filter(pool: PgPool) -> anyhow::Result<()> {
+    let mut filters = Vec::new();
+    for path in ["Artist/", "Artist/Album/", "Art_ist/", "Nothing/"] {
+        for recursive in [false, true] {
+            let value: LibraryDirectoryPath = path.parse()?;
+            filters.push(FilterTarget::Folder {
+                range: FolderFilterRange::In {
+                    value: value.clone(),
+                    recursive,
+                },
+            });
+            filters.push(FilterTarget::Folder {
+                range: FolderFilterRange::NotIn { value, recursive },
+            });
+        }
+    }
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("playlist_filter"))]
+async fn playlist_filter(pool: PgPool) -> anyhow::Result<()> {
+    let filters: Vec<_> = [1, 2, 3]
+        .into_iter()
+        .flat_map(|value| {
+            [
+                PlaylistFilterRange::In { value },
+                PlaylistFilterRange::NotIn { value },
+            ]
+        })
+        .map(|range| FilterTarget::InPlaylist { range })
+        .collect();
+
+    assert_matches_same_as_db(&pool, &filters).await
+}
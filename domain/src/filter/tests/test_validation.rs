@@ -0,0 +1,518 @@
+//! フィルタの検証・正規化のテスト
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use crate::{
+    filter::{
+        DateFilterRange, FilterError, FilterTarget, FilterWarning, FilterWarningKind,
+        FolderFilterRange, GroupOperand, IntFilterRange, PlaylistFilterRange, StringFilterRange,
+        TagsFilterRange, filter_document,
+        filter_validation::{normalize, validate},
+    },
+    playlist::{playlist_error::PlaylistError, playlist_repository},
+    search_preset::search_preset_sqls,
+};
+
+fn and(children: Vec<FilterTarget>) -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::And,
+        children,
+    }
+}
+
+fn or(children: Vec<FilterTarget>) -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::Or,
+        children,
+    }
+}
+
+fn artist(value: &str) -> FilterTarget {
+    FilterTarget::Artist {
+        range: StringFilterRange::Equal {
+            value: value.to_owned(),
+            normalize: false,
+        },
+    }
+}
+
+fn not_artist(value: &str) -> FilterTarget {
+    FilterTarget::Artist {
+        range: StringFilterRange::NotEqual {
+            value: value.to_owned(),
+            normalize: false,
+        },
+    }
+}
+
+fn tag(value: i32) -> FilterTarget {
+    FilterTarget::Tags {
+        range: TagsFilterRange::Contain { value },
+    }
+}
+
+#[test]
+fn test_normalize_flatten() {
+    let filter = and(vec![
+        artist("a"),
+        and(vec![artist("b"), and(vec![artist("c")])]),
+        or(vec![artist("d"), or(vec![artist("e"), artist("f")])]),
+        or(vec![and(vec![]), artist("g")]),
+    ]);
+
+    let (filter, warnings) = normalize(filter);
+
+    assert_eq!(
+        filter,
+        and(vec![
+            artist("a"),
+            artist("b"),
+            artist("c"),
+            or(vec![artist("d"), artist("e"), artist("f")]),
+            artist("g"),
+        ])
+    );
+    assert_eq!(warnings, vec![]);
+}
+
+#[test]
+fn test_normalize_empty() {
+    let (filter, warnings) = normalize(or(vec![and(vec![or(vec![])]), and(vec![])]));
+
+    assert_eq!(filter, or(vec![]));
+    assert_eq!(warnings, vec![]);
+}
+
+#[test]
+fn test_normalize_root_single_group() {
+    // 最上位はグループのまま残す
+    let (filter, _) = normalize(and(vec![or(vec![artist("a"), artist("b")])]));
+    assert_eq!(filter, or(vec![artist("a"), artist("b")]));
+
+    let (filter, _) = normalize(or(vec![and(vec![artist("a")])]));
+    assert_eq!(filter, or(vec![artist("a")]));
+
+    let (filter, _) = normalize(artist("a"));
+    assert_eq!(filter, artist("a"));
+}
+
+#[test]
+fn test_normalize_negated_group() {
+    let not = |children| FilterTarget::FilterGroup {
+        op: GroupOperand::Not,
+        children,
+    };
+    let nor = |children| FilterTarget::FilterGroup {
+        op: GroupOperand::Nor,
+        children,
+    };
+
+    // 否定のグループは展開・置き換えしない
+    let filter = and(vec![
+        artist("a"),
+        not(vec![not(vec![artist("b")])]),
+        nor(vec![artist("c"), nor(vec![artist("d")]), not(vec![])]),
+    ]);
+    let (normalized, warnings) = normalize(filter.clone());
+    assert_eq!(
+        normalized,
+        and(vec![
+            artist("a"),
+            not(vec![not(vec![artist("b")])]),
+            nor(vec![artist("c"), nor(vec![artist("d")])]),
+        ])
+    );
+    assert_eq!(warnings, vec![]);
+
+    // 最上位の否定のグループの子要素は、最上位に置き換えない
+    let (normalized, _) = normalize(not(vec![and(vec![artist("a"), artist("b")])]));
+    assert_eq!(normalized, not(vec![and(vec![artist("a"), artist("b")])]));
+}
+
+#[test]
+fn test_normalize_reversed_range() {
+    let filter = or(vec![
+        FilterTarget::Rating {
+            range: IntFilterRange::RangeIn { min: 5, max: 3 },
+        },
+        and(vec![
+            artist("a"),
+            FilterTarget::Duration {
+                range: IntFilterRange::RangeOut {
+                    min: 2000,
+                    max: 1000,
+                    include_null: false,
+                },
+            },
+        ]),
+    ]);
+
+    let (filter, warnings) = normalize(filter);
+
+    assert_eq!(
+        filter,
+        or(vec![
+            FilterTarget::Rating {
+                range: IntFilterRange::RangeIn { min: 3, max: 5 },
+            },
+            and(vec![
+                artist("a"),
+                FilterTarget::Duration {
+                    range: IntFilterRange::RangeOut {
+                        min: 1000,
+                        max: 2000,
+                        include_null: false,
+                    },
+                },
+            ]),
+        ])
+    );
+    assert_eq!(
+        warnings,
+        vec![
+            FilterWarning {
+                path: vec![0],
+                kind: FilterWarningKind::ReversedRange { min: 5, max: 3 },
+            },
+            FilterWarning {
+                path: vec![1, 1],
+                kind: FilterWarningKind::ReversedRange {
+                    min: 2000,
+                    max: 1000,
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_normalize_contradiction() {
+    let filter = or(vec![
+        and(vec![artist("a"), tag(1), and(vec![not_artist("a")])]),
+        // OR グループ内は矛盾ではない
+        artist("b"),
+        not_artist("b"),
+        // 値が異なれば矛盾ではない
+        and(vec![artist("c"), not_artist("d")]),
+    ]);
+
+    let (_, warnings) = normalize(filter);
+
+    assert_eq!(
+        warnings,
+        vec![FilterWarning {
+            path: vec![0, 2],
+            kind: FilterWarningKind::Contradiction { other: vec![0, 0] },
+        }]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "[0, 2]: [0, 0] の条件と矛盾するため、条件を満たす曲がありません"
+    );
+}
+
+#[test]
+fn test_normalize_contradiction_semantic() {
+    let rating = |range: IntFilterRange| FilterTarget::Rating { range };
+    let release = |range: DateFilterRange| FilterTarget::ReleaseDate { range };
+    let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+
+    let filter = or(vec![
+        // include_null の有無に関わらず、否定の関係
+        and(vec![
+            rating(IntFilterRange::NotEqual {
+                value: 3,
+                include_null: true,
+            }),
+            rating(IntFilterRange::Equal { value: 3 }),
+        ]),
+        // 範囲が重ならない
+        and(vec![
+            rating(IntFilterRange::LargeEqual { value: 4 }),
+            rating(IntFilterRange::RangeIn { min: 1, max: 3 }),
+            release(DateFilterRange::Before {
+                value: date("2000-01-01"),
+            }),
+            release(DateFilterRange::After {
+                value: date("2000-01-02"),
+            }),
+        ]),
+        // 範囲が重なれば矛盾ではない
+        and(vec![
+            rating(IntFilterRange::SmallEqual { value: 3 }),
+            rating(IntFilterRange::RangeIn { min: 3, max: 5 }),
+            release(DateFilterRange::Equal {
+                value: date("2000-01-01"),
+            }),
+            release(DateFilterRange::Before {
+                value: date("2000-01-01"),
+            }),
+            // 項目が異なれば矛盾ではない
+            FilterTarget::EntryDate {
+                range: DateFilterRange::After {
+                    value: date("2000-01-02"),
+                },
+            },
+        ]),
+    ]);
+
+    let (_, warnings) = normalize(filter);
+
+    assert_eq!(
+        warnings,
+        vec![
+            FilterWarning {
+                path: vec![0, 1],
+                kind: FilterWarningKind::Contradiction { other: vec![0, 0] },
+            },
+            FilterWarning {
+                path: vec![1, 1],
+                kind: FilterWarningKind::Contradiction { other: vec![1, 0] },
+            },
+            FilterWarning {
+                path: vec![1, 3],
+                kind: FilterWarningKind::Contradiction { other: vec![1, 2] },
+            },
+        ]
+    );
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_validation"))]
+async fn test_validate_unknown_tag(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let filter = and(vec![
+        tag(1),
+        or(vec![
+            tag(3),
+            FilterTarget::Tags {
+                range: TagsFilterRange::NotContain { value: 2 },
+            },
+        ]),
+        FilterTarget::Tags {
+            range: TagsFilterRange::NotContain { value: 4 },
+        },
+    ]);
+
+    let validation = validate(&mut tx, filter.clone()).await?;
+
+    assert_eq!(validation.filter, filter);
+    assert_eq!(
+        validation.warnings,
+        vec![
+            FilterWarning {
+                path: vec![1, 0],
+                kind: FilterWarningKind::UnknownTag { tag_id: 3 },
+            },
+            FilterWarning {
+                path: vec![2],
+                kind: FilterWarningKind::UnknownTag { tag_id: 4 },
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_validation"))]
+async fn test_validate_unknown_tag_in_list_and_group(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let filter = or(vec![
+        FilterTarget::Tags {
+            range: TagsFilterRange::ContainAny {
+                values: vec![1, 3, 2, 4],
+            },
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::GroupAny { group_id: 1 },
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::GroupNone { group_id: 9 },
+        },
+    ]);
+
+    let validation = validate(&mut tx, filter).await?;
+
+    assert_eq!(
+        validation.warnings,
+        vec![
+            FilterWarning {
+                path: vec![0],
+                kind: FilterWarningKind::UnknownTag { tag_id: 3 },
+            },
+            FilterWarning {
+                path: vec![0],
+                kind: FilterWarningKind::UnknownTag { tag_id: 4 },
+            },
+            FilterWarning {
+                path: vec![2],
+                kind: FilterWarningKind::UnknownTagGroup { group_id: 9 },
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_validation"))]
+async fn test_validate_unknown_playlist_and_folder(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let folder = |path: &str| FilterTarget::Folder {
+        range: FolderFilterRange::In {
+            value: path.parse().unwrap(),
+            recursive: true,
+        },
+    };
+    let playlist = |value: i32| FilterTarget::InPlaylist {
+        range: PlaylistFilterRange::NotIn { value },
+    };
+
+    let filter = or(vec![
+        folder("Artist/"),
+        folder("Nothing/"),
+        playlist(2),
+        playlist(99),
+    ]);
+
+    let validation = validate(&mut tx, filter).await?;
+
+    assert_eq!(
+        validation.warnings,
+        vec![
+            FilterWarning {
+                path: vec![3],
+                kind: FilterWarningKind::UnknownPlaylist { playlist_id: 99 },
+            },
+            FilterWarning {
+                path: vec![1],
+                kind: FilterWarningKind::UnknownFolder {
+                    path: "Nothing/".parse()?,
+                },
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_validation"))]
+async fn test_validate_invalid_regex(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let filter = and(vec![FilterTarget::Title {
+        range: StringFilterRange::Regex {
+            value: "(abc".to_owned(),
+        },
+    }]);
+
+    let result = validate(&mut tx, filter).await;
+    assert!(matches!(result, Err(FilterError::InvalidRegex { .. })));
+
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_validation"))]
+async fn test_update_filter(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let warnings = playlist_repository::update_filter(
+        &mut tx,
+        1,
+        and(vec![and(vec![tag(1), tag(5)]), or(vec![])]),
+    )
+    .await?;
+
+    assert_eq!(
+        warnings,
+        vec![FilterWarning {
+            path: vec![1],
+            kind: FilterWarningKind::UnknownTag { tag_id: 5 },
+        }]
+    );
+
+    let row = sqlx::query!("SELECT filter_json, listuped_flag FROM playlists WHERE id = 1")
+        .fetch_one(&mut *tx)
+        .await?;
+    let saved = filter_document::from_json(row.filter_json.unwrap())?;
+    assert_eq!(saved, and(vec![tag(1), tag(5)]));
+    assert!(!row.listuped_flag);
+
+    // フィルタプレイリスト以外は更新できない
+    let result = playlist_repository::update_filter(&mut tx, 2, and(vec![])).await;
+    assert!(matches!(
+        result,
+        Err(PlaylistError::FilterPlaylistNotFound { plist_id: 2 })
+    ));
+
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_validation"))]
+async fn test_update_filter_cycle(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let in_playlist = |value: i32| FilterTarget::InPlaylist {
+        range: PlaylistFilterRange::In { value },
+    };
+
+    // 自身を参照
+    let result = playlist_repository::update_filter(&mut tx, 1, in_playlist(1)).await;
+    assert!(matches!(
+        result,
+        Err(PlaylistError::PlaylistCycleDetected(ids)) if ids == vec![1, 1]
+    ));
+
+    // フォルダの子から、自身が参照されている
+    let result =
+        playlist_repository::update_filter(&mut tx, 1, and(vec![tag(1), in_playlist(3)])).await;
+    assert!(matches!(
+        result,
+        Err(PlaylistError::PlaylistCycleDetected(ids)) if ids == vec![1, 3, 4, 1]
+    ));
+
+    // 循環しなければ保存できる
+    playlist_repository::update_filter(&mut tx, 1, in_playlist(2)).await?;
+
+    Ok(())
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("filter_validation"))]
+async fn test_save_preset(pool: PgPool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let warnings =
+        search_preset_sqls::save_preset(&mut tx, "preset1", and(vec![and(vec![tag(2)])])).await?;
+    assert_eq!(warnings, vec![]);
+
+    let warnings = search_preset_sqls::save_preset(
+        &mut tx,
+        "preset2",
+        and(vec![artist("a"), not_artist("a")]),
+    )
+    .await?;
+    assert_eq!(
+        warnings,
+        vec![FilterWarning {
+            path: vec![1],
+            kind: FilterWarningKind::Contradiction { other: vec![0] },
+        }]
+    );
+
+    let rows =
+        sqlx::query!("SELECT order_index, name, filter_json FROM search_presets ORDER BY id")
+            .fetch_all(&mut *tx)
+            .await?;
+    assert_eq!(rows.len(), 2);
+
+    assert_eq!(rows[0].order_index, 0);
+    let saved = filter_document::from_json(rows[0].filter_json.clone())?;
+    assert_eq!(saved, and(vec![tag(2)]));
+
+    assert_eq!(rows[1].order_index, 1);
+    assert_eq!(rows[1].name, "preset2");
+
+    Ok(())
+}
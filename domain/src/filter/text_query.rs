@@ -0,0 +1,68 @@
+//! フィルタのテキストクエリ表記
+//!
+//! `artist:"foo" and rating>=4 and not tag:12 and release<2020-01-01` のような文字列と、
+//! `RootFilter` とを相互に変換する。
+//!
+//! # 構文
+//! - 条件は `項目 演算子 値` の形で記述する (例: `rating>=4`)
+//! - `and` / `or` で条件を連結する。`and` の方が優先される
+//! - `( )` で囲んだ部分は一つの `FilterGroup` となる
+//! - `not` を前に付けると、条件を否定した演算子に置き換える (例: `not tag:12` はタグを含まない)
+//! - 括弧の前に `not` を付けると、否定のグループとなる (`not (a and b)` は `Not`、`not (a or b)` は `Nor`)
+//! - 文字列の値は `"` で囲む (`"` と `\` は `\` でエスケープ)。空白・括弧を含まなければ省略可
+//!
+//! | 項目 | 種類 | 対象 |
+//! |------|------|------|
+//! | `tag` | タグ ID | Tags |
+//! | `tag_any` / `tag_all` | `,` 区切りのタグ ID (いずれかを含む / 全て含む) | Tags |
+//! | `tag_group` | タググループ ID (グループのタグのいずれかを含む) | Tags |
+//! | `rating` / `track` / `track_max` / `disc` / `disc_max` | 数値 | Rating など |
+//! | `duration` | 再生時間 (`分:秒` 形式、負の値は `-0:01`) | Duration |
+//! | `release` / `entry` | 日付 (`YYYY-MM-DD`、または `-7d` `-3m` のように今日からの日数・月数) | ReleaseDate / EntryDate |
+//! | `title` / `artist` / `album_artist` / `album` / `composer` / `genre` / `memo` / `memo_manage` / `original` | 文字列 | Title など |
+//! | `artwork` | `has` / `none` | Artwork |
+//! | `suggest` | `true` / `false` | SuggestTarget |
+//! | `folder` | ライブラリ内のフォルダのパス | Folder |
+//! | `playlist` | プレイリスト ID | InPlaylist |
+//!
+//! JSON の `target` の名前 (`release_date` など) も項目名として使用できる。
+//!
+//! | 種類 | 演算子 |
+//! |------|--------|
+//! | 文字列 | `:` 含む, `=` 等しい, `!=` 異なる, `^=` 始まる, `$=` 終わる, `~` / `~*` / `!~` / `!~*` 正規表現 (`*` 付きは大文字小文字を区別しない)。`%:` のように `%` を前に付けると表記ゆれを無視する |
+//! | 数値・再生時間 | `:` / `=` 等しい (`3..5` で範囲内、`none` で値なし), `!=` (`3..5` で範囲外、`none` で値あり), `>=`, `<=`, `>`, `<`。`?!=` は値がない曲も含む `!=` |
+//! | 日付 | `:` / `=` 等しい (`none` で日付なし), `!=` (`none` で日付あり), `?!=` 日付がない曲も含む `!=`, `<=` 以前, `>=` 以後, `<`, `>`。相対日付は `>=` 以内, `<` より前 のみ |
+//! | タグ | `:` タグを含む (`none` でタグなし) |
+//! | フォルダ | `=` フォルダ直下の曲, `:` サブフォルダ内の曲も含む |
+//! | プレイリスト | `:` プレイリストに含まれる |
+//! | アートワーク・フラグ | `:` |
+
+mod parser;
+
+mod printer;
+
+mod text_query_error;
+pub use text_query_error::{TextQueryError, TextQueryErrorKind};
+
+#[cfg(test)]
+mod tests;
+
+use crate::filter::{FilterTarget, RootFilter};
+
+/// テキストクエリを解析し、フィルタに変換
+///
+/// 最上位は常に `FilterTarget::FilterGroup` となる。
+/// 空文字列の場合は、条件の無い `FilterGroup` を返す。
+pub fn parse(text: &str) -> Result<RootFilter, TextQueryError> {
+    parser::parse(text)
+}
+
+/// フィルタをテキストクエリに変換
+///
+/// 出力したテキストを `parse` すると、元と同じ条件のフィルタになる。
+/// (子要素が1つ以下のグループは `GroupOperand::And` として、
+/// 最上位がグループでない場合や否定のグループの場合はその条件のみを含むグループとして、
+/// `GroupOperand::Nand` は `GroupOperand::Not` として読み込まれる)
+pub fn to_text(filter: &FilterTarget) -> String {
+    printer::to_text(filter)
+}
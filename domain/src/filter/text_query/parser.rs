@@ -0,0 +1,685 @@
+use chrono::{Days, NaiveDate};
+
+use crate::filter::{
+    ArtworkFilterRange, BoolFilterRange, DateFilterRange, FilterTarget, FolderFilterRange,
+    GroupOperand, IntFilterRange, PlaylistFilterRange, RootFilter, StringFilterRange,
+    TagsFilterRange,
+    text_query::{TextQueryError, TextQueryErrorKind},
+};
+
+/// テキストクエリを解析し、フィルタに変換
+pub fn parse(text: &str) -> Result<RootFilter, TextQueryError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+
+    parser.skip_whitespace();
+    //空のクエリは、条件の無いグループとする
+    if parser.is_end() {
+        return Ok(FilterTarget::FilterGroup {
+            op: GroupOperand::And,
+            children: vec![],
+        });
+    }
+
+    let expr = parser.parse_or()?;
+
+    parser.skip_whitespace();
+    if let Some(c) = parser.peek() {
+        return Err(parser.error(TextQueryErrorKind::UnexpectedChar(c)));
+    }
+
+    Ok(expr.into_group())
+}
+
+/// 解析途中の式
+enum Expr {
+    /// 単一の条件
+    Clause(FilterTarget),
+
+    /// 括弧無しで `and` / `or` で連結された条件
+    Chain(GroupOperand, Vec<FilterTarget>),
+
+    /// 括弧で囲まれたグループ
+    Group(FilterTarget),
+}
+
+impl Expr {
+    /// 他の条件の子要素とする形に変換
+    fn into_child(self) -> FilterTarget {
+        match self {
+            Expr::Clause(target) | Expr::Group(target) => target,
+            Expr::Chain(op, children) => FilterTarget::FilterGroup { op, children },
+        }
+    }
+
+    /// 括弧で囲まれた範囲 (もしくはクエリ全体) として、FilterGroup に変換
+    fn into_group(self) -> FilterTarget {
+        match self {
+            Expr::Chain(op, children) => FilterTarget::FilterGroup { op, children },
+            Expr::Clause(target) | Expr::Group(target) => FilterTarget::FilterGroup {
+                op: GroupOperand::And,
+                children: vec![target],
+            },
+        }
+    }
+}
+
+/// 括弧で囲まれたグループを否定したグループ
+///
+/// `not (a and b)` は `Not`、`not (a or b)` は `Nor` とする。
+/// 否定のグループを否定する場合は、そのグループを子要素とする `Not` とする。
+fn negate_group(group: FilterTarget) -> FilterTarget {
+    match group {
+        FilterTarget::FilterGroup {
+            op: GroupOperand::And,
+            children,
+        } => FilterTarget::FilterGroup {
+            op: GroupOperand::Not,
+            children,
+        },
+        FilterTarget::FilterGroup {
+            op: GroupOperand::Or,
+            children,
+        } => FilterTarget::FilterGroup {
+            op: GroupOperand::Nor,
+            children,
+        },
+        group => FilterTarget::FilterGroup {
+            op: GroupOperand::Not,
+            children: vec![group],
+        },
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+
+    /// 次に読む文字の位置
+    pos: usize,
+}
+
+impl Parser {
+    /// `or` で連結された式を解析
+    fn parse_or(&mut self) -> Result<Expr, TextQueryError> {
+        let first = self.parse_and()?;
+        self.parse_chain(first, "or", GroupOperand::Or, Self::parse_and)
+    }
+
+    /// `and` で連結された式を解析
+    fn parse_and(&mut self) -> Result<Expr, TextQueryError> {
+        let first = self.parse_unary()?;
+        self.parse_chain(first, "and", GroupOperand::And, Self::parse_unary)
+    }
+
+    /// keyword で連結された式を解析
+    ///
+    /// 連結されていなければ first をそのまま返す
+    fn parse_chain(
+        &mut self,
+        first: Expr,
+        keyword: &str,
+        op: GroupOperand,
+        parse_item: fn(&mut Self) -> Result<Expr, TextQueryError>,
+    ) -> Result<Expr, TextQueryError> {
+        let mut children = vec![];
+        let mut first = Some(first);
+
+        while self.consume_keyword(keyword) {
+            if let Some(f) = first.take() {
+                children.push(f.into_child());
+            }
+            children.push(parse_item(self)?.into_child());
+        }
+
+        Ok(match first {
+            Some(f) => f,
+            None => Expr::Chain(op, children),
+        })
+    }
+
+    /// `not` が付いている可能性のある式を解析
+    fn parse_unary(&mut self) -> Result<Expr, TextQueryError> {
+        self.skip_whitespace();
+        let start = self.pos;
+
+        if self.consume_keyword("not") {
+            return match self.parse_unary()? {
+                Expr::Clause(target) => target
+                    .negate()
+                    .map(Expr::Clause)
+                    .ok_or_else(|| self.error_at(start, TextQueryErrorKind::CannotNegate)),
+                //括弧で囲まれたグループは、否定のグループとする
+                Expr::Group(group) => Ok(Expr::Group(negate_group(group))),
+                Expr::Chain(..) => Err(self.error_at(start, TextQueryErrorKind::CannotNegate)),
+            };
+        }
+
+        self.parse_primary()
+    }
+
+    /// 括弧で囲まれた式か、単一の条件を解析
+    fn parse_primary(&mut self) -> Result<Expr, TextQueryError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                self.skip_whitespace();
+
+                //空の括弧は、条件の無いグループとする
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                    return Ok(Expr::Group(FilterTarget::FilterGroup {
+                        op: GroupOperand::And,
+                        children: vec![],
+                    }));
+                }
+
+                let inner = self.parse_or()?;
+
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(')') => {
+                        self.pos += 1;
+                        Ok(Expr::Group(inner.into_group()))
+                    }
+                    Some(c) => Err(self.error(TextQueryErrorKind::UnexpectedChar(c))),
+                    None => Err(self.error(TextQueryErrorKind::UnexpectedEnd)),
+                }
+            }
+            Some(_) => self.parse_clause().map(Expr::Clause),
+            None => Err(self.error(TextQueryErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    /// `項目 演算子 値` 形式の条件を解析
+    fn parse_clause(&mut self) -> Result<FilterTarget, TextQueryError> {
+        let field_pos = self.pos;
+        let field = self.read_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        if field.is_empty() {
+            return Err(match self.peek() {
+                Some(c) => self.error(TextQueryErrorKind::UnexpectedChar(c)),
+                None => self.error(TextQueryErrorKind::UnexpectedEnd),
+            });
+        }
+
+        self.skip_whitespace();
+        let op_pos = self.pos;
+        let op = self.read_while(|c| OPERATOR_CHARS.contains(c));
+        if op.is_empty() {
+            return Err(match self.peek() {
+                Some(c) => self.error(TextQueryErrorKind::UnexpectedChar(c)),
+                None => self.error(TextQueryErrorKind::UnexpectedEnd),
+            });
+        }
+
+        self.skip_whitespace();
+        let value_pos = self.pos;
+        let value = self.read_value()?;
+
+        let clause = Clause {
+            op: &op,
+            value: &value,
+        };
+
+        let Some(build) = field_builder(&field) else {
+            return Err(self.error_at(field_pos, TextQueryErrorKind::UnknownField(field)));
+        };
+
+        build(&clause).map_err(|e| match e {
+            ClauseError::Operator => self.error_at(
+                op_pos,
+                TextQueryErrorKind::UnknownOperator {
+                    field: field.clone(),
+                    op: op.clone(),
+                },
+            ),
+            ClauseError::Value => self.error_at(
+                value_pos,
+                TextQueryErrorKind::InvalidValue {
+                    field: field.clone(),
+                    value: value.clone(),
+                },
+            ),
+        })
+    }
+
+    /// 条件の値を読み込む
+    ///
+    /// `"` で囲まれていればエスケープを解除し、そうでなければ空白・括弧の手前までを読み込む
+    fn read_value(&mut self) -> Result<String, TextQueryError> {
+        if self.peek() != Some('"') {
+            let value = self.read_while(|c| !c.is_whitespace() && c != '(' && c != ')');
+            if value.is_empty() {
+                return Err(match self.peek() {
+                    Some(c) => self.error(TextQueryErrorKind::UnexpectedChar(c)),
+                    None => self.error(TextQueryErrorKind::UnexpectedEnd),
+                });
+            }
+            return Ok(value);
+        }
+
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(c) => value.push(c),
+                        None => return Err(self.error(TextQueryErrorKind::UnexpectedEnd)),
+                    }
+                }
+                Some(c) => value.push(c),
+                None => return Err(self.error(TextQueryErrorKind::UnexpectedEnd)),
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// キーワードがあれば読み進める
+    ///
+    /// キーワードの直後が英数字の場合 (`order` など) は、キーワードとみなさない
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+
+        let len = keyword.chars().count();
+        let matches = self.chars[self.pos..]
+            .iter()
+            .take(len)
+            .copied()
+            .eq(keyword.chars());
+        let boundary = self
+            .chars
+            .get(self.pos + len)
+            .is_none_or(|c| !c.is_ascii_alphanumeric() && *c != '_');
+
+        if matches && boundary {
+            self.pos += len;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn read_while(&mut self, pred: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn is_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn error(&self, kind: TextQueryErrorKind) -> TextQueryError {
+        self.error_at(self.pos, kind)
+    }
+
+    fn error_at(&self, position: usize, kind: TextQueryErrorKind) -> TextQueryError {
+        TextQueryError { position, kind }
+    }
+}
+
+/// 演算子に使用する文字
+const OPERATOR_CHARS: &str = "%?:=!<>^$~*";
+
+/// 解析した条件の演算子と値の文字列
+struct Clause<'a> {
+    op: &'a str,
+    value: &'a str,
+}
+
+/// 条件の組み立て時のエラー
+enum ClauseError {
+    /// 項目に使用できない演算子
+    Operator,
+    /// 不正な値
+    Value,
+}
+
+type ClauseBuilder = fn(&Clause) -> Result<FilterTarget, ClauseError>;
+
+/// 項目名から、条件を組み立てる関数を取得
+fn field_builder(field: &str) -> Option<ClauseBuilder> {
+    let builder: ClauseBuilder = match field {
+        "tag" | "tags" => |c| Ok(FilterTarget::Tags { range: tags(c)? }),
+        "tag_any" => |c| {
+            Ok(FilterTarget::Tags {
+                range: TagsFilterRange::ContainAny {
+                    values: tag_list(c)?,
+                },
+            })
+        },
+        "tag_all" => |c| {
+            Ok(FilterTarget::Tags {
+                range: TagsFilterRange::ContainAll {
+                    values: tag_list(c)?,
+                },
+            })
+        },
+        "tag_group" => |c| {
+            Ok(FilterTarget::Tags {
+                range: tag_group(c)?,
+            })
+        },
+        "rating" => |c| Ok(FilterTarget::Rating { range: int(c)? }),
+        "genre" => |c| Ok(FilterTarget::Genre { range: string(c)? }),
+        "artist" => |c| Ok(FilterTarget::Artist { range: string(c)? }),
+        "album_artist" => |c| Ok(FilterTarget::AlbumArtist { range: string(c)? }),
+        "album" => |c| Ok(FilterTarget::Album { range: string(c)? }),
+        "composer" => |c| Ok(FilterTarget::Composer { range: string(c)? }),
+        "title" => |c| Ok(FilterTarget::Title { range: string(c)? }),
+        "artwork" => |c| Ok(FilterTarget::Artwork { range: artwork(c)? }),
+        "duration" => |c| {
+            Ok(FilterTarget::Duration {
+                range: duration(c)?,
+            })
+        },
+        "release" | "release_date" => |c| Ok(FilterTarget::ReleaseDate { range: date(c)? }),
+        "track" | "track_number" => |c| Ok(FilterTarget::TrackNumber { range: int(c)? }),
+        "track_max" => |c| Ok(FilterTarget::TrackMax { range: int(c)? }),
+        "disc" | "disc_number" => |c| Ok(FilterTarget::DiscNumber { range: int(c)? }),
+        "disc_max" => |c| Ok(FilterTarget::DiscMax { range: int(c)? }),
+        "memo" => |c| Ok(FilterTarget::Memo { range: string(c)? }),
+        "memo_manage" => |c| Ok(FilterTarget::MemoManage { range: string(c)? }),
+        "entry" | "entry_date" => |c| Ok(FilterTarget::EntryDate { range: date(c)? }),
+        "original" | "original_track" => |c| Ok(FilterTarget::OriginalTrack { range: string(c)? }),
+        "suggest" | "suggest_target" => |c| Ok(FilterTarget::SuggestTarget { range: boolean(c)? }),
+        "folder" => |c| Ok(FilterTarget::Folder { range: folder(c)? }),
+        "playlist" | "in_playlist" => |c| {
+            Ok(FilterTarget::InPlaylist {
+                range: playlist(c)?,
+            })
+        },
+        _ => return None,
+    };
+    Some(builder)
+}
+
+fn string(c: &Clause) -> Result<StringFilterRange, ClauseError> {
+    let value = c.value.to_owned();
+
+    //先頭の % は表記ゆれを無視する指定
+    let (op, normalize) = match c.op.strip_prefix('%') {
+        Some(op) => (op, true),
+        None => (c.op, false),
+    };
+
+    Ok(match (op, normalize) {
+        (":", _) => StringFilterRange::Contain { value, normalize },
+        ("=", _) => StringFilterRange::Equal { value, normalize },
+        ("!=", _) => StringFilterRange::NotEqual { value, normalize },
+        ("^=", _) => StringFilterRange::Start { value, normalize },
+        ("$=", _) => StringFilterRange::End { value, normalize },
+        ("~", false) => StringFilterRange::Regex { value },
+        ("~*", false) => StringFilterRange::RegexIgnoreCase { value },
+        ("!~", false) => StringFilterRange::NotRegex { value },
+        ("!~*", false) => StringFilterRange::NotRegexIgnoreCase { value },
+        _ => return Err(ClauseError::Operator),
+    })
+}
+
+fn int(c: &Clause) -> Result<IntFilterRange, ClauseError> {
+    int_by(c, |s| s.parse().ok())
+}
+
+fn duration(c: &Clause) -> Result<IntFilterRange, ClauseError> {
+    int_by(c, parse_duration)
+}
+
+/// 数値の条件を組み立てる
+///
+/// `>` `<` は、`>=` `<=` に変換する。
+/// `?!=` は、値がない曲も含む `!=` とする。
+fn int_by(c: &Clause, parse: fn(&str) -> Option<i32>) -> Result<IntFilterRange, ClauseError> {
+    let value = || parse(c.value).ok_or(ClauseError::Value);
+    let range = || match c.value.split_once("..") {
+        Some((min, max)) => Ok(Some((
+            parse(min).ok_or(ClauseError::Value)?,
+            parse(max).ok_or(ClauseError::Value)?,
+        ))),
+        None => Ok(None),
+    };
+
+    if c.value == "none" {
+        return match c.op {
+            ":" | "=" => Ok(IntFilterRange::IsNull),
+            "!=" => Ok(IntFilterRange::NotNull),
+            _ => Err(ClauseError::Operator),
+        };
+    }
+
+    Ok(match c.op {
+        ":" | "=" => match range()? {
+            Some((min, max)) => IntFilterRange::RangeIn { min, max },
+            None => IntFilterRange::Equal { value: value()? },
+        },
+        "!=" | "?!=" => {
+            let include_null = c.op == "?!=";
+            match range()? {
+                Some((min, max)) => IntFilterRange::RangeOut {
+                    min,
+                    max,
+                    include_null,
+                },
+                None => IntFilterRange::NotEqual {
+                    value: value()?,
+                    include_null,
+                },
+            }
+        }
+        ">=" => IntFilterRange::LargeEqual { value: value()? },
+        "<=" => IntFilterRange::SmallEqual { value: value()? },
+        ">" => IntFilterRange::LargeEqual {
+            value: value()?.checked_add(1).ok_or(ClauseError::Value)?,
+        },
+        "<" => IntFilterRange::SmallEqual {
+            value: value()?.checked_sub(1).ok_or(ClauseError::Value)?,
+        },
+        _ => return Err(ClauseError::Operator),
+    })
+}
+
+/// `分:秒` 形式 (秒は小数可) の再生時間を、ミリ秒に変換
+///
+/// 負の値は、`-0:01.500` のように先頭に `-` を1つだけ付ける
+fn parse_duration(s: &str) -> Option<i32> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+
+    let (min, sec) = s.split_once(':')?;
+    //符号は先頭にのみ付けられる
+    if min.is_empty() || !min.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let min: i64 = min.parse().ok()?;
+
+    let (sec, millis) = match sec.split_once('.') {
+        Some((sec, frac)) => {
+            if frac.is_empty() || frac.len() > 3 || !frac.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let millis: i64 = format!("{frac:0<3}").parse().ok()?;
+            (sec, millis)
+        }
+        None => (sec, 0),
+    };
+    if sec.len() != 2 || !sec.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let sec: i64 = sec.parse().ok()?;
+    if sec >= 60 {
+        return None;
+    }
+
+    let millis = min
+        .checked_mul(60)?
+        .checked_add(sec)?
+        .checked_mul(1000)?
+        .checked_add(millis)?;
+    i32::try_from(if negative { -millis } else { millis }).ok()
+}
+
+/// 日付の条件を組み立てる
+///
+/// `>` `<` は、`>=` `<=` に変換する。`?!=` は、日付がない曲も含む `!=` とする。
+/// `-7d` `-3m` などの相対日付は、`>=` (以内) と `<` (より前) のみ使用できる。
+fn date(c: &Clause) -> Result<DateFilterRange, ClauseError> {
+    if c.value == "none" {
+        return match c.op {
+            ":" | "=" => Ok(DateFilterRange::None),
+            "!=" => Ok(DateFilterRange::NotNull),
+            _ => Err(ClauseError::Operator),
+        };
+    }
+
+    if let Some(relative) = c.value.strip_prefix('-') {
+        return relative_date(c.op, relative);
+    }
+
+    let value = NaiveDate::parse_from_str(c.value, "%Y-%m-%d").map_err(|_| ClauseError::Value)?;
+    let one_day = Days::new(1);
+
+    Ok(match c.op {
+        ":" | "=" => DateFilterRange::Equal { value },
+        "!=" | "?!=" => DateFilterRange::NotEqual {
+            value,
+            include_null: c.op == "?!=",
+        },
+        "<=" => DateFilterRange::Before { value },
+        ">=" => DateFilterRange::After { value },
+        "<" => DateFilterRange::Before {
+            value: value.checked_sub_days(one_day).ok_or(ClauseError::Value)?,
+        },
+        ">" => DateFilterRange::After {
+            value: value.checked_add_days(one_day).ok_or(ClauseError::Value)?,
+        },
+        _ => return Err(ClauseError::Operator),
+    })
+}
+
+/// 相対日付の条件を組み立てる
+///
+/// value: 先頭の `-` を除いた値 (`7d` `3m` など)
+fn relative_date(op: &str, value: &str) -> Result<DateFilterRange, ClauseError> {
+    //末尾の文字を単位とする (マルチバイト文字でも文字単位で分割する)
+    let (unit_index, unit) = value.char_indices().next_back().ok_or(ClauseError::Value)?;
+    let value: u32 = value[..unit_index]
+        .parse()
+        .map_err(|_| ClauseError::Value)?;
+
+    Ok(match (op, unit) {
+        (">=", 'd') => DateFilterRange::WithinDays { value },
+        (">=", 'm') => DateFilterRange::WithinMonths { value },
+        ("<", 'd') => DateFilterRange::OlderThanDays { value },
+        ("<", 'm') => DateFilterRange::OlderThanMonths { value },
+        (_, 'd' | 'm') => return Err(ClauseError::Operator),
+        _ => return Err(ClauseError::Value),
+    })
+}
+
+fn tags(c: &Clause) -> Result<TagsFilterRange, ClauseError> {
+    if !matches!(c.op, ":" | "=") {
+        return Err(ClauseError::Operator);
+    }
+
+    Ok(match c.value {
+        "none" => TagsFilterRange::None,
+        v => TagsFilterRange::Contain {
+            value: v.parse().map_err(|_| ClauseError::Value)?,
+        },
+    })
+}
+
+/// `,` 区切りのタグ ID の一覧を解析する
+fn tag_list(c: &Clause) -> Result<Vec<i32>, ClauseError> {
+    if !matches!(c.op, ":" | "=") {
+        return Err(ClauseError::Operator);
+    }
+
+    c.value
+        .split(',')
+        .map(|v| v.parse().map_err(|_| ClauseError::Value))
+        .collect()
+}
+
+fn tag_group(c: &Clause) -> Result<TagsFilterRange, ClauseError> {
+    if !matches!(c.op, ":" | "=") {
+        return Err(ClauseError::Operator);
+    }
+
+    Ok(TagsFilterRange::GroupAny {
+        group_id: c.value.parse().map_err(|_| ClauseError::Value)?,
+    })
+}
+
+/// フォルダの条件を組み立てる
+///
+/// `=` はフォルダ直下の曲のみ、`:` はサブフォルダ内の曲も対象とする
+fn folder(c: &Clause) -> Result<FolderFilterRange, ClauseError> {
+    let recursive = match c.op {
+        "=" => false,
+        ":" => true,
+        _ => return Err(ClauseError::Operator),
+    };
+
+    Ok(FolderFilterRange::In {
+        value: c.value.parse().map_err(|_| ClauseError::Value)?,
+        recursive,
+    })
+}
+
+fn playlist(c: &Clause) -> Result<PlaylistFilterRange, ClauseError> {
+    if !matches!(c.op, ":" | "=") {
+        return Err(ClauseError::Operator);
+    }
+
+    Ok(PlaylistFilterRange::In {
+        value: c.value.parse().map_err(|_| ClauseError::Value)?,
+    })
+}
+
+fn artwork(c: &Clause) -> Result<ArtworkFilterRange, ClauseError> {
+    if !matches!(c.op, ":" | "=") {
+        return Err(ClauseError::Operator);
+    }
+
+    match c.value {
+        "has" => Ok(ArtworkFilterRange::Has),
+        "none" => Ok(ArtworkFilterRange::None),
+        _ => Err(ClauseError::Value),
+    }
+}
+
+fn boolean(c: &Clause) -> Result<BoolFilterRange, ClauseError> {
+    if !matches!(c.op, ":" | "=") {
+        return Err(ClauseError::Operator);
+    }
+
+    match c.value {
+        "true" => Ok(BoolFilterRange::True),
+        "false" => Ok(BoolFilterRange::False),
+        _ => Err(ClauseError::Value),
+    }
+}
@@ -0,0 +1,226 @@
+use chrono::NaiveDate;
+
+use crate::filter::{
+    ArtworkFilterRange, BoolFilterRange, DateFilterRange, FilterTarget, FolderFilterRange,
+    GroupOperand, IntFilterRange, PlaylistFilterRange, StringFilterRange, TagsFilterRange,
+};
+
+/// フィルタをテキストクエリに変換
+pub fn to_text(filter: &FilterTarget) -> String {
+    match filter {
+        //最上位のグループは括弧で囲まない
+        FilterTarget::FilterGroup { op, children } if !op.is_negated() => {
+            children_text(op, children)
+        }
+        _ => clause_text(filter),
+    }
+}
+
+/// グループの子要素を、演算子で連結した文字列
+fn children_text(op: &GroupOperand, children: &[FilterTarget]) -> String {
+    let separator = match op {
+        GroupOperand::And | GroupOperand::Not | GroupOperand::Nand => " and ",
+        GroupOperand::Or | GroupOperand::Nor => " or ",
+    };
+
+    children
+        .iter()
+        .map(clause_text)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// 条件の文字列
+///
+/// グループは括弧で囲む
+fn clause_text(filter: &FilterTarget) -> String {
+    match filter {
+        FilterTarget::FilterGroup { op, children } if op.is_negated() => {
+            format!("not ({})", children_text(op, children))
+        }
+        FilterTarget::FilterGroup { op, children } => {
+            format!("({})", children_text(op, children))
+        }
+        FilterTarget::Tags { range } => match range {
+            TagsFilterRange::Contain { value } => format!("tag:{value}"),
+            TagsFilterRange::NotContain { value } => format!("not tag:{value}"),
+            TagsFilterRange::None => "tag:none".to_owned(),
+            TagsFilterRange::ContainAny { values } => format!("tag_any:{}", tag_list_text(values)),
+            TagsFilterRange::ContainAll { values } => format!("tag_all:{}", tag_list_text(values)),
+            TagsFilterRange::ContainNone { values } => {
+                format!("not tag_any:{}", tag_list_text(values))
+            }
+            TagsFilterRange::GroupAny { group_id } => format!("tag_group:{group_id}"),
+            TagsFilterRange::GroupNone { group_id } => format!("not tag_group:{group_id}"),
+        },
+        FilterTarget::Rating { range } => int_text("rating", range, |v| v.to_string()),
+        FilterTarget::Genre { range } => string_text("genre", range),
+        FilterTarget::Artist { range } => string_text("artist", range),
+        FilterTarget::AlbumArtist { range } => string_text("album_artist", range),
+        FilterTarget::Album { range } => string_text("album", range),
+        FilterTarget::Composer { range } => string_text("composer", range),
+        FilterTarget::Title { range } => string_text("title", range),
+        FilterTarget::Artwork { range } => match range {
+            ArtworkFilterRange::Has => "artwork:has".to_owned(),
+            ArtworkFilterRange::None => "artwork:none".to_owned(),
+        },
+        FilterTarget::Duration { range } => int_text("duration", range, duration_text),
+        FilterTarget::ReleaseDate { range } => date_text("release", range),
+        FilterTarget::TrackNumber { range } => int_text("track", range, |v| v.to_string()),
+        FilterTarget::TrackMax { range } => int_text("track_max", range, |v| v.to_string()),
+        FilterTarget::DiscNumber { range } => int_text("disc", range, |v| v.to_string()),
+        FilterTarget::DiscMax { range } => int_text("disc_max", range, |v| v.to_string()),
+        FilterTarget::Memo { range } => string_text("memo", range),
+        FilterTarget::MemoManage { range } => string_text("memo_manage", range),
+        FilterTarget::EntryDate { range } => date_text("entry", range),
+        FilterTarget::OriginalTrack { range } => string_text("original", range),
+        FilterTarget::SuggestTarget { range } => match range {
+            BoolFilterRange::True => "suggest:true".to_owned(),
+            BoolFilterRange::False => "suggest:false".to_owned(),
+        },
+        FilterTarget::Folder { range } => match range {
+            FolderFilterRange::In { value, recursive } => folder_text(value.as_ref(), *recursive),
+            FolderFilterRange::NotIn { value, recursive } => {
+                format!("not {}", folder_text(value.as_ref(), *recursive))
+            }
+        },
+        FilterTarget::InPlaylist { range } => match range {
+            PlaylistFilterRange::In { value } => format!("playlist:{value}"),
+            PlaylistFilterRange::NotIn { value } => format!("not playlist:{value}"),
+        },
+    }
+}
+
+fn string_text(field: &str, range: &StringFilterRange) -> String {
+    let normalize_prefix = |normalize: &bool| if *normalize { "%" } else { "" };
+
+    match range {
+        StringFilterRange::Equal { value, normalize } => {
+            format!("{field}{}={}", normalize_prefix(normalize), quote(value))
+        }
+        StringFilterRange::NotEqual { value, normalize } => {
+            format!("{field}{}!={}", normalize_prefix(normalize), quote(value))
+        }
+        StringFilterRange::Contain { value, normalize } => {
+            format!("{field}{}:{}", normalize_prefix(normalize), quote(value))
+        }
+        StringFilterRange::NotContain { value, normalize } => {
+            format!(
+                "not {field}{}:{}",
+                normalize_prefix(normalize),
+                quote(value)
+            )
+        }
+        StringFilterRange::Start { value, normalize } => {
+            format!("{field}{}^={}", normalize_prefix(normalize), quote(value))
+        }
+        StringFilterRange::End { value, normalize } => {
+            format!("{field}{}$={}", normalize_prefix(normalize), quote(value))
+        }
+        StringFilterRange::Regex { value } => format!("{field}~{}", quote(value)),
+        StringFilterRange::RegexIgnoreCase { value } => format!("{field}~*{}", quote(value)),
+        StringFilterRange::NotRegex { value } => format!("{field}!~{}", quote(value)),
+        StringFilterRange::NotRegexIgnoreCase { value } => format!("{field}!~*{}", quote(value)),
+    }
+}
+
+fn int_text(field: &str, range: &IntFilterRange, value_text: fn(i32) -> String) -> String {
+    match range {
+        IntFilterRange::Equal { value } => format!("{field}={}", value_text(*value)),
+        IntFilterRange::NotEqual {
+            value,
+            include_null,
+        } => format!(
+            "{field}{}!={}",
+            include_null_prefix(*include_null),
+            value_text(*value)
+        ),
+        IntFilterRange::LargeEqual { value } => format!("{field}>={}", value_text(*value)),
+        IntFilterRange::SmallEqual { value } => format!("{field}<={}", value_text(*value)),
+        IntFilterRange::RangeIn { min, max } => {
+            format!("{field}={}..{}", value_text(*min), value_text(*max))
+        }
+        IntFilterRange::RangeOut {
+            min,
+            max,
+            include_null: false,
+        } => format!("not {field}={}..{}", value_text(*min), value_text(*max)),
+        IntFilterRange::RangeOut {
+            min,
+            max,
+            include_null: true,
+        } => format!("{field}?!={}..{}", value_text(*min), value_text(*max)),
+        IntFilterRange::IsNull => format!("{field}=none"),
+        IntFilterRange::NotNull => format!("{field}!=none"),
+    }
+}
+
+fn date_text(field: &str, range: &DateFilterRange) -> String {
+    let value_text = |value: &NaiveDate| value.format("%Y-%m-%d").to_string();
+
+    match range {
+        DateFilterRange::Equal { value } => format!("{field}={}", value_text(value)),
+        DateFilterRange::NotEqual {
+            value,
+            include_null,
+        } => format!(
+            "{field}{}!={}",
+            include_null_prefix(*include_null),
+            value_text(value)
+        ),
+        DateFilterRange::Before { value } => format!("{field}<={}", value_text(value)),
+        DateFilterRange::After { value } => format!("{field}>={}", value_text(value)),
+        DateFilterRange::None => format!("{field}=none"),
+        DateFilterRange::NotNull => format!("{field}!=none"),
+        DateFilterRange::WithinDays { value } => format!("{field}>=-{value}d"),
+        DateFilterRange::WithinMonths { value } => format!("{field}>=-{value}m"),
+        DateFilterRange::OlderThanDays { value } => format!("{field}<-{value}d"),
+        DateFilterRange::OlderThanMonths { value } => format!("{field}<-{value}m"),
+    }
+}
+
+fn tag_list_text(values: &[i32]) -> String {
+    let v: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    v.join(",")
+}
+
+fn include_null_prefix(include_null: bool) -> &'static str {
+    if include_null { "?" } else { "" }
+}
+
+fn folder_text(path: &str, recursive: bool) -> String {
+    let op = if recursive { ":" } else { "=" };
+    format!("folder{op}{}", quote(path))
+}
+
+/// ミリ秒の再生時間を `分:秒` 形式に変換
+///
+/// 1秒未満の端数があれば、小数点以下3桁まで出力する。
+/// 負の値は、先頭に `-` を1つだけ付ける
+fn duration_text(millis: i32) -> String {
+    let sign = if millis < 0 { "-" } else { "" };
+    let millis = i64::from(millis).abs();
+    let min = millis / 60_000;
+    let sec = millis % 60_000 / 1000;
+    let frac = millis % 1000;
+
+    if frac == 0 {
+        format!("{sign}{min}:{sec:02}")
+    } else {
+        format!("{sign}{min}:{sec:02}.{frac:03}")
+    }
+}
+
+/// 文字列を `"` で囲み、`"` と `\` をエスケープ
+fn quote(value: &str) -> String {
+    let mut text = String::with_capacity(value.len() + 2);
+    text.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            text.push('\\');
+        }
+        text.push(c);
+    }
+    text.push('"');
+    text
+}
@@ -0,0 +1,501 @@
+use chrono::NaiveDate;
+use test_case::test_case;
+
+use super::*;
+use crate::filter::{
+    ArtworkFilterRange, BoolFilterRange, DateFilterRange, FolderFilterRange, GroupOperand,
+    IntFilterRange, PlaylistFilterRange, StringFilterRange, TagsFilterRange,
+};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn and(children: Vec<FilterTarget>) -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::And,
+        children,
+    }
+}
+
+fn or(children: Vec<FilterTarget>) -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::Or,
+        children,
+    }
+}
+
+fn not(children: Vec<FilterTarget>) -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::Not,
+        children,
+    }
+}
+
+fn nor(children: Vec<FilterTarget>) -> FilterTarget {
+    FilterTarget::FilterGroup {
+        op: GroupOperand::Nor,
+        children,
+    }
+}
+
+fn artist_contain(value: &str) -> FilterTarget {
+    FilterTarget::Artist {
+        range: StringFilterRange::Contain {
+            value: value.to_owned(),
+            normalize: false,
+        },
+    }
+}
+
+fn rating_large_equal(value: i32) -> FilterTarget {
+    FilterTarget::Rating {
+        range: IntFilterRange::LargeEqual { value },
+    }
+}
+
+#[test]
+fn test_parse_example() {
+    let filter = parse(r#"artist:"foo" and rating>=4 and not tag:12 and release<2020-01-01"#);
+
+    assert_eq!(
+        filter,
+        Ok(and(vec![
+            artist_contain("foo"),
+            rating_large_equal(4),
+            FilterTarget::Tags {
+                range: TagsFilterRange::NotContain { value: 12 }
+            },
+            FilterTarget::ReleaseDate {
+                range: DateFilterRange::Before {
+                    value: date(2019, 12, 31)
+                }
+            },
+        ]))
+    );
+}
+
+#[test]
+fn test_parse_precedence() {
+    // and の方が or より優先される
+    assert_eq!(
+        parse("artist:a or artist:b and rating>=4"),
+        Ok(or(vec![
+            artist_contain("a"),
+            and(vec![artist_contain("b"), rating_large_equal(4)]),
+        ]))
+    );
+
+    assert_eq!(
+        parse("(artist:a or artist:b) and rating>=4"),
+        Ok(and(vec![
+            or(vec![artist_contain("a"), artist_contain("b")]),
+            rating_large_equal(4),
+        ]))
+    );
+}
+
+#[test_case("", and(vec![]) ; "empty")]
+#[test_case("   ", and(vec![]) ; "whitespace")]
+#[test_case("()", and(vec![and(vec![])]) ; "empty_paren")]
+#[test_case("artist:a", and(vec![artist_contain("a")]) ; "single")]
+#[test_case("(artist:a)", and(vec![and(vec![artist_contain("a")])]) ; "single_paren")]
+#[test_case("artist:android", and(vec![artist_contain("android")]) ; "keyword_prefix_value")]
+#[test_case("not (artist:a)", and(vec![not(vec![artist_contain("a")])]) ; "not_paren")]
+#[test_case("not (artist:a and rating>=4)", and(vec![not(vec![artist_contain("a"), rating_large_equal(4)])]) ; "not_and")]
+#[test_case("not (artist:a or artist:b)", and(vec![nor(vec![artist_contain("a"), artist_contain("b")])]) ; "not_or")]
+#[test_case("not not (artist:a)", and(vec![not(vec![not(vec![artist_contain("a")])])]) ; "double_not_paren")]
+#[test_case(r#"artist:"a \"b\" \\c""#, and(vec![artist_contain(r#"a "b" \c"#)]) ; "escape")]
+#[test_case("artist : \"a b\"", and(vec![artist_contain("a b")]) ; "spaces")]
+fn test_parse(text: &str, expected: FilterTarget) {
+    assert_eq!(parse(text), Ok(expected));
+}
+
+#[test_case("rating>3", IntFilterRange::LargeEqual { value: 4 } ; "greater")]
+#[test_case("rating<3", IntFilterRange::SmallEqual { value: 2 } ; "less")]
+#[test_case("rating:3", IntFilterRange::Equal { value: 3 } ; "colon")]
+#[test_case("rating=2..4", IntFilterRange::RangeIn { min: 2, max: 4 } ; "range_in")]
+#[test_case("not rating=2..4", IntFilterRange::RangeOut { min: 2, max: 4, include_null: false } ; "range_out")]
+#[test_case("not rating!=3", IntFilterRange::Equal { value: 3 } ; "double_negate")]
+#[test_case("rating!=2..4", IntFilterRange::RangeOut { min: 2, max: 4, include_null: false } ; "range_out_not_equal")]
+#[test_case("rating?!=3", IntFilterRange::NotEqual { value: 3, include_null: true } ; "not_equal_include_null")]
+#[test_case("rating?!=2..4", IntFilterRange::RangeOut { min: 2, max: 4, include_null: true } ; "range_out_include_null")]
+#[test_case("rating=none", IntFilterRange::IsNull ; "is_null")]
+#[test_case("rating!=none", IntFilterRange::NotNull ; "not_null")]
+#[test_case("not rating:none", IntFilterRange::NotNull ; "negate_is_null")]
+fn test_parse_int(text: &str, expected: IntFilterRange) {
+    assert_eq!(
+        parse(text),
+        Ok(and(vec![FilterTarget::Rating { range: expected }]))
+    );
+}
+
+#[test_case("duration>=3:00", IntFilterRange::LargeEqual { value: 180_000 } ; "minutes")]
+#[test_case("duration<=1:02.5", IntFilterRange::SmallEqual { value: 62_500 } ; "fraction")]
+#[test_case("duration=0:30..1:00.250", IntFilterRange::RangeIn { min: 30_000, max: 60_250 } ; "range")]
+#[test_case("duration>=-0:01.500", IntFilterRange::LargeEqual { value: -1_500 } ; "negative")]
+#[test_case("duration=-1:01..-0:00.001", IntFilterRange::RangeIn { min: -61_000, max: -1 } ; "negative_range")]
+fn test_parse_duration(text: &str, expected: IntFilterRange) {
+    assert_eq!(
+        parse(text),
+        Ok(and(vec![FilterTarget::Duration { range: expected }]))
+    );
+}
+
+#[test_case("entry>=-7d", DateFilterRange::WithinDays { value: 7 } ; "within_days")]
+#[test_case("entry>=-3m", DateFilterRange::WithinMonths { value: 3 } ; "within_months")]
+#[test_case("entry<-30d", DateFilterRange::OlderThanDays { value: 30 } ; "older_than_days")]
+#[test_case("entry<-1m", DateFilterRange::OlderThanMonths { value: 1 } ; "older_than_months")]
+#[test_case("entry>2024-02-29", DateFilterRange::After { value: date(2024, 3, 1) } ; "greater")]
+#[test_case("entry!=none", DateFilterRange::NotNull ; "not_null")]
+#[test_case("entry?!=2024-03-01", DateFilterRange::NotEqual { value: date(2024, 3, 1), include_null: true } ; "not_equal_include_null")]
+fn test_parse_date(text: &str, expected: DateFilterRange) {
+    assert_eq!(
+        parse(text),
+        Ok(and(vec![FilterTarget::EntryDate { range: expected }]))
+    );
+}
+
+#[test_case("title%:abc", StringFilterRange::Contain { value: "abc".to_owned(), normalize: true } ; "normalize_contain")]
+#[test_case("title^=abc", StringFilterRange::Start { value: "abc".to_owned(), normalize: false } ; "start")]
+#[test_case("title$=abc", StringFilterRange::End { value: "abc".to_owned(), normalize: false } ; "end")]
+#[test_case("title~*abc", StringFilterRange::RegexIgnoreCase { value: "abc".to_owned() } ; "regex_ignore_case")]
+#[test_case("not title~abc", StringFilterRange::NotRegex { value: "abc".to_owned() } ; "not_regex")]
+fn test_parse_string(text: &str, expected: StringFilterRange) {
+    assert_eq!(
+        parse(text),
+        Ok(and(vec![FilterTarget::Title { range: expected }]))
+    );
+}
+
+#[test_case("folder^=a/", 6, TextQueryErrorKind::UnknownOperator { field: "folder".to_owned(), op: "^=".to_owned() } ; "folder_operator")]
+#[test_case("playlist:abc", 9, TextQueryErrorKind::InvalidValue { field: "playlist".to_owned(), value: "abc".to_owned() } ; "invalid_playlist")]
+#[test_case("artist", 6, TextQueryErrorKind::UnexpectedEnd ; "missing_operator")]
+#[test_case("artist:", 7, TextQueryErrorKind::UnexpectedEnd ; "missing_value")]
+#[test_case("artist:\"abc", 11, TextQueryErrorKind::UnexpectedEnd ; "unclosed_quote")]
+#[test_case("(artist:a", 9, TextQueryErrorKind::UnexpectedEnd ; "unclosed_paren")]
+#[test_case("artist:a)", 8, TextQueryErrorKind::UnexpectedChar(')') ; "extra_paren")]
+#[test_case("artist:a rating:3", 9, TextQueryErrorKind::UnexpectedChar('r') ; "missing_and")]
+#[test_case("artist:a and", 12, TextQueryErrorKind::UnexpectedEnd ; "trailing_and")]
+#[test_case("rating>=4 and year:2020", 14, TextQueryErrorKind::UnknownField("year".to_owned()) ; "unknown_field")]
+#[test_case("rating^=4", 6, TextQueryErrorKind::UnknownOperator { field: "rating".to_owned(), op: "^=".to_owned() } ; "unknown_operator")]
+#[test_case("title%~a", 5, TextQueryErrorKind::UnknownOperator { field: "title".to_owned(), op: "%~".to_owned() } ; "normalize_regex")]
+#[test_case("rating>=four", 8, TextQueryErrorKind::InvalidValue { field: "rating".to_owned(), value: "four".to_owned() } ; "invalid_int")]
+#[test_case("release=2020-13-01", 8, TextQueryErrorKind::InvalidValue { field: "release".to_owned(), value: "2020-13-01".to_owned() } ; "invalid_date")]
+#[test_case("entry<=-7d", 5, TextQueryErrorKind::UnknownOperator { field: "entry".to_owned(), op: "<=".to_owned() } ; "relative_date_operator")]
+#[test_case("entry>=-7w", 7, TextQueryErrorKind::InvalidValue { field: "entry".to_owned(), value: "-7w".to_owned() } ; "relative_date_unit")]
+#[test_case("release>=-7日", 9, TextQueryErrorKind::InvalidValue { field: "release".to_owned(), value: "-7日".to_owned() } ; "relative_date_multibyte_unit")]
+#[test_case("release>=-", 9, TextQueryErrorKind::InvalidValue { field: "release".to_owned(), value: "-".to_owned() } ; "relative_date_empty")]
+#[test_case("duration>=3:5", 10, TextQueryErrorKind::InvalidValue { field: "duration".to_owned(), value: "3:5".to_owned() } ; "invalid_duration")]
+#[test_case("duration>=--0:01", 10, TextQueryErrorKind::InvalidValue { field: "duration".to_owned(), value: "--0:01".to_owned() } ; "double_negative_duration")]
+#[test_case("duration>=0:-01", 10, TextQueryErrorKind::InvalidValue { field: "duration".to_owned(), value: "0:-01".to_owned() } ; "negative_seconds")]
+#[test_case("duration>=+1:00", 10, TextQueryErrorKind::InvalidValue { field: "duration".to_owned(), value: "+1:00".to_owned() } ; "signed_duration")]
+#[test_case("duration>=1:+5", 10, TextQueryErrorKind::InvalidValue { field: "duration".to_owned(), value: "1:+5".to_owned() } ; "signed_seconds")]
+#[test_case("artist:a and not rating>=4", 13, TextQueryErrorKind::CannotNegate ; "negate_large_equal")]
+#[test_case("track>=none", 5, TextQueryErrorKind::UnknownOperator { field: "track".to_owned(), op: ">=".to_owned() } ; "null_operator")]
+#[test_case("track?=3", 5, TextQueryErrorKind::UnknownOperator { field: "track".to_owned(), op: "?=".to_owned() } ; "include_null_operator")]
+#[test_case("tag_any:1,,2", 8, TextQueryErrorKind::InvalidValue { field: "tag_any".to_owned(), value: "1,,2".to_owned() } ; "invalid_tag_list")]
+#[test_case("not tag_all:1,2", 0, TextQueryErrorKind::CannotNegate ; "negate_contain_all")]
+fn test_parse_error(text: &str, position: usize, kind: TextQueryErrorKind) {
+    assert_eq!(parse(text), Err(TextQueryError { position, kind }));
+}
+
+#[test]
+fn test_error_message() {
+    let error = parse("rating>=4 and year:2020").unwrap_err();
+    assert_eq!(error.to_string(), "15文字目: 不明な項目です: year");
+}
+
+/// 全ての種類の条件
+fn all_clauses() -> Vec<FilterTarget> {
+    let mut clauses = vec![
+        FilterTarget::Tags {
+            range: TagsFilterRange::Contain { value: 3 },
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::NotContain { value: 12 },
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::None,
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::ContainAny { values: vec![1, 2] },
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::ContainAll { values: vec![3] },
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::ContainNone {
+                values: vec![4, 5, 6],
+            },
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::GroupAny { group_id: 7 },
+        },
+        FilterTarget::Tags {
+            range: TagsFilterRange::GroupNone { group_id: 8 },
+        },
+        FilterTarget::Artwork {
+            range: ArtworkFilterRange::Has,
+        },
+        FilterTarget::Artwork {
+            range: ArtworkFilterRange::None,
+        },
+        FilterTarget::SuggestTarget {
+            range: BoolFilterRange::True,
+        },
+        FilterTarget::SuggestTarget {
+            range: BoolFilterRange::False,
+        },
+        FilterTarget::InPlaylist {
+            range: PlaylistFilterRange::In { value: 4 },
+        },
+        FilterTarget::InPlaylist {
+            range: PlaylistFilterRange::NotIn { value: 5 },
+        },
+    ];
+
+    for recursive in [false, true] {
+        clauses.extend([
+            FilterTarget::Folder {
+                range: FolderFilterRange::In {
+                    value: "Artist/".parse().unwrap(),
+                    recursive,
+                },
+            },
+            FilterTarget::Folder {
+                range: FolderFilterRange::NotIn {
+                    value: r#"Artist/Album "1" (2)/"#.parse().unwrap(),
+                    recursive,
+                },
+            },
+        ]);
+    }
+
+    let value = r#"a "b" \c (d) e"#.to_owned();
+    let string_ranges = [false, true]
+        .into_iter()
+        .flat_map(|normalize| {
+            [
+                StringFilterRange::Equal {
+                    value: value.clone(),
+                    normalize,
+                },
+                StringFilterRange::NotEqual {
+                    value: value.clone(),
+                    normalize,
+                },
+                StringFilterRange::Contain {
+                    value: value.clone(),
+                    normalize,
+                },
+                StringFilterRange::NotContain {
+                    value: value.clone(),
+                    normalize,
+                },
+                StringFilterRange::Start {
+                    value: value.clone(),
+                    normalize,
+                },
+                StringFilterRange::End {
+                    value: value.clone(),
+                    normalize,
+                },
+            ]
+        })
+        .chain([
+            StringFilterRange::Regex {
+                value: value.clone(),
+            },
+            StringFilterRange::RegexIgnoreCase {
+                value: value.clone(),
+            },
+            StringFilterRange::NotRegex {
+                value: value.clone(),
+            },
+            StringFilterRange::NotRegexIgnoreCase {
+                value: String::new(),
+            },
+        ]);
+    for range in string_ranges {
+        clauses.extend([
+            FilterTarget::Genre {
+                range: range.clone(),
+            },
+            FilterTarget::Artist {
+                range: range.clone(),
+            },
+            FilterTarget::AlbumArtist {
+                range: range.clone(),
+            },
+            FilterTarget::Album {
+                range: range.clone(),
+            },
+            FilterTarget::Composer {
+                range: range.clone(),
+            },
+            FilterTarget::Title {
+                range: range.clone(),
+            },
+            FilterTarget::Memo {
+                range: range.clone(),
+            },
+            FilterTarget::MemoManage {
+                range: range.clone(),
+            },
+            FilterTarget::OriginalTrack { range },
+        ]);
+    }
+
+    let int_ranges = [
+        IntFilterRange::Equal { value: 3 },
+        IntFilterRange::NotEqual {
+            value: -1,
+            include_null: false,
+        },
+        IntFilterRange::LargeEqual { value: 61_500 },
+        IntFilterRange::SmallEqual { value: 0 },
+        IntFilterRange::RangeIn {
+            min: 1,
+            max: 180_000,
+        },
+        IntFilterRange::RangeOut {
+            min: 2,
+            max: 5,
+            include_null: false,
+        },
+        IntFilterRange::NotEqual {
+            value: 7,
+            include_null: true,
+        },
+        IntFilterRange::RangeOut {
+            min: 3,
+            max: 4,
+            include_null: true,
+        },
+        IntFilterRange::IsNull,
+        IntFilterRange::NotNull,
+    ];
+    for range in int_ranges {
+        clauses.extend([
+            FilterTarget::Rating {
+                range: range.clone(),
+            },
+            FilterTarget::TrackNumber {
+                range: range.clone(),
+            },
+            FilterTarget::TrackMax {
+                range: range.clone(),
+            },
+            FilterTarget::DiscNumber {
+                range: range.clone(),
+            },
+            FilterTarget::DiscMax {
+                range: range.clone(),
+            },
+        ]);
+        if !matches!(range, IntFilterRange::NotEqual { .. }) {
+            clauses.push(FilterTarget::Duration { range });
+        }
+    }
+
+    let date_ranges = [
+        DateFilterRange::Equal {
+            value: date(2020, 1, 1),
+        },
+        DateFilterRange::NotEqual {
+            value: date(1999, 12, 31),
+            include_null: false,
+        },
+        DateFilterRange::Before {
+            value: date(2021, 9, 25),
+        },
+        DateFilterRange::After {
+            value: date(2012, 4, 5),
+        },
+        DateFilterRange::None,
+        DateFilterRange::NotNull,
+        DateFilterRange::NotEqual {
+            value: date(2000, 2, 29),
+            include_null: true,
+        },
+        DateFilterRange::WithinDays { value: 7 },
+        DateFilterRange::WithinMonths { value: 3 },
+        DateFilterRange::OlderThanDays { value: 30 },
+        DateFilterRange::OlderThanMonths { value: 12 },
+    ];
+    for range in date_ranges {
+        clauses.extend([
+            FilterTarget::ReleaseDate {
+                range: range.clone(),
+            },
+            FilterTarget::EntryDate { range },
+        ]);
+    }
+
+    clauses
+}
+
+#[test]
+fn test_round_trip_all_clauses() {
+    for clause in all_clauses() {
+        let root = and(vec![clause]);
+        let text = to_text(&root);
+        assert_eq!(parse(&text), Ok(root), "text: {text}");
+    }
+}
+
+#[test]
+fn test_round_trip_groups() {
+    let clauses = all_clauses();
+    let root = or(vec![
+        and(clauses[..10].to_vec()),
+        and(vec![]),
+        clauses[10].clone(),
+        and(vec![
+            or(clauses[11..15].to_vec()),
+            and(vec![and(clauses[15..18].to_vec()), clauses[18].clone()]),
+        ]),
+        not(vec![clauses[19].clone()]),
+        nor(vec![clauses[20].clone(), not(clauses[21..23].to_vec())]),
+    ]);
+
+    let text = to_text(&root);
+    assert_eq!(parse(&text), Ok(root), "text: {text}");
+}
+
+#[test_case(and(vec![]), "" ; "empty")]
+#[test_case(artist_contain("a"), r#"artist:"a""# ; "not_group")]
+#[test_case(
+    and(vec![artist_contain("a"), or(vec![rating_large_equal(4), and(vec![])])]),
+    r#"artist:"a" and (rating>=4 or ())"# ;
+    "nested"
+)]
+#[test_case(
+    not(vec![artist_contain("a"), nor(vec![rating_large_equal(4), artist_contain("b")])]),
+    r#"not (artist:"a" and not (rating>=4 or artist:"b"))"# ;
+    "negated_group"
+)]
+#[test_case(
+    FilterTarget::Duration { range: IntFilterRange::RangeIn { min: 61_000, max: 62_500 } },
+    "duration=1:01..1:02.500" ;
+    "duration"
+)]
+fn test_to_text(filter: FilterTarget, expected: &str) {
+    assert_eq!(to_text(&filter), expected);
+}
+
+/// 負の値や、範囲の端の値の再生時間も、出力した文字列から同じ条件に戻せる
+#[test_case(-1_000, "-0:01" ; "negative_second")]
+#[test_case(-61_500, "-1:01.500" ; "negative_minute")]
+#[test_case(i32::MIN, "-35791:23.648" ; "min")]
+#[test_case(i32::MAX, "35791:23.647" ; "max")]
+fn test_round_trip_duration(value: i32, expected: &str) {
+    let root = and(vec![FilterTarget::Duration {
+        range: IntFilterRange::Equal { value },
+    }]);
+
+    let text = to_text(&root);
+    assert_eq!(text, format!("duration={expected}"));
+    assert_eq!(parse(&text), Ok(root));
+}
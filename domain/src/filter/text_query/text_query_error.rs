@@ -0,0 +1,51 @@
+use std::fmt;
+
+/// テキストクエリの解析エラー
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("{}文字目: {kind}", .position + 1)]
+pub struct TextQueryError {
+    /// エラーが発生した位置 (0 始まりの文字数)
+    pub position: usize,
+
+    /// エラーの内容
+    pub kind: TextQueryErrorKind,
+}
+
+/// テキストクエリの解析エラーの内容
+#[derive(Debug, PartialEq, Eq)]
+pub enum TextQueryErrorKind {
+    /// 途中でテキストが終わった
+    UnexpectedEnd,
+
+    /// 想定外の文字
+    UnexpectedChar(char),
+
+    /// 項目名が不明
+    UnknownField(String),
+
+    /// 項目に使用できない演算子
+    UnknownOperator { field: String, op: String },
+
+    /// 項目の値として不正
+    InvalidValue { field: String, value: String },
+
+    /// 否定できない条件に `not` が付いている
+    CannotNegate,
+}
+
+impl fmt::Display for TextQueryErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "クエリが途中で終わっています"),
+            Self::UnexpectedChar(c) => write!(f, "想定外の文字です: {c}"),
+            Self::UnknownField(field) => write!(f, "不明な項目です: {field}"),
+            Self::UnknownOperator { field, op } => {
+                write!(f, "{field} には演算子 {op} を使用できません")
+            }
+            Self::InvalidValue { field, value } => {
+                write!(f, "{field} の値が不正です: {value}")
+            }
+            Self::CannotNegate => write!(f, "この条件は not で否定できません"),
+        }
+    }
+}
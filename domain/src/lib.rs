@@ -11,9 +11,12 @@ pub use non_empty_string::{EmptyStringError, NonEmptyString};
 
 pub mod path;
 pub mod playlist;
+pub mod search_preset;
 
 pub mod sort_type;
-pub use sort_type::{SortType, SortTypeWithPlaylist};
+pub use sort_type::{
+    SortField, SortKey, SortKeyKind, SortSpec, SortSpecItem, SortType, SortTypeWithPlaylist,
+};
 
 pub mod string_order_cnv;
 pub mod track;
@@ -2,8 +2,12 @@
 
 pub mod playlist_error;
 
+pub mod playlist_repository;
+
 pub mod playlist_sqls;
 
+pub mod playlist_tracks_repository;
+
 pub mod playlist_tracks_sqls;
 
 pub mod playlist_tree;
@@ -1,15 +1,50 @@
+use crate::filter::FilterError;
+
 /// プレイリスト関連のエラー
 #[derive(thiserror::Error, Debug)]
 pub enum PlaylistError {
-    #[error("フィルタの deserialize に失敗しました: {}", .0)]
-    FailedToDeserializeFilter(serde_json::Error),
-
     #[error("フィルタプレイリストにフィルタがありません: playlist_id={plist_id}")]
     FilterPlaylistHasNoFilter { plist_id: i32 },
 
+    #[error("フィルタプレイリストが見つかりません: playlist_id={plist_id}")]
+    FilterPlaylistNotFound { plist_id: i32 },
+
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+
+    #[error("プレイリストの参照が循環しています: {}", display_playlist_cycle(.0))]
+    PlaylistCycleDetected(Vec<i32>),
+
     #[error("親が見つからないプレイリストが検出されました: {}", diaplay_playlist_no_parents_detected(.0))]
     PlaylistNoParentsDetected(Vec<PlaylistNoParentsDetectedItem>),
 
+    #[error("プレイリストが見つかりません: playlist_id={plist_id}")]
+    PlaylistNotFound { plist_id: i32 },
+
+    #[error("親に指定したプレイリストがフォルダではありません: playlist_id={parent_id}")]
+    ParentIsNotFolder { parent_id: i32 },
+
+    #[error(
+        "プレイリストを自身の子孫に移動することはできません: playlist_id={plist_id}, parent_id={parent_id}"
+    )]
+    MoveIntoDescendant { plist_id: i32, parent_id: i32 },
+
+    #[error("削除するプレイリストが、他のフィルタプレイリストから参照されています: playlist_id={plist_id}, 参照元={}", display_playlist_ids(.referenced_by))]
+    PlaylistReferencedByFilter {
+        plist_id: i32,
+        referenced_by: Vec<i32>,
+    },
+
+    #[error("通常プレイリストではありません: playlist_id={plist_id}")]
+    PlaylistIsNotNormal { plist_id: i32 },
+
+    #[error("プレイリストの曲の位置が範囲外です: playlist_id={plist_id}, index={index}, len={len}")]
+    TrackIndexOutOfRange {
+        plist_id: i32,
+        index: usize,
+        len: usize,
+    },
+
     #[error(transparent)]
     Sqlx(#[from] sqlx::Error),
 }
@@ -36,6 +71,18 @@ fn diaplay_playlist_no_parents_detected(items: &[PlaylistNoParentsDetectedItem])
     v.join(", ")
 }
 
+/// 循環しているプレイリストの ID を、参照順に矢印で繋いだ文字列
+fn display_playlist_cycle(ids: &[i32]) -> String {
+    let v: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    v.join(" -> ")
+}
+
+/// プレイリストの ID を、カンマで繋いだ文字列
+fn display_playlist_ids(ids: &[i32]) -> String {
+    let v: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    v.join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,486 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet};
+
+use sqlx::{PgTransaction, types::Json};
+
+use crate::{
+    NonEmptyString, SortSpec,
+    filter::{FilterWarning, RootFilter, filter_document, filter_validation},
+    playlist::{PlaylistType, playlist_error::PlaylistError, playlist_sqls},
+};
+
+/// 新規作成するプレイリストの種類と、種類ごとの内容
+#[derive(Debug, PartialEq, Clone)]
+pub enum NewPlaylistContent {
+    /// 通常プレイリスト (曲が空の状態で作成する)
+    Normal,
+
+    /// フィルタプレイリスト
+    Filter(RootFilter),
+
+    /// フォルダプレイリスト
+    Folder,
+}
+
+impl NewPlaylistContent {
+    fn playlist_type(&self) -> PlaylistType {
+        match self {
+            Self::Normal => PlaylistType::Normal,
+            Self::Filter(_) => PlaylistType::Filter,
+            Self::Folder => PlaylistType::Folder,
+        }
+    }
+}
+
+/// 作成したプレイリスト
+#[derive(Debug, PartialEq)]
+pub struct CreatedPlaylist {
+    /// プレイリストID
+    pub id: i32,
+
+    /// フィルタの警告 (フィルタプレイリスト以外では空)
+    pub warnings: Vec<FilterWarning>,
+}
+
+/// プレイリストを作成し、親フォルダの末尾に追加する
+///
+/// フィルタプレイリストのフィルタは、検証・正規化してから保存する。
+/// # Arguments
+/// - parent_id: 親のフォルダプレイリストの ID (None なら最上位)
+pub async fn create_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    parent_id: Option<i32>,
+    name: &NonEmptyString,
+    content: NewPlaylistContent,
+    sort_spec: &SortSpec,
+) -> Result<CreatedPlaylist, PlaylistError> {
+    if let Some(parent_id) = parent_id {
+        check_parent_folder(tx, parent_id).await?;
+    }
+
+    let playlist_type = content.playlist_type();
+    let (filter_json, warnings) = match content {
+        NewPlaylistContent::Filter(filter) => {
+            let validation = filter_validation::validate(tx, filter).await?;
+            (
+                Some(filter_document::to_json(&validation.filter)),
+                validation.warnings,
+            )
+        }
+        _ => (None, vec![]),
+    };
+
+    //通常プレイリストは、常にリストアップ済み
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO playlists (playlist_type, name, parent_id, in_folder_order, filter_json, sort_spec, listuped_flag)
+        VALUES (
+            $1, $2, $3,
+            (SELECT COALESCE(MAX(in_folder_order), -1) + 1 FROM playlists WHERE parent_id IS NOT DISTINCT FROM $3),
+            $4, $5, $6
+        )
+        RETURNING id
+        "#,
+        playlist_type as PlaylistType,
+        name.as_ref() as &str,
+        parent_id,
+        filter_json,
+        Json(sort_spec) as _,
+        playlist_type == PlaylistType::Normal,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    //フォルダの内容が変わりうるため、リストアップ済みフラグを解除する
+    if parent_id.is_some() {
+        playlist_sqls::reset_listuped_flag(tx).await?;
+    }
+
+    Ok(CreatedPlaylist { id, warnings })
+}
+
+/// プレイリストの名前を変更
+pub async fn rename_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    name: &NonEmptyString,
+) -> Result<(), PlaylistError> {
+    let result = sqlx::query!(
+        "UPDATE playlists SET name = $1 WHERE id = $2",
+        name.as_ref() as &str,
+        playlist_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(PlaylistError::PlaylistNotFound {
+            plist_id: playlist_id,
+        });
+    }
+
+    Ok(())
+}
+
+/// フィルタプレイリストのフィルタを更新
+///
+/// フィルタは検証・正規化してから保存する。
+/// `InPlaylist` の参照が循環するフィルタは、リストアップできないため保存しない。
+/// 検索結果が変わるため、全プレイリストのリストアップ済みフラグを解除する。
+/// # Returns
+/// フィルタの警告
+pub async fn update_filter<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    filter: RootFilter,
+) -> Result<Vec<FilterWarning>, PlaylistError> {
+    let validation = filter_validation::validate(tx, filter).await?;
+    check_reference_cycle(tx, playlist_id, &validation.filter).await?;
+
+    let result = sqlx::query!(
+        "UPDATE playlists SET filter_json = $1 WHERE id = $2 AND playlist_type = $3",
+        Json(filter_document::to_json(&validation.filter)) as _,
+        playlist_id,
+        PlaylistType::Filter as PlaylistType,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(PlaylistError::FilterPlaylistNotFound {
+            plist_id: playlist_id,
+        });
+    }
+
+    playlist_sqls::reset_listuped_flag(tx).await?;
+
+    Ok(validation.warnings)
+}
+
+/// プレイリストの並べ替えの指定を更新
+///
+/// DAP に保存するプレイリストファイルの曲順が変わるため、DAP 変更フラグを立てる。
+pub async fn update_sort_spec<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    sort_spec: &SortSpec,
+) -> Result<(), PlaylistError> {
+    let result = sqlx::query!(
+        "UPDATE playlists SET sort_spec = $1, dap_changed = true WHERE id = $2",
+        Json(sort_spec) as _,
+        playlist_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(PlaylistError::PlaylistNotFound {
+            plist_id: playlist_id,
+        });
+    }
+
+    Ok(())
+}
+
+/// プレイリストを、別の親や位置に移動する
+///
+/// 移動元・移動先の `in_folder_order` は 0 から詰めて振り直す。
+/// # Arguments
+/// - parent_id: 移動先の親のフォルダプレイリストの ID (None なら最上位)
+/// - position: 移動先の親の中での位置 (子の数より大きければ末尾)
+pub async fn move_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    parent_id: Option<i32>,
+    position: usize,
+) -> Result<(), PlaylistError> {
+    let old_parent_id = select_parent_id(tx, playlist_id).await?;
+
+    if let Some(parent_id) = parent_id {
+        check_parent_folder(tx, parent_id).await?;
+
+        //移動先の親の祖先に自身が含まれていれば、自身の子孫への移動となる
+        let ancestor_ids = sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT id, parent_id FROM playlists WHERE id = $1
+                UNION
+                SELECT playlists.id, playlists.parent_id
+                FROM playlists JOIN ancestors ON playlists.id = ancestors.parent_id
+            )
+            SELECT id AS "id!" FROM ancestors
+            "#,
+            parent_id,
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        if ancestor_ids.contains(&playlist_id) {
+            return Err(PlaylistError::MoveIntoDescendant {
+                plist_id: playlist_id,
+                parent_id,
+            });
+        }
+    }
+
+    let mut children: Vec<_> = select_children_ids(tx, parent_id)
+        .await?
+        .into_iter()
+        .filter(|id| *id != playlist_id)
+        .collect();
+    children.insert(position.min(children.len()), playlist_id);
+    renumber_children(tx, parent_id, &children).await?;
+
+    if old_parent_id != parent_id {
+        let old_children = select_children_ids(tx, old_parent_id).await?;
+        renumber_children(tx, old_parent_id, &old_children).await?;
+
+        //フォルダの内容が変わるため、リストアップ済みフラグを解除する
+        playlist_sqls::reset_listuped_flag(tx).await?;
+    }
+
+    Ok(())
+}
+
+/// プレイリストを、子孫のプレイリストも含めて削除する
+///
+/// 削除するプレイリストを、削除しないフィルタプレイリストが `InPlaylist` で参照している場合はエラーとする。
+pub async fn delete_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+) -> Result<(), PlaylistError> {
+    let parent_id = select_parent_id(tx, playlist_id).await?;
+
+    let delete_ids = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id FROM playlists WHERE id = $1
+            UNION
+            SELECT playlists.id FROM playlists JOIN subtree ON playlists.parent_id = subtree.id
+        )
+        SELECT id AS "id!" FROM subtree
+        "#,
+        playlist_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    //削除しないフィルタプレイリストから参照されていないか確認
+    //フィルタの deserialize に失敗するプレイリストは、リストアップ時にエラーとなるため、ここでは無視する
+    let filter_rows = sqlx::query!(
+        r#"
+        SELECT id, filter_json AS "filter_json!"
+        FROM playlists
+        WHERE playlist_type = $1 AND filter_json IS NOT NULL AND NOT (id = ANY($2))
+        ORDER BY id
+        "#,
+        PlaylistType::Filter as PlaylistType,
+        &delete_ids,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let referenced_by: Vec<i32> = filter_rows
+        .into_iter()
+        .filter(|row| {
+            filter_document::from_json(row.filter_json.clone()).is_ok_and(|filter| {
+                filter
+                    .referenced_playlist_ids()
+                    .iter()
+                    .any(|id| delete_ids.contains(id))
+            })
+        })
+        .map(|row| row.id)
+        .collect();
+    if !referenced_by.is_empty() {
+        return Err(PlaylistError::PlaylistReferencedByFilter {
+            plist_id: playlist_id,
+            referenced_by,
+        });
+    }
+
+    sqlx::query!(
+        "DELETE FROM playlist_tracks WHERE playlist_id = ANY($1)",
+        &delete_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!("DELETE FROM playlists WHERE id = ANY($1)", &delete_ids)
+        .execute(&mut **tx)
+        .await?;
+
+    let children = select_children_ids(tx, parent_id).await?;
+    renumber_children(tx, parent_id, &children).await?;
+
+    //フォルダの内容が変わりうるため、リストアップ済みフラグを解除する
+    if parent_id.is_some() {
+        playlist_sqls::reset_listuped_flag(tx).await?;
+    }
+
+    Ok(())
+}
+
+/// プレイリストの親の ID を取得
+async fn select_parent_id<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+) -> Result<Option<i32>, PlaylistError> {
+    let row = sqlx::query!("SELECT parent_id FROM playlists WHERE id = $1", playlist_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(PlaylistError::PlaylistNotFound {
+            plist_id: playlist_id,
+        })?;
+
+    Ok(row.parent_id)
+}
+
+/// 親に指定するプレイリストが、フォルダプレイリストか確認
+async fn check_parent_folder<'c>(
+    tx: &mut PgTransaction<'c>,
+    parent_id: i32,
+) -> Result<(), PlaylistError> {
+    let playlist_type = sqlx::query_scalar!(
+        r#"SELECT playlist_type AS "playlist_type: PlaylistType" FROM playlists WHERE id = $1"#,
+        parent_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or(PlaylistError::PlaylistNotFound {
+        plist_id: parent_id,
+    })?;
+
+    if playlist_type != PlaylistType::Folder {
+        return Err(PlaylistError::ParentIsNotFolder { parent_id });
+    }
+
+    Ok(())
+}
+
+/// 親の直下のプレイリストの ID を、`in_folder_order` 順に取得
+async fn select_children_ids<'c>(
+    tx: &mut PgTransaction<'c>,
+    parent_id: Option<i32>,
+) -> sqlx::Result<Vec<i32>> {
+    sqlx::query_scalar!(
+        "SELECT id FROM playlists WHERE parent_id IS NOT DISTINCT FROM $1 ORDER BY in_folder_order",
+        parent_id
+    )
+    .fetch_all(&mut **tx)
+    .await
+}
+
+/// プレイリストの親を設定し、`in_folder_order` を指定した順に 0 から振り直す
+///
+/// UNIQUE (parent_id, in_folder_order) に更新途中で違反しないよう、
+/// 一旦重複しない負の値にしてから、最終的な値を設定する。
+/// # Arguments
+/// - ordered_ids: 親の直下の全プレイリストの ID を、並べたい順に並べたもの
+async fn renumber_children<'c>(
+    tx: &mut PgTransaction<'c>,
+    parent_id: Option<i32>,
+    ordered_ids: &[i32],
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE playlists SET parent_id = $1, in_folder_order = -list.ord::INTEGER
+        FROM UNNEST($2::INTEGER[]) WITH ORDINALITY AS list(id, ord)
+        WHERE playlists.id = list.id
+        "#,
+        parent_id,
+        ordered_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE playlists SET in_folder_order = list.ord::INTEGER - 1
+        FROM UNNEST($1::INTEGER[]) WITH ORDINALITY AS list(id, ord)
+        WHERE playlists.id = list.id
+        "#,
+        ordered_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// フィルタプレイリストのフィルタを変更した場合に、プレイリストの参照が循環しないか確認
+///
+/// 他のプレイリストは保存済みのフィルタ・親子関係を使い、
+/// 対象のプレイリストから、フォルダの子と `InPlaylist` の参照を辿って自身に戻るか判定する。
+/// # Arguments
+/// - playlist_id: フィルタを変更するプレイリストの ID
+/// - filter: 変更後のフィルタ
+async fn check_reference_cycle<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    filter: &RootFilter,
+) -> Result<(), PlaylistError> {
+    let rows = sqlx::query!("SELECT id, parent_id, filter_json FROM playlists")
+        .fetch_all(&mut **tx)
+        .await?;
+
+    //プレイリストの ID と、その内容が依存するプレイリストの ID
+    let mut dependencies: HashMap<i32, Vec<i32>> = HashMap::new();
+    dependencies.insert(playlist_id, filter.referenced_playlist_ids());
+    for row in rows {
+        if let Some(parent_id) = row.parent_id {
+            dependencies.entry(parent_id).or_default().push(row.id);
+        }
+        if row.id == playlist_id {
+            continue;
+        }
+        if let Some(filter) = row
+            .filter_json
+            .and_then(|json| filter_document::from_json(json).ok())
+        {
+            dependencies
+                .entry(row.id)
+                .or_default()
+                .extend(filter.referenced_playlist_ids());
+        }
+    }
+
+    /// from から依存関係を辿り、playlist_id に戻る経路を path に追加する
+    fn find_cycle(
+        dependencies: &HashMap<i32, Vec<i32>>,
+        playlist_id: i32,
+        from: i32,
+        visited: &mut HashSet<i32>,
+        path: &mut Vec<i32>,
+    ) -> bool {
+        for &id in dependencies.get(&from).into_iter().flatten() {
+            if id == playlist_id {
+                path.push(id);
+                return true;
+            }
+            if visited.insert(id) {
+                path.push(id);
+                if find_cycle(dependencies, playlist_id, id, visited, path) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        false
+    }
+
+    let mut path = vec![playlist_id];
+    if find_cycle(
+        &dependencies,
+        playlist_id,
+        playlist_id,
+        &mut HashSet::new(),
+        &mut path,
+    ) {
+        return Err(PlaylistError::PlaylistCycleDetected(path));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+-- プレイリストの作成・変更・移動・削除のテスト用データ
+--
+-- 1 Folder A
+--   2 Normal 1
+--   3 Folder B
+--     4 Filter 1
+--   5 Normal 2
+-- 6 Root Normal
+-- 7 Ref Normal 2 (5 を参照するフィルタ)
+
+INSERT INTO tracks (id, duration, path, title, title_order) VALUES
+    (1, 180, '/music/01.mp3', 'Song 1', 'Song 1'),
+    (2, 180, '/music/02.mp3', 'Song 2', 'Song 2');
+
+INSERT INTO playlists (id, playlist_type, name, parent_id, in_folder_order, filter_json, sort_spec, listuped_flag, dap_changed) VALUES
+    (1, 'folder', 'Folder A', NULL, 0, NULL, '[{"field": "playlist"}]', true, false),
+    (2, 'normal', 'Normal 1', 1, 0, NULL, '[{"field": "playlist"}]', true, false),
+    (3, 'folder', 'Folder B', 1, 1, NULL, '[{"field": "playlist"}]', true, false),
+    (4, 'filter', 'Filter 1', 3, 0,
+        '{"version": 1, "filter": {"target": "rating", "range": {"op": "large_equal", "value": 4}}}',
+        '[{"field": "title"}]', true, false),
+    (5, 'normal', 'Normal 2', 1, 2, NULL, '[{"field": "playlist"}]', true, false),
+    (6, 'normal', 'Root Normal', NULL, 1, NULL, '[{"field": "playlist"}]', true, false),
+    (7, 'filter', 'Ref Normal 2', NULL, 2,
+        '{"version": 1, "filter": {"target": "in_playlist", "range": {"op": "in", "value": 5}}}',
+        '[{"field": "title"}]', true, false);
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    (2, 0, 1),
+    (2, 1, 2),
+    (3, 0, 2),
+    (4, 0, 2),
+    (5, 0, 1),
+    (7, 0, 1);
+
+-- 新規作成するプレイリストの ID が、既存の ID と重複しないようにする
+SELECT setval(pg_get_serial_sequence('playlists', 'id'), (SELECT MAX(id) FROM playlists));
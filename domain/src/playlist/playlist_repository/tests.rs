@@ -0,0 +1,446 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use sqlx::{PgPool, PgTransaction};
+
+use super::*;
+use crate::{
+    SortField, SortSpecItem, SortType,
+    filter::{FilterTarget, FilterWarningKind, PlaylistFilterRange},
+};
+
+/// 親の直下のプレイリストの (ID, in_folder_order) を、in_folder_order 順に取得
+async fn children<'c>(
+    tx: &mut PgTransaction<'c>,
+    parent_id: Option<i32>,
+) -> Result<Vec<(i32, i32)>> {
+    let rows = sqlx::query!(
+        "SELECT id, in_folder_order FROM playlists WHERE parent_id IS NOT DISTINCT FROM $1 ORDER BY in_folder_order",
+        parent_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.id, row.in_folder_order))
+        .collect())
+}
+
+/// 全プレイリストの ID
+async fn all_ids<'c>(tx: &mut PgTransaction<'c>) -> Result<Vec<i32>> {
+    Ok(sqlx::query_scalar!("SELECT id FROM playlists ORDER BY id")
+        .fetch_all(&mut **tx)
+        .await?)
+}
+
+async fn listuped_flag<'c>(tx: &mut PgTransaction<'c>, playlist_id: i32) -> Result<bool> {
+    Ok(sqlx::query_scalar!(
+        "SELECT listuped_flag FROM playlists WHERE id = $1",
+        playlist_id
+    )
+    .fetch_one(&mut **tx)
+    .await?)
+}
+
+fn name(s: &str) -> NonEmptyString {
+    NonEmptyString::from_str(s).unwrap()
+}
+
+mod test_create_playlist {
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_create_normal_root(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let created = create_playlist(
+            &mut tx,
+            None,
+            &name("New Normal"),
+            NewPlaylistContent::Normal,
+            &SortType::Artist.spec(false),
+        )
+        .await?;
+        assert!(created.warnings.is_empty());
+
+        // 最上位の末尾に追加される
+        assert_eq!(
+            children(&mut tx, None).await?,
+            vec![(1, 0), (6, 1), (7, 2), (created.id, 3)]
+        );
+
+        let row = sqlx::query!(
+            r#"SELECT name, playlist_type AS "playlist_type: PlaylistType", filter_json, sort_spec AS "sort_spec: Json<SortSpec>", listuped_flag FROM playlists WHERE id = $1"#,
+            created.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        assert_eq!(row.name, "New Normal");
+        assert_eq!(row.playlist_type, PlaylistType::Normal);
+        assert_eq!(row.filter_json, None);
+        assert_eq!(row.sort_spec.0, SortType::Artist.spec(false));
+        assert!(row.listuped_flag);
+
+        // 最上位への追加では、他のプレイリストのリストアップ済みフラグは変わらない
+        assert!(listuped_flag(&mut tx, 7).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_create_filter_in_folder(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let filter = FilterTarget::InPlaylist {
+            range: PlaylistFilterRange::In { value: 99 },
+        };
+        let created = create_playlist(
+            &mut tx,
+            Some(3),
+            &name("New Filter"),
+            NewPlaylistContent::Filter(filter.clone()),
+            &SortSpec::new(vec![SortSpecItem::new(SortField::AlbumArtist, true)]),
+        )
+        .await?;
+
+        // フィルタの警告を返す
+        assert_eq!(created.warnings.len(), 1);
+        assert_eq!(
+            created.warnings[0].kind,
+            FilterWarningKind::UnknownPlaylist { playlist_id: 99 }
+        );
+
+        assert_eq!(
+            children(&mut tx, Some(3)).await?,
+            vec![(4, 0), (created.id, 1)]
+        );
+
+        let filter_json = sqlx::query_scalar!(
+            r#"SELECT filter_json AS "filter_json!" FROM playlists WHERE id = $1"#,
+            created.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        assert_eq!(filter_document::from_json(filter_json)?, filter);
+
+        // フィルタプレイリストは未リストアップの状態で作成し、他のフラグも解除する
+        assert!(!listuped_flag(&mut tx, created.id).await?);
+        assert!(!listuped_flag(&mut tx, 1).await?);
+        assert!(!listuped_flag(&mut tx, 7).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_create_folder(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let created = create_playlist(
+            &mut tx,
+            Some(1),
+            &name("New Folder"),
+            NewPlaylistContent::Folder,
+            &SortSpec::default(),
+        )
+        .await?;
+
+        assert_eq!(
+            children(&mut tx, Some(1)).await?,
+            vec![(2, 0), (3, 1), (5, 2), (created.id, 3)]
+        );
+        assert!(!listuped_flag(&mut tx, created.id).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_invalid_parent(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = create_playlist(
+            &mut tx,
+            Some(2),
+            &name("In Normal"),
+            NewPlaylistContent::Normal,
+            &SortSpec::default(),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::ParentIsNotFolder { parent_id: 2 })
+        ));
+
+        let result = create_playlist(
+            &mut tx,
+            Some(99),
+            &name("In Unknown"),
+            NewPlaylistContent::Normal,
+            &SortSpec::default(),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::PlaylistNotFound { plist_id: 99 })
+        ));
+
+        assert_eq!(all_ids(&mut tx).await?, vec![1, 2, 3, 4, 5, 6, 7]);
+
+        Ok(())
+    }
+}
+
+mod test_rename_playlist {
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_rename(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        rename_playlist(&mut tx, 2, &name("Renamed")).await?;
+
+        let name = sqlx::query_scalar!("SELECT name FROM playlists WHERE id = 2")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert_eq!(name, "Renamed");
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_not_found(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = rename_playlist(&mut tx, 99, &name("Renamed")).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::PlaylistNotFound { plist_id: 99 })
+        ));
+
+        Ok(())
+    }
+}
+
+mod test_update_sort_spec {
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_update(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let spec = SortSpec::new(vec![
+            SortSpecItem::new(SortField::ReleaseDate, true),
+            SortSpecItem::new(SortField::Rating, false),
+        ]);
+        update_sort_spec(&mut tx, 4, &spec).await?;
+
+        let row = sqlx::query!(
+            r#"SELECT sort_spec AS "sort_spec: Json<SortSpec>", dap_changed FROM playlists WHERE id = 4"#
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        assert_eq!(row.sort_spec.0, spec);
+        // 曲順が変わるため、DAP に保存し直す
+        assert!(row.dap_changed);
+
+        let result = update_sort_spec(&mut tx, 99, &spec).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::PlaylistNotFound { plist_id: 99 })
+        ));
+
+        Ok(())
+    }
+}
+
+mod test_move_playlist {
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_move_in_same_parent(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        move_playlist(&mut tx, 5, Some(1), 0).await?;
+        assert_eq!(
+            children(&mut tx, Some(1)).await?,
+            vec![(5, 0), (2, 1), (3, 2)]
+        );
+
+        move_playlist(&mut tx, 5, Some(1), 1).await?;
+        assert_eq!(
+            children(&mut tx, Some(1)).await?,
+            vec![(2, 0), (5, 1), (3, 2)]
+        );
+
+        // 子の数より大きい位置は末尾
+        move_playlist(&mut tx, 2, Some(1), 10).await?;
+        assert_eq!(
+            children(&mut tx, Some(1)).await?,
+            vec![(5, 0), (3, 1), (2, 2)]
+        );
+
+        // 親が変わらなければ、リストアップ済みフラグは変わらない
+        assert!(listuped_flag(&mut tx, 1).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_move_to_other_parent(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        move_playlist(&mut tx, 2, None, 1).await?;
+        assert_eq!(
+            children(&mut tx, None).await?,
+            vec![(1, 0), (2, 1), (6, 2), (7, 3)]
+        );
+        // 移動元は詰める
+        assert_eq!(children(&mut tx, Some(1)).await?, vec![(3, 0), (5, 1)]);
+
+        // 子孫ごと移動する
+        move_playlist(&mut tx, 3, Some(1), 0).await?;
+        move_playlist(&mut tx, 1, None, 3).await?;
+        move_playlist(&mut tx, 3, None, 0).await?;
+        assert_eq!(
+            children(&mut tx, None).await?,
+            vec![(3, 0), (2, 1), (6, 2), (7, 3), (1, 4)]
+        );
+        assert_eq!(children(&mut tx, Some(1)).await?, vec![(5, 0)]);
+        assert_eq!(children(&mut tx, Some(3)).await?, vec![(4, 0)]);
+
+        // フォルダの内容が変わるため、リストアップ済みフラグを解除する
+        assert!(!listuped_flag(&mut tx, 1).await?);
+        assert!(!listuped_flag(&mut tx, 7).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_move_into_descendant(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = move_playlist(&mut tx, 1, Some(3), 0).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::MoveIntoDescendant {
+                plist_id: 1,
+                parent_id: 3
+            })
+        ));
+
+        // 自身の中への移動
+        let result = move_playlist(&mut tx, 3, Some(3), 0).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::MoveIntoDescendant {
+                plist_id: 3,
+                parent_id: 3
+            })
+        ));
+
+        assert_eq!(
+            children(&mut tx, Some(1)).await?,
+            vec![(2, 0), (3, 1), (5, 2)]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_invalid_target(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = move_playlist(&mut tx, 6, Some(2), 0).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::ParentIsNotFolder { parent_id: 2 })
+        ));
+
+        let result = move_playlist(&mut tx, 99, None, 0).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::PlaylistNotFound { plist_id: 99 })
+        ));
+
+        Ok(())
+    }
+}
+
+mod test_delete_playlist {
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_delete_recursive(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        delete_playlist(&mut tx, 3).await?;
+
+        assert_eq!(all_ids(&mut tx).await?, vec![1, 2, 5, 6, 7]);
+        assert_eq!(children(&mut tx, Some(1)).await?, vec![(2, 0), (5, 1)]);
+
+        // 削除したプレイリストの曲リストも削除する
+        let track_playlist_ids = sqlx::query_scalar!(
+            "SELECT DISTINCT playlist_id FROM playlist_tracks ORDER BY playlist_id"
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        assert_eq!(track_playlist_ids, vec![2, 5, 7]);
+
+        assert!(!listuped_flag(&mut tx, 1).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_delete_root(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        delete_playlist(&mut tx, 6).await?;
+
+        assert_eq!(children(&mut tx, None).await?, vec![(1, 0), (7, 1)]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_referenced_by_filter(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // 子孫が参照されている場合も削除できない
+        for playlist_id in [5, 1] {
+            let result = delete_playlist(&mut tx, playlist_id).await;
+            match result {
+                Err(PlaylistError::PlaylistReferencedByFilter {
+                    plist_id,
+                    referenced_by,
+                }) => {
+                    assert_eq!(plist_id, playlist_id);
+                    assert_eq!(referenced_by, vec![7]);
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+        assert_eq!(all_ids(&mut tx).await?, vec![1, 2, 3, 4, 5, 6, 7]);
+
+        // 参照元を削除すれば削除できる
+        delete_playlist(&mut tx, 7).await?;
+        delete_playlist(&mut tx, 1).await?;
+        assert_eq!(all_ids(&mut tx).await?, vec![6]);
+        assert_eq!(children(&mut tx, None).await?, vec![(6, 0)]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_repository"))]
+    async fn test_not_found(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = delete_playlist(&mut tx, 99).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::PlaylistNotFound { plist_id: 99 })
+        ));
+
+        Ok(())
+    }
+}
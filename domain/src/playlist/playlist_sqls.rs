@@ -1,6 +1,8 @@
+use std::collections::{HashMap, HashSet};
+
 use sqlx::PgTransaction;
 
-use crate::playlist::PlaylistType;
+use crate::{filter::filter_document, playlist::PlaylistType};
 
 /// 全フィルタプレイリスト・フォルダプレイリストの、リストアップ済みフラグを解除する。
 pub async fn reset_listuped_flag<'c>(tx: &mut PgTransaction<'c>) -> sqlx::Result<()> {
@@ -16,6 +18,79 @@ pub async fn reset_listuped_flag<'c>(tx: &mut PgTransaction<'c>) -> sqlx::Result
     Ok(())
 }
 
+/// 指定したプレイリストの内容に依存するプレイリストの、リストアップ済みフラグを解除する
+///
+/// 対象は `select_dependent_ids` を参照。
+pub async fn reset_dependent_listuped_flag<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_ids: &[i32],
+) -> sqlx::Result<()> {
+    if playlist_ids.is_empty() {
+        return Ok(());
+    }
+
+    let reset_ids: Vec<i32> = select_dependent_ids(tx, playlist_ids)
+        .await?
+        .into_iter()
+        .collect();
+    sqlx::query!(
+        "UPDATE playlists SET listuped_flag = false WHERE id = ANY($1) AND playlist_type IN ($2::playlist_type, $3::playlist_type)",
+        &reset_ids,
+        PlaylistType::Filter as PlaylistType,
+        PlaylistType::Folder as PlaylistType
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// 指定したプレイリストの内容に依存するプレイリストの ID を取得
+///
+/// 親のフォルダプレイリストと、フィルタの `InPlaylist` で参照しているフィルタプレイリストを、
+/// 依存関係を辿って全て対象とする。
+/// フィルタの deserialize に失敗するプレイリストは、リストアップ時にエラーとなるため、ここでは無視する
+pub async fn select_dependent_ids<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_ids: &[i32],
+) -> sqlx::Result<HashSet<i32>> {
+    if playlist_ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let rows = sqlx::query!("SELECT id, parent_id, filter_json FROM playlists")
+        .fetch_all(&mut **tx)
+        .await?;
+
+    //プレイリストの ID と、その内容に依存するプレイリストの ID
+    let mut dependents: HashMap<i32, Vec<i32>> = HashMap::new();
+    for row in rows {
+        if let Some(parent_id) = row.parent_id {
+            dependents.entry(row.id).or_default().push(parent_id);
+        }
+        if let Some(filter) = row
+            .filter_json
+            .and_then(|json| filter_document::from_json(json).ok())
+        {
+            for referenced_id in filter.referenced_playlist_ids() {
+                dependents.entry(referenced_id).or_default().push(row.id);
+            }
+        }
+    }
+
+    let mut dependent_ids = HashSet::new();
+    let mut pending = playlist_ids.to_vec();
+    while let Some(id) = pending.pop() {
+        for dependent_id in dependents.get(&id).into_iter().flatten() {
+            if dependent_ids.insert(*dependent_id) {
+                pending.push(*dependent_id);
+            }
+        }
+    }
+
+    Ok(dependent_ids)
+}
+
 pub async fn set_dap_changed<'c>(
     tx: &mut PgTransaction<'c>,
     playlist_id: i32,
@@ -0,0 +1,379 @@
+#[cfg(test)]
+mod tests;
+
+use sqlx::PgTransaction;
+
+use crate::playlist::{
+    PlaylistType, playlist_error::PlaylistError, playlist_sqls, playlist_tracks_sqls,
+};
+
+/// 通常プレイリストの指定位置に曲を挿入する
+///
+/// 挿入位置以降の曲だけを後ろにずらす。
+/// # Arguments
+/// - position: 挿入する位置 (曲数より大きければ末尾)
+/// - track_ids: 挿入する曲の ID (この順に並ぶ)
+pub async fn insert_tracks<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    position: usize,
+    track_ids: &[i32],
+) -> Result<(), PlaylistError> {
+    check_normal_playlist(tx, playlist_id).await?;
+    if track_ids.is_empty() {
+        return Ok(());
+    }
+
+    let position = position.min(count_tracks(tx, playlist_id).await?);
+
+    shift_tracks(tx, playlist_id, position, track_ids.len()).await?;
+    insert_at(tx, playlist_id, position, track_ids).await?;
+
+    on_tracks_changed(tx, playlist_id).await?;
+    Ok(())
+}
+
+/// 通常プレイリストの末尾に曲を追加する
+pub async fn append_tracks<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    track_ids: &[i32],
+) -> Result<(), PlaylistError> {
+    check_normal_playlist(tx, playlist_id).await?;
+    if track_ids.is_empty() {
+        return Ok(());
+    }
+
+    let len = count_tracks(tx, playlist_id).await?;
+    insert_at(tx, playlist_id, len, track_ids).await?;
+
+    on_tracks_changed(tx, playlist_id).await?;
+    Ok(())
+}
+
+/// 通常プレイリストから、指定位置の曲を削除する
+///
+/// 範囲外の位置が含まれていれば、何も削除せずにエラーとする。
+/// # Arguments
+/// - positions: 削除する曲の位置 (順不同・重複可)
+pub async fn remove_tracks_at<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    positions: &[usize],
+) -> Result<(), PlaylistError> {
+    check_normal_playlist(tx, playlist_id).await?;
+
+    let len = count_tracks(tx, playlist_id).await?;
+    if let Some(&index) = positions.iter().find(|&&index| index >= len) {
+        return Err(PlaylistError::TrackIndexOutOfRange {
+            plist_id: playlist_id,
+            index,
+            len,
+        });
+    }
+
+    let Some(&first) = positions.iter().min() else {
+        return Ok(());
+    };
+    let positions: Vec<i32> = positions.iter().map(|&index| index as i32).collect();
+
+    sqlx::query!(
+        "DELETE FROM playlist_tracks WHERE playlist_id = $1 AND order_index = ANY($2)",
+        playlist_id,
+        &positions,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    renumber_from(tx, playlist_id, first).await?;
+
+    on_tracks_changed(tx, playlist_id).await?;
+    Ok(())
+}
+
+/// 通常プレイリストから、指定した曲を全て削除する
+///
+/// # Returns
+/// 削除した件数
+pub async fn remove_tracks<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    track_ids: &[i32],
+) -> Result<usize, PlaylistError> {
+    check_normal_playlist(tx, playlist_id).await?;
+
+    let removed = sqlx::query_scalar!(
+        "DELETE FROM playlist_tracks WHERE playlist_id = $1 AND track_id = ANY($2) RETURNING order_index",
+        playlist_id,
+        track_ids,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    after_remove(tx, playlist_id, &removed).await?;
+    Ok(removed.len())
+}
+
+/// 通常プレイリストの連続した曲を、別の位置に移動する
+///
+/// 移動の影響を受ける範囲の曲だけを並べ直す。
+/// # Arguments
+/// - start: 移動する曲の先頭の位置
+/// - count: 移動する曲数
+/// - to: 移動後の、移動した曲の先頭の位置
+pub async fn move_tracks<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    start: usize,
+    count: usize,
+    to: usize,
+) -> Result<(), PlaylistError> {
+    check_normal_playlist(tx, playlist_id).await?;
+
+    let len = count_tracks(tx, playlist_id).await?;
+    for first in [start, to] {
+        let last = first + count.max(1) - 1;
+        if last >= len {
+            return Err(PlaylistError::TrackIndexOutOfRange {
+                plist_id: playlist_id,
+                index: last,
+                len,
+            });
+        }
+    }
+
+    if count == 0 || start == to {
+        return Ok(());
+    }
+
+    //移動元と移動先を含む範囲
+    let window_start = start.min(to);
+    let window_end = start.max(to) + count;
+
+    //範囲内の曲を一旦削除し、並べ替えてから登録し直す
+    let mut rows = sqlx::query!(
+        r#"
+        DELETE FROM playlist_tracks
+        WHERE playlist_id = $1 AND order_index >= $2 AND order_index < $3
+        RETURNING order_index, track_id
+        "#,
+        playlist_id,
+        window_start as i32,
+        window_end as i32,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+    //RETURNING の順序は保証されないため、order_index 順に並べる
+    rows.sort_by_key(|row| row.order_index);
+
+    let mut track_ids: Vec<i32> = rows.into_iter().map(|row| row.track_id).collect();
+    let moved: Vec<i32> = track_ids
+        .drain(start - window_start..start - window_start + count)
+        .collect();
+    track_ids.splice(to - window_start..to - window_start, moved);
+
+    insert_at(tx, playlist_id, window_start, &track_ids).await?;
+
+    on_tracks_changed(tx, playlist_id).await?;
+    Ok(())
+}
+
+/// 通常プレイリストから重複した曲を削除する
+///
+/// 同じ曲が複数含まれる場合、最も前にあるものを残す。
+/// # Returns
+/// 削除した件数
+pub async fn remove_duplicates<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+) -> Result<usize, PlaylistError> {
+    check_normal_playlist(tx, playlist_id).await?;
+
+    let removed = sqlx::query_scalar!(
+        r#"
+        DELETE FROM playlist_tracks AS target
+        WHERE target.playlist_id = $1 AND EXISTS (
+            SELECT 1 FROM playlist_tracks AS prev
+            WHERE prev.playlist_id = target.playlist_id
+                AND prev.track_id = target.track_id
+                AND prev.order_index < target.order_index
+        )
+        RETURNING target.order_index
+        "#,
+        playlist_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    after_remove(tx, playlist_id, &removed).await?;
+    Ok(removed.len())
+}
+
+/// 通常プレイリストの曲を、全て置き換える
+pub async fn replace_tracks<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    track_ids: &[i32],
+) -> Result<(), PlaylistError> {
+    check_normal_playlist(tx, playlist_id).await?;
+
+    playlist_tracks_sqls::delete_by_playlist_id(tx, playlist_id).await?;
+    insert_at(tx, playlist_id, 0, track_ids).await?;
+
+    on_tracks_changed(tx, playlist_id).await?;
+    Ok(())
+}
+
+/// 曲を編集できる通常プレイリストか確認
+async fn check_normal_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+) -> Result<(), PlaylistError> {
+    let playlist_type = sqlx::query_scalar!(
+        r#"SELECT playlist_type AS "playlist_type: PlaylistType" FROM playlists WHERE id = $1"#,
+        playlist_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or(PlaylistError::PlaylistNotFound {
+        plist_id: playlist_id,
+    })?;
+
+    if playlist_type != PlaylistType::Normal {
+        return Err(PlaylistError::PlaylistIsNotNormal {
+            plist_id: playlist_id,
+        });
+    }
+
+    Ok(())
+}
+
+/// プレイリストの曲数
+async fn count_tracks<'c>(tx: &mut PgTransaction<'c>, playlist_id: i32) -> sqlx::Result<usize> {
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM playlist_tracks WHERE playlist_id = $1"#,
+        playlist_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(count as usize)
+}
+
+/// 曲を削除した後、削除した位置以降の `order_index` を詰めてフラグを更新する
+///
+/// 何も削除していなければ、何もしない。
+/// # Arguments
+/// - removed: 削除した曲の `order_index`
+async fn after_remove<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    removed: &[i32],
+) -> sqlx::Result<()> {
+    let Some(&first) = removed.iter().min() else {
+        return Ok(());
+    };
+
+    renumber_from(tx, playlist_id, first as usize).await?;
+    on_tracks_changed(tx, playlist_id).await
+}
+
+/// 曲を変更した後の、プレイリストのフラグの更新
+///
+/// DAP に保存し直すよう変更フラグを立て、
+/// このプレイリストを参照しうるフィルタプレイリストのリストアップ済みフラグを解除する。
+async fn on_tracks_changed<'c>(tx: &mut PgTransaction<'c>, playlist_id: i32) -> sqlx::Result<()> {
+    playlist_sqls::set_dap_changed(tx, playlist_id, true).await?;
+    playlist_sqls::reset_listuped_flag(tx).await
+}
+
+/// 指定位置以降の曲を、後ろにずらす
+///
+/// PRIMARY KEY (playlist_id, order_index) に更新途中で違反しないよう、
+/// 一旦重複しない負の値にしてから、最終的な値を設定する。
+async fn shift_tracks<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    from: usize,
+    offset: usize,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE playlist_tracks SET order_index = -(order_index + $3) - 1 WHERE playlist_id = $1 AND order_index >= $2",
+        playlist_id,
+        from as i32,
+        offset as i32,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE playlist_tracks SET order_index = -order_index - 1 WHERE playlist_id = $1 AND order_index < 0",
+        playlist_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// 指定位置以降の曲の `order_index` を、順序を保ったまま from から詰めて振り直す
+///
+/// `shift_tracks` と同様に、一旦負の値にしてから最終的な値を設定する。
+async fn renumber_from<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    from: usize,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE playlist_tracks SET order_index = -order_index - 1 WHERE playlist_id = $1 AND order_index >= $2",
+        playlist_id,
+        from as i32,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    //負の値は、元の順序の逆順になっている
+    sqlx::query!(
+        r#"
+        UPDATE playlist_tracks SET order_index = $2 + list.num::INTEGER - 1
+        FROM (
+            SELECT order_index, ROW_NUMBER() OVER (ORDER BY order_index DESC) AS num
+            FROM playlist_tracks
+            WHERE playlist_id = $1 AND order_index < 0
+        ) AS list
+        WHERE playlist_tracks.playlist_id = $1 AND playlist_tracks.order_index = list.order_index
+        "#,
+        playlist_id,
+        from as i32,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// 空いている位置に、曲を順に登録する
+///
+/// # Arguments
+/// - from: 先頭の曲の `order_index`
+async fn insert_at<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    from: usize,
+    track_ids: &[i32],
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO playlist_tracks (playlist_id, order_index, track_id)
+        SELECT $1, $2 + list.ord::INTEGER - 1, list.track_id
+        FROM UNNEST($3::INTEGER[]) WITH ORDINALITY AS list(track_id, ord)
+        "#,
+        playlist_id,
+        from as i32,
+        track_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
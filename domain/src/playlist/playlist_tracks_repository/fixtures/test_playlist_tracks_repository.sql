@@ -0,0 +1,33 @@
+-- 通常プレイリストの曲の編集のテスト用データ
+--
+-- 1 Normal 1: 曲 1, 2, 3, 2, 4
+-- 2 Normal 2: 曲 1
+-- 3 Filter 1 (1 を参照するフィルタ)
+-- 4 Folder A
+
+INSERT INTO tracks (id, duration, path, title, title_order) VALUES
+    (1, 180, '/music/01.mp3', 'Song 1', 'Song 1'),
+    (2, 180, '/music/02.mp3', 'Song 2', 'Song 2'),
+    (3, 180, '/music/03.mp3', 'Song 3', 'Song 3'),
+    (4, 180, '/music/04.mp3', 'Song 4', 'Song 4'),
+    (5, 180, '/music/05.mp3', 'Song 5', 'Song 5');
+
+INSERT INTO playlists (id, playlist_type, name, parent_id, in_folder_order, filter_json, sort_spec, listuped_flag, dap_changed) VALUES
+    (1, 'normal', 'Normal 1', NULL, 0, NULL, '[{"field": "playlist"}]', true, false),
+    (2, 'normal', 'Normal 2', NULL, 1, NULL, '[{"field": "playlist"}]', true, false),
+    (3, 'filter', 'Filter 1', NULL, 2,
+        '{"version": 1, "filter": {"target": "in_playlist", "range": {"op": "in", "value": 1}}}',
+        '[{"field": "title"}]', true, false),
+    (4, 'folder', 'Folder A', NULL, 3, NULL, '[{"field": "playlist"}]', true, false);
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    (1, 0, 1),
+    (1, 1, 2),
+    (1, 2, 3),
+    (1, 3, 2),
+    (1, 4, 4),
+    (2, 0, 1),
+    (3, 0, 1),
+    (3, 1, 2),
+    (3, 2, 3),
+    (3, 3, 4);
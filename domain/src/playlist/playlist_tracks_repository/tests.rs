@@ -0,0 +1,347 @@
+use anyhow::Result;
+use sqlx::{PgPool, PgTransaction};
+
+use super::*;
+
+/// プレイリストの (order_index, 曲ID) を、order_index 順に取得
+async fn tracks<'c>(tx: &mut PgTransaction<'c>, playlist_id: i32) -> Result<Vec<(i32, i32)>> {
+    let rows = sqlx::query!(
+        "SELECT order_index, track_id FROM playlist_tracks WHERE playlist_id = $1 ORDER BY order_index",
+        playlist_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.order_index, row.track_id))
+        .collect())
+}
+
+/// 曲ID を順に並べ、期待する (order_index, 曲ID) のリストとする
+fn ordered(track_ids: &[i32]) -> Vec<(i32, i32)> {
+    track_ids
+        .iter()
+        .enumerate()
+        .map(|(index, track_id)| (index as i32, *track_id))
+        .collect()
+}
+
+/// (DAP 変更フラグ, フィルタプレイリストのリストアップ済みフラグ)
+async fn flags<'c>(tx: &mut PgTransaction<'c>) -> Result<(bool, bool)> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            (SELECT dap_changed FROM playlists WHERE id = 1) AS "dap_changed!",
+            (SELECT listuped_flag FROM playlists WHERE id = 3) AS "listuped_flag!"
+        "#
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok((row.dap_changed, row.listuped_flag))
+}
+
+mod test_insert_tracks {
+    use super::*;
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_insert_middle(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        insert_tracks(&mut tx, 1, 1, &[5, 4]).await?;
+
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 5, 4, 2, 3, 2, 4]));
+        // DAP に保存し直し、参照するフィルタプレイリストはリストアップし直す
+        assert_eq!(flags(&mut tx).await?, (true, false));
+
+        // 他のプレイリストは変わらない
+        assert_eq!(tracks(&mut tx, 2).await?, ordered(&[1]));
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_insert_edges(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        insert_tracks(&mut tx, 1, 0, &[5]).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[5, 1, 2, 3, 2, 4]));
+
+        // 曲数より大きい位置は末尾
+        insert_tracks(&mut tx, 1, 100, &[3]).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[5, 1, 2, 3, 2, 4, 3]));
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_empty(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        insert_tracks(&mut tx, 1, 1, &[]).await?;
+        append_tracks(&mut tx, 1, &[]).await?;
+
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 2, 3, 2, 4]));
+        assert_eq!(flags(&mut tx).await?, (false, true));
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_not_normal(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        for playlist_id in [3, 4] {
+            let result = insert_tracks(&mut tx, playlist_id, 0, &[5]).await;
+            assert!(matches!(
+                result,
+                Err(PlaylistError::PlaylistIsNotNormal { plist_id }) if plist_id == playlist_id
+            ));
+        }
+
+        let result = insert_tracks(&mut tx, 99, 0, &[5]).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::PlaylistNotFound { plist_id: 99 })
+        ));
+
+        Ok(())
+    }
+}
+
+mod test_append_tracks {
+    use super::*;
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_append(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        append_tracks(&mut tx, 1, &[5, 1]).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 2, 3, 2, 4, 5, 1]));
+        assert_eq!(flags(&mut tx).await?, (true, false));
+
+        Ok(())
+    }
+}
+
+mod test_remove_tracks_at {
+    use super::*;
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_remove(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        remove_tracks_at(&mut tx, 1, &[3, 1, 3]).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 3, 4]));
+        assert_eq!(flags(&mut tx).await?, (true, false));
+
+        remove_tracks_at(&mut tx, 1, &[0]).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[3, 4]));
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_out_of_range(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = remove_tracks_at(&mut tx, 1, &[0, 5]).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::TrackIndexOutOfRange {
+                plist_id: 1,
+                index: 5,
+                len: 5
+            })
+        ));
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 2, 3, 2, 4]));
+
+        Ok(())
+    }
+}
+
+mod test_remove_tracks {
+    use super::*;
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_remove(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        assert_eq!(remove_tracks(&mut tx, 1, &[2, 5]).await?, 2);
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 3, 4]));
+        assert_eq!(flags(&mut tx).await?, (true, false));
+
+        // 他のプレイリストの曲は削除しない
+        assert_eq!(tracks(&mut tx, 3).await?, ordered(&[1, 2, 3, 4]));
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_no_match(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        assert_eq!(remove_tracks(&mut tx, 1, &[5]).await?, 0);
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 2, 3, 2, 4]));
+        assert_eq!(flags(&mut tx).await?, (false, true));
+
+        Ok(())
+    }
+}
+
+mod test_move_tracks {
+    use super::*;
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_move_backward(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // [1, 2] を末尾へ
+        move_tracks(&mut tx, 1, 0, 2, 3).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[3, 2, 4, 1, 2]));
+        assert_eq!(flags(&mut tx).await?, (true, false));
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_move_forward(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // [3, 2] を先頭から 2 番目へ
+        move_tracks(&mut tx, 1, 2, 2, 1).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 3, 2, 2, 4]));
+
+        move_tracks(&mut tx, 1, 4, 1, 0).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[4, 1, 3, 2, 2]));
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_no_move(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        move_tracks(&mut tx, 1, 1, 2, 1).await?;
+        move_tracks(&mut tx, 1, 1, 0, 3).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 2, 3, 2, 4]));
+        assert_eq!(flags(&mut tx).await?, (false, true));
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_out_of_range(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        // 移動元が範囲外
+        let result = move_tracks(&mut tx, 1, 4, 2, 0).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::TrackIndexOutOfRange {
+                plist_id: 1,
+                index: 5,
+                len: 5
+            })
+        ));
+
+        // 移動先が範囲外
+        let result = move_tracks(&mut tx, 1, 0, 2, 4).await;
+        assert!(matches!(
+            result,
+            Err(PlaylistError::TrackIndexOutOfRange {
+                plist_id: 1,
+                index: 5,
+                len: 5
+            })
+        ));
+
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 2, 3, 2, 4]));
+
+        Ok(())
+    }
+}
+
+mod test_remove_duplicates {
+    use super::*;
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_remove(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        append_tracks(&mut tx, 1, &[1, 4]).await?;
+        assert_eq!(remove_duplicates(&mut tx, 1).await?, 3);
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[1, 2, 3, 4]));
+        assert_eq!(flags(&mut tx).await?, (true, false));
+
+        // 重複が無ければ何もしない
+        assert_eq!(remove_duplicates(&mut tx, 2).await?, 0);
+        assert_eq!(tracks(&mut tx, 2).await?, ordered(&[1]));
+
+        Ok(())
+    }
+}
+
+mod test_replace_tracks {
+    use super::*;
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_tracks_repository")
+    )]
+    async fn test_replace(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        replace_tracks(&mut tx, 1, &[5, 3, 5]).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, ordered(&[5, 3, 5]));
+        assert_eq!(flags(&mut tx).await?, (true, false));
+
+        replace_tracks(&mut tx, 1, &[]).await?;
+        assert_eq!(tracks(&mut tx, 1).await?, vec![]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+//! 検索プリセット関係のDB機能
+
+pub mod search_preset_sqls;
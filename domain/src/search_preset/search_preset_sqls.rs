@@ -0,0 +1,50 @@
+use sqlx::{PgTransaction, types::Json};
+
+use crate::filter::{FilterError, FilterWarning, RootFilter, filter_document, filter_validation};
+
+/// 検索プリセットを保存
+///
+/// 同じ名前のプリセットがあればフィルタを上書きし、無ければ末尾に追加する。
+/// フィルタは検証・正規化してから保存する。
+/// # Returns
+/// フィルタの警告
+pub async fn save_preset<'c>(
+    tx: &mut PgTransaction<'c>,
+    name: &str,
+    filter: RootFilter,
+) -> Result<Vec<FilterWarning>, FilterError> {
+    let validation = filter_validation::validate(tx, filter).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO search_presets (order_index, name, filter_json)
+        VALUES ((SELECT COALESCE(MAX(order_index), -1) + 1 FROM search_presets), $1, $2)
+        ON CONFLICT (name) DO UPDATE SET filter_json = EXCLUDED.filter_json
+        "#,
+        name,
+        Json(filter_document::to_json(&validation.filter)) as _,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(validation.warnings)
+}
+
+/// 検索プリセットのフィルタを、名前を指定して取得
+///
+/// 古い形式で保存されたフィルタは、現在の形式に変換して返す。
+/// # Returns
+/// プリセットが無ければ None
+pub async fn select_filter_by_name<'c>(
+    tx: &mut PgTransaction<'c>,
+    name: &str,
+) -> Result<Option<RootFilter>, FilterError> {
+    let json = sqlx::query_scalar!(
+        "SELECT filter_json FROM search_presets WHERE name = $1",
+        name
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    json.map(filter_document::from_json).transpose()
+}
@@ -1,3 +1,4 @@
+pub mod sort_spec;
 pub mod with_playlist;
 
 #[cfg(test)]
@@ -6,12 +7,12 @@ mod tests;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub use sort_spec::{SortField, SortSpec, SortSpecItem};
 pub use with_playlist::SortTypeWithPlaylist;
 
 /// 曲のソートの種類
 ///
-/// SortTypeWithPlaylist では SortType 定義を流用しているが、ToSchema 実装では文字列の配列を直接指定している。
-/// SortType の種類を増やす際は注意！
+/// SortTypeWithPlaylist の ToSchema 実装でも、この schema を使用する
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
@@ -40,6 +41,14 @@ pub enum SortType {
     EntryDate,
     /// パス
     Path,
+    /// ランダム
+    ///
+    /// 同じ seed なら同じ順序となるため、ページ分割して取得しても並びが変わらない
+    Random { seed: i32 },
+    /// レートで重み付けしたランダム
+    ///
+    /// レートが高い曲ほど前に来やすい。同じ seed なら同じ順序となる
+    WeightedShuffle { seed: i32 },
 }
 
 impl SortType {
@@ -47,41 +56,121 @@ impl SortType {
     ///
     /// - is_desc: ソートが降順か
     ///
-    /// `title_order ASC, tracks.id DESC` の形式の文字列を返す
+    /// `tracks.title_order ASC, tracks.id DESC` の形式の文字列を返す
     pub fn order_query(&self, desc: bool) -> String {
-        let dir = asc_or_desc_query(desc);
+        keys_order_query(&self.spec(desc).sort_keys(None))
+    }
+
+    /// ランダムの並び順の seed (ランダムの並び順でなければ None)
+    pub fn seed(&self) -> Option<i32> {
+        match self {
+            Self::Random { seed } | Self::WeightedShuffle { seed } => Some(*seed),
+            _ => None,
+        }
+    }
+
+    /// 並べ替えのキーを、優先順に取得
+    ///
+    /// 最後は必ず `tracks.id` となり、曲ごとに一意な順序となる。
+    pub fn sort_keys(&self) -> Vec<SortKey> {
+        self.spec(false).sort_keys(None)
+    }
+
+    /// この並び順と同じ並べ替えの指定を作成
+    ///
+    /// - desc: 全ての項目を降順とするか
+    pub fn spec(&self, desc: bool) -> SortSpec {
+        SortSpec::new(
+            self.fields()
+                .into_iter()
+                .map(|field| SortSpecItem::new(field, desc))
+                .collect(),
+        )
+    }
+
+    /// 並べ替えの項目を、優先順に取得
+    pub fn fields(&self) -> Vec<SortField> {
+        use SortField::*;
 
         match self {
-            Self::TrackName => format!("title_order {dir}, tracks.id {dir}"),
-            Self::Artist => format!(
-                "artist_order {dir}, album_order {dir}, disc_number {dir}, track_number {dir}, title_order {dir}, tracks.id {dir}"
-            ),
-            Self::Album => format!(
-                "album_order {dir}, artist_order {dir}, disc_number {dir}, track_number {dir}, title_order {dir}, tracks.id {dir}"
-            ),
-            Self::Genre => format!(
-                "genre {dir}, artist_order {dir}, album_order {dir}, disc_number {dir}, track_number {dir}, title_order {dir}, tracks.id {dir}"
-            ),
-            Self::Composer => format!(
-                "composer_order {dir}, artist_order {dir}, album_order {dir}, disc_number {dir}, track_number {dir}, title_order {dir}, tracks.id {dir}"
-            ),
-            Self::Duration => format!("duration {dir}, title_order {dir}, tracks.id {dir}"),
-            Self::TrackIndex => format!(
-                "track_number {dir}, artist_order {dir}, album_order {dir}, disc_number {dir}, title_order {dir}, tracks.id {dir}"
-            ),
-            Self::DiscIndex => format!(
-                "disc_number {dir}, artist_order {dir}, album_order {dir}, track_number {dir}, title_order {dir}, tracks.id {dir}"
-            ),
-            Self::ReleaseDate => format!(
-                "release_date {dir}, artist_order {dir}, album_order {dir}, disc_number {dir}, track_number {dir}, title_order {dir}, tracks.id {dir}"
-            ),
-            Self::Rating => format!(
-                "rating {dir}, artist_order {dir}, album_order {dir}, disc_number {dir}, track_number {dir}, title_order {dir}, tracks.id {dir}"
-            ),
-            Self::EntryDate => format!("created_at {dir}, path {dir}"),
-            Self::Path => format!("path {dir}"),
+            Self::TrackName => vec![Title],
+            Self::Artist => vec![Artist, Album, DiscNumber, TrackNumber, Title],
+            Self::Album => vec![Album, Artist, DiscNumber, TrackNumber, Title],
+            Self::Genre => vec![Genre, Artist, Album, DiscNumber, TrackNumber, Title],
+            Self::Composer => vec![Composer, Artist, Album, DiscNumber, TrackNumber, Title],
+            Self::Duration => vec![Duration, Title],
+            Self::TrackIndex => vec![TrackNumber, Artist, Album, DiscNumber, Title],
+            Self::DiscIndex => vec![DiscNumber, Artist, Album, TrackNumber, Title],
+            Self::ReleaseDate => vec![ReleaseDate, Artist, Album, DiscNumber, TrackNumber, Title],
+            Self::Rating => vec![Rating, Artist, Album, DiscNumber, TrackNumber, Title],
+            Self::EntryDate => vec![EntryDate, Path],
+            Self::Path => vec![Path],
+            Self::Random { seed } => vec![Random { seed: *seed }],
+            Self::WeightedShuffle { seed } => vec![WeightedShuffle { seed: *seed }],
+        }
+    }
+}
+
+/// 並べ替えのキー
+///
+/// キーの式は NULL にならない。
+/// NULL になりうるカラムは、NULL かどうかのキーと、NULL を置き換えた値のキーに分ける。
+/// (昇順では NULL が最後、降順では NULL が最初となり、PostgreSQL の既定の並び順と一致する)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SortKey {
+    /// キーの SQL の式
+    pub expression: String,
+
+    /// キーの値の型
+    pub kind: SortKeyKind,
+
+    /// 降順か
+    pub desc: bool,
+}
+
+impl SortKey {
+    pub fn new(expression: impl Into<String>, kind: SortKeyKind) -> Self {
+        Self {
+            expression: expression.into(),
+            kind,
+            desc: false,
         }
     }
+
+    /// 並び順の方向を指定したキー
+    pub fn with_desc(mut self, desc: bool) -> Self {
+        self.desc = desc;
+        self
+    }
+
+    /// NULL になりうる整数のカラムのキー
+    pub(crate) fn nullable_int(column: &str) -> [Self; 2] {
+        [
+            Self::new(format!("{column} IS NULL"), SortKeyKind::Bool),
+            Self::new(format!("COALESCE({column}, 0)"), SortKeyKind::Int),
+        ]
+    }
+}
+
+/// 並べ替えのキーの値の型
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortKeyKind {
+    Bool,
+    SmallInt,
+    Int,
+    Text,
+    Date,
+    DateTime,
+}
+
+/// 並べ替えのキーから、ソート順のクエリを作成
+///
+/// `tracks.title_order ASC, tracks.id ASC` の形式の文字列を返す
+pub fn keys_order_query(keys: &[SortKey]) -> String {
+    keys.iter()
+        .map(|key| format!("{} {}", key.expression, asc_or_desc_query(key.desc)))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[derive(Debug, Error)]
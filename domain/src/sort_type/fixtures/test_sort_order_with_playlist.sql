@@ -26,8 +26,8 @@ INSERT INTO tracks (
     (4, 160, '/music/album1/04.mp3', 'D Song', 'D Song', 'Artist A', 'Artist A', 'Album A', 'Album A', 'Rock', 'Composer A', 'Composer A', 3, 1, '2023-01-15', 2, '2023-06-04 13:00:00'),
     (5, 220, '/music/album2/05.mp3', 'E Song', 'E Song', 'Artist B', 'Artist B', 'Album B', 'Album B', 'Jazz', 'Composer B', 'Composer B', 2, 2, '2023-02-15', 5, '2023-06-05 14:00:00');
 
-INSERT INTO playlists (id, playlist_type, name, in_folder_order, sort_type) VALUES
-    (1, 'normal', 'Playlist A', 0, 'playlist');
+INSERT INTO playlists (id, playlist_type, name, in_folder_order, sort_spec) VALUES
+    (1, 'normal', 'Playlist A', 0, '[{"field": "playlist"}]');
 
 INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
     (1, 0, 1),
@@ -0,0 +1,9 @@
+-- SortSpec のテスト用データ
+-- アルバムアーティストやリリース日、レートの値が同じ曲を含む
+
+INSERT INTO tracks (id, duration, path, title, title_order, album_artist, album_artist_order, release_date, rating) VALUES
+    (1, 180, '/music/01.mp3', 'A Song', 'A Song', 'Various', 'Various', '2023-01-01', 3),
+    (2, 180, '/music/02.mp3', 'B Song', 'B Song', 'Artist A', 'Artist A', NULL, 5),
+    (3, 180, '/music/03.mp3', 'C Song', 'C Song', 'Artist B', 'Artist B', '2023-01-01', 5),
+    (4, 180, '/music/04.mp3', 'D Song', 'D Song', 'Artist A', 'Artist A', '2022-01-01', 3),
+    (5, 180, '/music/05.mp3', 'E Song', 'E Song', 'Various', 'Various', '2023-01-01', 3);
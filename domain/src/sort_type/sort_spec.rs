@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+
+use crate::sort_type::{SortKey, SortKeyKind};
+
+/// 曲の並べ替えの項目
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum SortField {
+    /// 曲名
+    Title,
+    /// アーティスト
+    Artist,
+    /// アルバムアーティスト
+    AlbumArtist,
+    /// アルバム
+    Album,
+    /// ジャンル
+    Genre,
+    /// 作曲者
+    Composer,
+    /// 再生時間
+    Duration,
+    /// トラック番号
+    TrackNumber,
+    /// ディスク番号
+    DiscNumber,
+    /// リリース日
+    ReleaseDate,
+    /// レート
+    Rating,
+    /// 登録日
+    EntryDate,
+    /// パス
+    Path,
+    /// ランダム
+    ///
+    /// 同じ seed なら同じ順序となる
+    Random { seed: i32 },
+    /// レートで重み付けしたランダム
+    ///
+    /// レートが高い曲ほど前に来やすい。同じ seed なら同じ順序となる
+    WeightedShuffle { seed: i32 },
+    /// プレイリストでの並び順
+    ///
+    /// プレイリストの曲の検索でのみ使用可能
+    Playlist,
+}
+
+impl SortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Artist => "artist",
+            Self::AlbumArtist => "album_artist",
+            Self::Album => "album",
+            Self::Genre => "genre",
+            Self::Composer => "composer",
+            Self::Duration => "duration",
+            Self::TrackNumber => "track_number",
+            Self::DiscNumber => "disc_number",
+            Self::ReleaseDate => "release_date",
+            Self::Rating => "rating",
+            Self::EntryDate => "entry_date",
+            Self::Path => "path",
+            Self::Random { .. } => "random",
+            Self::WeightedShuffle { .. } => "weighted_shuffle",
+            Self::Playlist => "playlist",
+        }
+    }
+
+    /// 項目の並べ替えのキー (昇順)
+    ///
+    /// - playlist_track_index_column: `playlist_tracks.order_index` カラムの名前
+    ///   (プレイリストの曲の検索でなければ None とし、プレイリスト順のキーは作成しない)
+    fn sort_keys(&self, playlist_track_index_column: Option<&str>) -> Vec<SortKey> {
+        match self {
+            Self::Title => vec![SortKey::new("tracks.title_order", SortKeyKind::Text)],
+            Self::Artist => vec![SortKey::new("tracks.artist_order", SortKeyKind::Text)],
+            Self::AlbumArtist => vec![SortKey::new("tracks.album_artist_order", SortKeyKind::Text)],
+            Self::Album => vec![SortKey::new("tracks.album_order", SortKeyKind::Text)],
+            Self::Genre => vec![SortKey::new("tracks.genre", SortKeyKind::Text)],
+            Self::Composer => vec![SortKey::new("tracks.composer_order", SortKeyKind::Text)],
+            Self::Duration => vec![SortKey::new("tracks.duration", SortKeyKind::Int)],
+            Self::TrackNumber => SortKey::nullable_int("tracks.track_number").into(),
+            Self::DiscNumber => SortKey::nullable_int("tracks.disc_number").into(),
+            Self::ReleaseDate => vec![
+                SortKey::new("tracks.release_date IS NULL", SortKeyKind::Bool),
+                SortKey::new(
+                    "COALESCE(tracks.release_date, DATE '1970-01-01')",
+                    SortKeyKind::Date,
+                ),
+            ],
+            Self::Rating => vec![SortKey::new("tracks.rating", SortKeyKind::SmallInt)],
+            Self::EntryDate => vec![SortKey::new("tracks.created_at", SortKeyKind::DateTime)],
+            Self::Path => vec![SortKey::new("tracks.path", SortKeyKind::Text)],
+            Self::Random { seed } => vec![SortKey::new(
+                format!("md5('{seed}:' || tracks.id)"),
+                SortKeyKind::Text,
+            )],
+            // seed と曲 ID から (0, 1) の一様乱数 u を作り、-ln(u) / 重み の昇順に並べる。
+            // (重み付きのランダムサンプリングと同じ方法で、重みが大きい曲ほど値が小さくなりやすい)
+            Self::WeightedShuffle { seed } => vec![SortKey::new(
+                format!(
+                    "FLOOR(-LN((('x' || substr(md5('{seed}:' || tracks.id), 1, 8))::bit(32)::bigint + 1) / 4294967297.0) \
+                    / (GREATEST(tracks.rating, 0) + 1) * 10000000)::INTEGER"
+                ),
+                SortKeyKind::Int,
+            )],
+            Self::Playlist => playlist_track_index_column
+                .map(|column| SortKey::new(column, SortKeyKind::Int))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// 並べ替えの項目と、その方向
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SortSpecItem {
+    /// 並べ替えの項目
+    #[serde(flatten)]
+    pub field: SortField,
+
+    /// 降順か
+    #[serde(default)]
+    pub desc: bool,
+}
+
+impl SortSpecItem {
+    pub fn new(field: SortField, desc: bool) -> Self {
+        Self { field, desc }
+    }
+}
+
+/// 曲の並べ替えの指定
+///
+/// 並べ替えの項目と方向の組を、優先順に並べたもの。
+/// プレイリストには JSON の配列として保存する (`playlists.sort_spec`)。
+///
+/// 順序が一意になるよう、並べ替えのキーの最後には `tracks.id` が自動で加わる。
+/// 既存の `SortType` は、`SortType::spec` で名前付きの指定として使用できる。
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SortSpec {
+    items: Vec<SortSpecItem>,
+}
+
+impl SortSpec {
+    /// 項目を優先順に指定して作成
+    ///
+    /// 項目が空なら、曲 ID 順となる
+    pub fn new(items: Vec<SortSpecItem>) -> Self {
+        Self { items }
+    }
+
+    /// 並べ替えの項目
+    pub fn items(&self) -> &[SortSpecItem] {
+        &self.items
+    }
+
+    /// プレイリスト順の項目を含むか
+    pub fn uses_playlist_order(&self) -> bool {
+        self.items
+            .iter()
+            .any(|item| item.field == SortField::Playlist)
+    }
+
+    /// ランダムの項目を含むか
+    pub fn uses_random_order(&self) -> bool {
+        self.items.iter().any(|item| {
+            matches!(
+                item.field,
+                SortField::Random { .. } | SortField::WeightedShuffle { .. }
+            )
+        })
+    }
+
+    /// ランダムの項目の seed を、全て指定した seed に置き換えた指定を作成
+    pub fn with_random_seed(&self, seed: i32) -> Self {
+        let items = self
+            .items
+            .iter()
+            .map(|item| {
+                let field = match item.field {
+                    SortField::Random { .. } => SortField::Random { seed },
+                    SortField::WeightedShuffle { .. } => SortField::WeightedShuffle { seed },
+                    field => field,
+                };
+                SortSpecItem::new(field, item.desc)
+            })
+            .collect();
+        Self::new(items)
+    }
+
+    /// カーソルに記録する並び順の名前
+    ///
+    /// `release_date desc,random:3` のように、項目と方向を全て含む
+    pub fn cursor_name(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| {
+                let mut name = item.field.as_str().to_owned();
+                if let SortField::Random { seed } | SortField::WeightedShuffle { seed } = item.field
+                {
+                    name.push_str(&format!(":{seed}"));
+                }
+                if item.desc {
+                    name.push_str(" desc");
+                }
+                name
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// 並べ替えのキーを、優先順に取得
+    ///
+    /// - playlist_track_index_column: `playlist_tracks.order_index` カラムの名前
+    ///   (プレイリストの曲の検索でなければ None)
+    ///
+    /// 指定した項目のキーの後に `tracks.id` を加え、曲ごとに一意な順序とする。
+    /// プレイリストの曲の検索では、同じ曲が複数含まれる場合も一意になるよう、
+    /// プレイリスト順の項目が無ければ更にプレイリスト内の位置を加える。
+    /// 加えるキーの方向は、最後の項目の方向に合わせる。
+    pub fn sort_keys(&self, playlist_track_index_column: Option<&str>) -> Vec<SortKey> {
+        let mut keys: Vec<_> = self
+            .items
+            .iter()
+            .flat_map(|item| {
+                item.field
+                    .sort_keys(playlist_track_index_column)
+                    .into_iter()
+                    .map(|key| key.with_desc(item.desc))
+            })
+            .collect();
+
+        let desc = self.items.last().is_some_and(|item| item.desc);
+        keys.push(SortKey::new("tracks.id", SortKeyKind::Int).with_desc(desc));
+
+        if let Some(column) = playlist_track_index_column
+            && !self.uses_playlist_order()
+        {
+            keys.push(SortKey::new(column, SortKeyKind::Int).with_desc(desc));
+        }
+
+        keys
+    }
+}
+
+impl From<Vec<SortSpecItem>> for SortSpec {
+    fn from(items: Vec<SortSpecItem>) -> Self {
+        Self::new(items)
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use sqlx::PgPool;
 
-use crate::{SortType, SortTypeWithPlaylist};
+use crate::{SortField, SortSpec, SortSpecItem, SortType, SortTypeWithPlaylist};
 
 /// SortType::order_query() のテスト
 mod test_order_query {
@@ -298,3 +298,280 @@ mod test_order_query_with_playlist {
         Ok(())
     }
 }
+
+/// ランダムの並び順のテスト
+mod test_random {
+    use super::*;
+
+    async fn select_ids(pool: &PgPool, sort_type: SortType) -> anyhow::Result<Vec<i32>> {
+        let order_query = sort_type.order_query(false);
+        let sql = format!("SELECT id FROM tracks ORDER BY {order_query}");
+
+        Ok(sqlx::query_scalar(&sql).fetch_all(pool).await?)
+    }
+
+    /// 同じ seed なら同じ順序、異なる seed なら異なる順序
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_sort_order"))]
+    async fn test_random_stable(pool: PgPool) -> Result<()> {
+        let first = select_ids(&pool, SortType::Random { seed: 1 }).await?;
+        let second = select_ids(&pool, SortType::Random { seed: 1 }).await?;
+        let other_seed = select_ids(&pool, SortType::Random { seed: 2 }).await?;
+
+        assert_eq!(first, second);
+        assert_ne!(first, other_seed);
+
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(sorted, vec![1, 2, 3, 4, 5]);
+
+        Ok(())
+    }
+
+    /// レートが高い曲ほど前に来やすい
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_weighted_shuffle(pool: PgPool) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO tracks (id, duration, path, title, rating)
+            SELECT i, 100, '/music/' || i || '.mp3', 'Track ' || i, CASE WHEN i % 2 = 0 THEN 5 ELSE 0 END
+            FROM generate_series(1, 600) AS i",
+        )
+        .execute(&pool)
+        .await?;
+
+        for seed in [1, 2, 3] {
+            let sort_type = SortType::WeightedShuffle { seed };
+            let ids = select_ids(&pool, sort_type).await?;
+            assert_eq!(ids, select_ids(&pool, sort_type).await?);
+
+            // 前半の曲のうち、レート 5 の曲が大半を占める
+            let high_rated = ids[..300].iter().filter(|id| *id % 2 == 0).count();
+            assert!(high_rated > 200, "seed: {seed}, high_rated: {high_rated}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_serde() -> anyhow::Result<()> {
+        let sort_type = SortType::Random { seed: 3 };
+        let json = serde_json::to_value(sort_type)?;
+        assert_eq!(json, serde_json::json!({ "random": { "seed": 3 } }));
+        assert_eq!(serde_json::from_value::<SortType>(json.clone())?, sort_type);
+
+        let with_playlist: SortTypeWithPlaylist = serde_json::from_value(json)?;
+        assert_eq!(with_playlist, sort_type.into());
+        assert_eq!(
+            serde_json::to_value(with_playlist)?,
+            serde_json::json!({ "random": { "seed": 3 } })
+        );
+
+        let with_playlist: SortTypeWithPlaylist = serde_json::from_str(r#""artist""#)?;
+        assert_eq!(with_playlist, SortType::Artist.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str_seed() {
+        let sort_type: SortTypeWithPlaylist = "weighted_shuffle:42".parse().unwrap();
+        assert_eq!(sort_type, SortType::WeightedShuffle { seed: 42 }.into());
+        assert_eq!(sort_type.as_str(), "weighted_shuffle");
+
+        let sort_type: SortTypeWithPlaylist = "random:-3".parse().unwrap();
+        assert_eq!(sort_type, SortType::Random { seed: -3 }.into());
+
+        // seed の無いランダムや、不正な seed はエラー
+        for s in [
+            "random",
+            "weighted_shuffle",
+            "random:",
+            "random:x",
+            "artist:1",
+        ] {
+            assert!(s.parse::<SortTypeWithPlaylist>().is_err(), "{s}");
+        }
+
+        // seed の無い名前での deserialize もエラー
+        assert!(serde_json::from_str::<SortTypeWithPlaylist>(r#""random""#).is_err());
+    }
+}
+
+/// SortTypeWithPlaylist の OpenAPI の schema のテスト
+#[cfg(feature = "openapi")]
+mod test_schema {
+    use utoipa::PartialSchema;
+
+    use super::*;
+
+    /// serialize した値が、いずれかの schema の形式と一致する
+    #[test]
+    fn test_with_playlist_schema() -> anyhow::Result<()> {
+        let schema = serde_json::to_value(SortTypeWithPlaylist::schema())?;
+        let items = schema["oneOf"].as_array().unwrap();
+
+        assert_eq!(items[0]["enum"], serde_json::json!(["playlist"]));
+
+        // SortType の schema から、文字列の値とランダムの並び順のプロパティ名を集める
+        let mut names = vec![];
+        for item in items[1]["oneOf"].as_array().unwrap() {
+            if let Some(values) = item["enum"].as_array() {
+                names.extend(values.iter().map(|v| v.as_str().unwrap().to_owned()));
+            }
+            if let Some(properties) = item["properties"].as_object() {
+                names.extend(properties.keys().cloned());
+            }
+        }
+
+        for sort_type in [
+            SortType::Artist,
+            SortType::Random { seed: 1 },
+            SortType::WeightedShuffle { seed: 1 },
+        ] {
+            let json = serde_json::to_value(SortTypeWithPlaylist::from(sort_type))?;
+            let name = match &json {
+                serde_json::Value::String(name) => name.clone(),
+                value => value.as_object().unwrap().keys().next().unwrap().clone(),
+            };
+            assert!(names.contains(&name), "{json}");
+        }
+        assert!(!names.contains(&"playlist".to_owned()));
+
+        Ok(())
+    }
+}
+
+/// SortSpec のテスト
+mod test_sort_spec {
+    use super::*;
+    use crate::sort_type::keys_order_query;
+
+    async fn select_ids(pool: &PgPool, spec: &SortSpec) -> anyhow::Result<Vec<i32>> {
+        let order_query = keys_order_query(&spec.sort_keys(None));
+        let sql = format!("SELECT id FROM tracks ORDER BY {order_query}");
+
+        Ok(sqlx::query_scalar(&sql).fetch_all(pool).await?)
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_sort_spec"))]
+    async fn test_album_artist(pool: PgPool) -> Result<()> {
+        let spec = SortSpec::new(vec![SortSpecItem::new(SortField::AlbumArtist, false)]);
+
+        // 同じアルバムアーティストの曲は、曲 ID 順
+        assert_eq!(select_ids(&pool, &spec).await?, vec![2, 4, 3, 1, 5]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_sort_spec"))]
+    async fn test_mixed_direction(pool: PgPool) -> Result<()> {
+        let spec = SortSpec::new(vec![
+            SortSpecItem::new(SortField::ReleaseDate, true),
+            SortSpecItem::new(SortField::Rating, false),
+        ]);
+
+        // リリース日の降順 (NULL が最初)、同じ日付ならレートの昇順、同じレートなら曲 ID の昇順
+        assert_eq!(select_ids(&pool, &spec).await?, vec![2, 1, 5, 3, 4]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_sort_spec"))]
+    async fn test_id_follows_last_direction(pool: PgPool) -> Result<()> {
+        let spec = SortSpec::new(vec![SortSpecItem::new(SortField::Rating, true)]);
+
+        // 同じレートの曲は、最後の項目と同じく曲 ID の降順
+        assert_eq!(select_ids(&pool, &spec).await?, vec![3, 2, 5, 4, 1]);
+
+        // 項目が無ければ曲 ID 順
+        assert_eq!(
+            select_ids(&pool, &SortSpec::default()).await?,
+            vec![1, 2, 3, 4, 5]
+        );
+
+        Ok(())
+    }
+
+    /// SortType を変換した指定は、SortType と同じキーとなる
+    #[test]
+    fn test_preset() {
+        for sort_type in [
+            SortType::TrackName,
+            SortType::Artist,
+            SortType::Genre,
+            SortType::ReleaseDate,
+            SortType::EntryDate,
+            SortType::Random { seed: 1 },
+        ] {
+            let keys = sort_type.spec(true).sort_keys(None);
+            assert_eq!(keys.last().unwrap().expression, "tracks.id");
+            assert!(keys.iter().all(|key| key.desc), "{sort_type:?}");
+            assert_eq!(sort_type.order_query(true), keys_order_query(&keys));
+        }
+
+        assert_eq!(
+            SortType::Artist.order_query(false),
+            "tracks.artist_order ASC, tracks.album_order ASC, \
+            tracks.disc_number IS NULL ASC, COALESCE(tracks.disc_number, 0) ASC, \
+            tracks.track_number IS NULL ASC, COALESCE(tracks.track_number, 0) ASC, \
+            tracks.title_order ASC, tracks.id ASC"
+        );
+    }
+
+    /// プレイリストの曲の検索では、プレイリスト内の位置を加える
+    #[test]
+    fn test_playlist_keys() {
+        let spec = SortSpec::new(vec![SortSpecItem::new(SortField::Title, true)]);
+        assert_eq!(
+            keys_order_query(&spec.sort_keys(Some("playlist_tracks.order_index"))),
+            "tracks.title_order DESC, tracks.id DESC, playlist_tracks.order_index DESC"
+        );
+
+        // プレイリスト順を含む場合は加えない
+        let spec = SortTypeWithPlaylist::Playlist.spec(false);
+        assert_eq!(
+            keys_order_query(&spec.sort_keys(Some("playlist_tracks.order_index"))),
+            "playlist_tracks.order_index ASC, tracks.id ASC"
+        );
+    }
+
+    #[test]
+    fn test_serde() -> anyhow::Result<()> {
+        let spec = SortSpec::new(vec![
+            SortSpecItem::new(SortField::ReleaseDate, true),
+            SortSpecItem::new(SortField::WeightedShuffle { seed: 3 }, false),
+        ]);
+
+        let json = serde_json::to_value(&spec)?;
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "field": "release_date", "desc": true },
+                { "field": "weighted_shuffle", "seed": 3, "desc": false },
+            ])
+        );
+        assert_eq!(serde_json::from_value::<SortSpec>(json)?, spec);
+
+        // desc は省略可能
+        let spec: SortSpec = serde_json::from_str(r#"[{"field": "album_artist"}]"#)?;
+        assert_eq!(
+            spec,
+            SortSpec::new(vec![SortSpecItem::new(SortField::AlbumArtist, false)])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursor_name() {
+        let spec = SortSpec::new(vec![
+            SortSpecItem::new(SortField::ReleaseDate, true),
+            SortSpecItem::new(SortField::Random { seed: 3 }, false),
+        ]);
+        assert_eq!(spec.cursor_name(), "release_date desc,random:3");
+
+        assert_ne!(
+            SortType::Artist.spec(false).cursor_name(),
+            SortType::Artist.spec(true).cursor_name()
+        );
+    }
+}
@@ -1,13 +1,8 @@
-use std::{error::Error, str::FromStr};
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
-use sqlx::{
-    Postgres,
-    encode::IsNull,
-    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
-};
 
-use crate::sort_type::{SortType, UnknownSortType};
+use crate::sort_type::{SortField, SortSpec, SortSpecItem, SortType, UnknownSortType};
 
 /// 曲のソートの種類 (プレイリスト順付き)
 ///
@@ -37,32 +32,58 @@ impl SortTypeWithPlaylist {
                 SortType::Rating => "rating",
                 SortType::EntryDate => "entry_date",
                 SortType::Path => "path",
+                SortType::Random { .. } => "random",
+                SortType::WeightedShuffle { .. } => "weighted_shuffle",
             },
         }
     }
 
+    /// ランダムの並び順の seed (ランダムの並び順でなければ None)
+    pub fn seed(&self) -> Option<i32> {
+        match self {
+            Self::Playlist => None,
+            Self::General(t) => t.seed(),
+        }
+    }
+
+    /// この並び順と同じ並べ替えの指定を作成
+    ///
+    /// - desc: 全ての項目を降順とするか
+    pub fn spec(&self, desc: bool) -> SortSpec {
+        match self {
+            Self::Playlist => SortSpec::new(vec![SortSpecItem::new(SortField::Playlist, desc)]),
+            Self::General(t) => t.spec(desc),
+        }
+    }
+
     /// カラムのソート順のクエリを取得
     ///
     /// - is_desc: ソートが降順か
     /// - playlist_track_index_column: `playlist_tracks.order_index` カラムの名前
     ///
-    /// `title_order ASC, tracks.id DESC` の形式の文字列を返す
+    /// `tracks.title_order ASC, tracks.id DESC` の形式の文字列を返す
     pub fn order_query(&self, desc: bool, playlist_track_index_column: &str) -> String {
-        match self {
-            Self::General(t) => t.order_query(desc),
-
-            Self::Playlist => {
-                let dir = super::asc_or_desc_query(desc);
-                format!("{playlist_track_index_column} {dir}")
-            }
-        }
+        super::keys_order_query(&self.spec(desc).sort_keys(Some(playlist_track_index_column)))
     }
 }
 
+/// 名前から変換する
+///
+/// ランダムの並び順は、`random:42` のように seed を `:` の後に指定する。
+/// seed の無い `random` は、常に同じ並びになってしまうためエラーとする。
 impl FromStr for SortTypeWithPlaylist {
     type Err = UnknownSortType;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((name, seed)) = s.split_once(':') {
+            let seed = seed.parse().map_err(|_| UnknownSortType(s.to_string()))?;
+            return match name {
+                "random" => Ok(SortType::Random { seed }.into()),
+                "weighted_shuffle" => Ok(SortType::WeightedShuffle { seed }.into()),
+                _ => Err(UnknownSortType(s.to_string())),
+            };
+        }
+
         match s {
             "playlist" => Ok(Self::Playlist),
 
@@ -78,7 +99,6 @@ impl FromStr for SortTypeWithPlaylist {
             "rating" => Ok(SortType::Rating.into()),
             "entry_date" => Ok(SortType::EntryDate.into()),
             "path" => Ok(SortType::Path.into()),
-
             _ => Err(UnknownSortType(s.to_string())),
         }
     }
@@ -107,58 +127,40 @@ impl<'de> Deserialize<'de> for SortTypeWithPlaylist {
     where
         D: serde::Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        let t = Self::from_str(&s).map_err(serde::de::Error::custom)?;
-        Ok(t)
-    }
-}
-
-impl sqlx::Type<Postgres> for SortTypeWithPlaylist {
-    fn type_info() -> PgTypeInfo {
-        PgTypeInfo::with_name("sort_type_with_playlist")
-    }
-}
-
-impl<'r> sqlx::Decode<'r, Postgres> for SortTypeWithPlaylist {
-    fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
-        let s = <String as sqlx::Decode<Postgres>>::decode(value)?;
-
-        Ok(Self::from_str(&s)?)
-    }
-}
+        /// 文字列か、seed 付きの SortType
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Name(String),
+            General(SortType),
+        }
 
-impl<'q> sqlx::Encode<'q, Postgres> for SortTypeWithPlaylist {
-    fn encode_by_ref(
-        &self,
-        buf: &mut PgArgumentBuffer,
-    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
-        <&str as sqlx::Encode<Postgres>>::encode_by_ref(&self.as_str(), buf)
+        match Repr::deserialize(deserializer)? {
+            Repr::Name(s) => Self::from_str(&s).map_err(serde::de::Error::custom),
+            Repr::General(t) => Ok(t.into()),
+        }
     }
 }
 
 #[cfg(feature = "openapi")]
 impl utoipa::ToSchema for SortTypeWithPlaylist {}
 
+/// プレイリスト順の文字列か、SortType の schema
+///
+/// ランダムの並び順は、`{"random": {"seed": 1}}` の形式となる
 #[cfg(feature = "openapi")]
 impl utoipa::PartialSchema for SortTypeWithPlaylist {
     fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
-        utoipa::openapi::ObjectBuilder::new()
+        use utoipa::openapi::{ObjectBuilder, OneOfBuilder, Type};
+
+        OneOfBuilder::new()
             .description(Some("曲のソートの種類 (プレイリスト順付き)"))
-            .enum_values(Some([
-                "playlist",
-                "track_name",
-                "artist",
-                "album",
-                "genre",
-                "composer",
-                "duration",
-                "track_index",
-                "disc_index",
-                "release_date",
-                "rating",
-                "entry_date",
-                "path",
-            ]))
+            .item(
+                ObjectBuilder::new()
+                    .schema_type(Type::String)
+                    .enum_values(Some(["playlist"])),
+            )
+            .item(<SortType as utoipa::PartialSchema>::schema())
             .into()
     }
 }
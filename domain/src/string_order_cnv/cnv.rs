@@ -71,4 +71,27 @@ mod tests {
     fn text_cnv(input: &str, expect: &str) {
         assert_eq!(&cnv(input), expect);
     }
+
+    /// SQL の order_cnv() 関数が、cnv と同じ変換を行うか確認
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_sql_order_cnv(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        //全ての変換対象文字と、半角カナの濁音・半濁音の組み合わせ
+        let mut input: String = CHAR_ORDER_MAP.keys().collect();
+        for c in HAN_KANA_MAP.keys() {
+            input.push(*c);
+            input.push('ﾞ');
+            input.push(*c);
+            input.push('ﾟ');
+            input.push(*c);
+        }
+        input.push_str("ﾅﾞﾅ゜ﾞﾅﾞ漢字 123");
+
+        let actual: String = sqlx::query_scalar("SELECT order_cnv($1)")
+            .bind(&input)
+            .fetch_one(&pool)
+            .await?;
+
+        assert_eq!(actual, cnv(&input));
+        Ok(())
+    }
 }
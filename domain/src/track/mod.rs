@@ -6,4 +6,7 @@ pub use track_duration::TrackDuration;
 pub mod track_error;
 pub use track_error::TrackError;
 
+pub mod track_record;
+pub use track_record::TrackRecord;
+
 pub mod track_sqls;
@@ -0,0 +1,101 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::{path::LibraryDirectoryPath, track::TrackDuration};
+
+/// 曲の 1 レコード分のデータ
+///
+/// `FilterTarget::matches` で、DB を使わずにフィルタ条件を判定するために使用する。
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrackRecord {
+    /// 曲 ID
+    pub id: i32,
+
+    /// 曲名
+    pub title: String,
+
+    /// アーティスト
+    pub artist: String,
+
+    /// アルバムアーティスト
+    pub album_artist: String,
+
+    /// アルバム
+    pub album: String,
+
+    /// 作曲者
+    pub composer: String,
+
+    /// ジャンル
+    pub genre: String,
+
+    /// 再生時間
+    pub duration: TrackDuration,
+
+    /// トラック番号
+    pub track_number: Option<i32>,
+
+    /// トラック最大数
+    pub track_max: Option<i32>,
+
+    /// ディスク番号
+    pub disc_number: Option<i32>,
+
+    /// ディスク最大数
+    pub disc_max: Option<i32>,
+
+    /// リリース日
+    pub release_date: Option<NaiveDate>,
+
+    /// レート
+    pub rating: i16,
+
+    /// 原曲
+    pub original_track: String,
+
+    /// サジェスト対象
+    pub suggest_target: bool,
+
+    /// メモ
+    pub memo: String,
+
+    /// 管理メモ
+    pub memo_manage: String,
+
+    /// 曲名の並べ替え用文字列
+    pub title_order: String,
+
+    /// アーティストの並べ替え用文字列
+    pub artist_order: String,
+
+    /// アルバムアーティストの並べ替え用文字列
+    pub album_artist_order: String,
+
+    /// アルバムの並べ替え用文字列
+    pub album_order: String,
+
+    /// 作曲者の並べ替え用文字列
+    pub composer_order: String,
+
+    /// ジャンルの並べ替え用文字列
+    pub genre_order: String,
+
+    /// 登録日時 (`tracks.created_at`)
+    pub created_at: DateTime<Utc>,
+
+    /// 曲に付けられたタグの ID (`track_tags.tag_id`)
+    pub tag_ids: Vec<i32>,
+
+    /// 曲に付けられたタグのグループの ID (`tags.group_id`)
+    pub tag_group_ids: Vec<i32>,
+
+    /// アートワークがあるか (`track_artworks` にレコードがあるか)
+    pub has_artwork: bool,
+
+    /// 曲のフォルダのパス (`folder_paths.path`)
+    ///
+    /// ライブラリ直下の曲の場合は None
+    pub folder_path: Option<LibraryDirectoryPath>,
+
+    /// 曲を含むプレイリストの ID (`playlist_tracks.playlist_id`)
+    pub playlist_ids: Vec<i32>,
+}
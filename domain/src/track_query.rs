@@ -1,9 +1,30 @@
 //! 複雑なクエリを使用して曲を検索する機能
 
+pub mod full_text_query;
+pub use full_text_query::{FullTextQuery, FullTextQueryBuilder};
+
+pub mod general_query;
+pub use general_query::{TrackQuery, TrackQueryBuilder};
+
+pub mod group_query;
+pub use group_query::{TrackGroup, TrackGroupBy, TrackGroupQuery, TrackGroupQueryBuilder};
+
 pub mod playlist_query;
 
+pub mod prepared_query;
+pub use prepared_query::PreparedTrackQuery;
+
+pub mod query_cursor;
+pub use query_cursor::{QueryCursor, QueryPage};
+
 pub mod select_column;
 pub use select_column::SelectColumn;
 
+pub mod summary_query;
+pub use summary_query::{TrackSummary, TrackSummaryQuery, TrackSummaryQueryBuilder};
+
 pub mod track_query_error;
 pub use track_query_error::TrackQueryError;
+
+pub mod track_row;
+pub use track_row::TrackRow;
@@ -0,0 +1,260 @@
+#[cfg(test)]
+mod tests;
+
+use sqlx::{PgTransaction, Postgres, QueryBuilder, Row, postgres::PgRow};
+
+use crate::{
+    db_utils::like_esc,
+    filter::{FilterContext, FilterTimeZone, RootFilter},
+    string_order_cnv,
+    track_query::{SelectColumn, TrackQueryError, TrackRow, playlist_query},
+};
+
+/// 関連度の計算に使う項目と、その重み
+///
+/// 語がどの項目にも含まれなければ、メモか歌詞に含まれるものとして `OTHER_WEIGHT` とする。
+const RANK_FIELDS: [(&str, &str); 4] = [
+    ("order_cnv(tracks.title) || ' ' || tracks.title_order", "8"),
+    (
+        "order_cnv(tracks.artist) || ' ' || tracks.artist_order || ' ' || order_cnv(tracks.album_artist) || ' ' || tracks.album_artist_order",
+        "4",
+    ),
+    ("order_cnv(tracks.album) || ' ' || tracks.album_order", "2"),
+    (
+        "order_cnv(tracks.composer) || ' ' || tracks.composer_order",
+        "1",
+    ),
+];
+
+/// メモ・歌詞にのみ含まれる語の重み
+const OTHER_WEIGHT: &str = "0.5";
+
+/// 曲名・アーティスト・アルバム・作曲者・メモ・歌詞を対象とした全文検索の条件
+///
+/// 検索文字列を空白で区切った全ての語を含む曲を、関連度の高い順に取得する。
+/// 語は `string_order_cnv::cnv` で変換して比較するため、表記ゆれ
+/// (大文字・小文字、カタカナ・ひらがな、半角カナ等) を無視する。
+/// 日本語も検索できるよう、単語単位ではなく部分一致で比較する。
+///
+/// 関連度は、語を含む項目の重み (曲名 > アーティスト > アルバム > 作曲者 > メモ・歌詞) の合計。
+/// 関連度が同じ曲は、曲名の並べ替え用の値の順とする。
+#[derive(Debug, PartialEq, Clone)]
+pub struct FullTextQuery {
+    /// 検索文字列
+    text: String,
+
+    /// 追加の絞り込みのフィルタ
+    filter: Option<RootFilter>,
+
+    /// 取得するカラムの指定
+    columns: Vec<SelectColumn>,
+
+    /// LIMIT (曲レコードの取得件数)
+    limit: Option<u32>,
+
+    /// OFFSET (曲レコードの取得開始位置)
+    offset: Option<u32>,
+
+    /// フィルタの日付の判定に使うタイムゾーン (None ならローカルタイムゾーン)
+    time_zone: Option<FilterTimeZone>,
+}
+
+impl FullTextQuery {
+    /// カラムを指定し、検索文字列を含む曲を関連度の高い順に検索
+    ///
+    /// 各行には、指定したカラムに加えて関連度 (`row_rank` で取得) が含まれる。
+    /// 検索文字列に語が無ければ、空のリストを返す。
+    pub async fn fetch<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<Vec<PgRow>, TrackQueryError> {
+        let terms = self.terms();
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let context = FilterContext::from_time_zone(self.time_zone);
+
+        if let Some(filter) = &self.filter {
+            //不正な正規表現は、クエリの実行前にエラーとする
+            filter.validate_regex()?;
+
+            playlist_query::prepare_referenced_playlists(tx, filter, context).await?;
+        }
+
+        let patterns: Vec<_> = terms
+            .iter()
+            .map(|term| format!("%{}%", like_esc::escape(term)))
+            .collect();
+
+        let column_names: Vec<_> = self
+            .columns
+            .iter()
+            .map(SelectColumn::sql_column_name)
+            .collect();
+
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT ");
+        builder.push(column_names.join(","));
+
+        //語ごとに、語を含む項目の重みの最大値を合計する
+        builder.push(", (");
+        for (i, pattern) in patterns.iter().enumerate() {
+            if i > 0 {
+                builder.push(" + ");
+            }
+            builder.push("GREATEST(");
+            for (field, weight) in RANK_FIELDS {
+                builder
+                    .push(format!("CASE WHEN {field} LIKE "))
+                    .push_bind(pattern.clone())
+                    .push(format!(" ESCAPE '$' THEN {weight} ELSE 0 END, "));
+            }
+            builder.push(OTHER_WEIGHT).push(")");
+        }
+        builder.push(")::REAL AS search_rank FROM tracks");
+
+        // アートワーク ID を取得する場合は、先頭のアートワークだけを取得できるように JOIN する
+        if self.columns.contains(&SelectColumn::ArtworkId) {
+            builder.push(
+                " LEFT JOIN track_artworks ON tracks.id = track_artworks.track_id AND track_artworks.order_index = 0",
+            );
+        }
+
+        //全ての語を含む曲に絞り込む (search_text の pg_trgm インデックスを使用する)
+        builder.push(" WHERE ");
+        for (i, pattern) in patterns.into_iter().enumerate() {
+            if i > 0 {
+                builder.push(" AND ");
+            }
+            builder
+                .push("tracks.search_text LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '$'");
+        }
+
+        if let Some(filter) = self.filter.as_ref().filter(|f| f.has_condition()) {
+            builder.push(" AND (");
+            filter.push_where_expression(&mut builder, &context);
+            builder.push(")");
+        }
+
+        builder.push(" ORDER BY search_rank DESC, tracks.title_order, tracks.id");
+
+        // LIMIT, OFFSET が指定されていれば追加
+        if let Some(limit) = self.limit {
+            builder.push(format!(" LIMIT {limit}"));
+        }
+        if let Some(offset) = self.offset {
+            builder.push(format!(" OFFSET {offset}"));
+        }
+
+        let list = builder.build().fetch_all(&mut **tx).await?;
+
+        Ok(list)
+    }
+
+    /// 検索文字列を含む曲を、指定したカラムを読み取った `TrackRow` として関連度の高い順に取得
+    pub async fn fetch_rows<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<Vec<TrackRow>, TrackQueryError> {
+        let rows = self.fetch(tx).await?;
+
+        let tracks = rows
+            .iter()
+            .map(|row| TrackRow::from_row(row, &self.columns))
+            .collect::<sqlx::Result<Vec<_>>>()?;
+
+        Ok(tracks)
+    }
+
+    /// PgRow から関連度を取得
+    pub fn row_rank(row: &PgRow) -> sqlx::Result<f32> {
+        row.try_get("search_rank")
+    }
+
+    /// 検索文字列を空白で区切り、比較用に変換した語
+    fn terms(&self) -> Vec<String> {
+        let mut terms: Vec<String> = vec![];
+
+        for term in self.text.split_whitespace() {
+            let term = string_order_cnv::cnv(term);
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+
+        terms
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FullTextQueryBuilder {
+    text: String,
+    filter: Option<RootFilter>,
+    columns: Vec<SelectColumn>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    time_zone: Option<FilterTimeZone>,
+}
+
+impl FullTextQueryBuilder {
+    /// 検索文字列を指定して作成
+    ///
+    /// 空白で区切った全ての語を含む曲を検索する
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            filter: None,
+            columns: Vec::default(),
+            limit: None,
+            offset: None,
+            time_zone: None,
+        }
+    }
+
+    /// 追加の絞り込みのフィルタを指定
+    pub fn filter(mut self, filter: RootFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// 取得するカラムを追加
+    ///
+    /// column は一つ以上の指定が必須
+    pub fn column(mut self, column: SelectColumn) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// `LIMIT` を指定 (曲レコードの取得件数)
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// `OFFSET` を指定 (曲レコードの取得開始位置)
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// フィルタの日付の判定に使うタイムゾーンを指定 (`FilterContext::from_time_zone` を参照)
+    pub fn time_zone(mut self, time_zone: impl Into<FilterTimeZone>) -> Self {
+        self.time_zone = Some(time_zone.into());
+        self
+    }
+
+    pub fn build(self) -> FullTextQuery {
+        assert!(!self.columns.is_empty(), "columns cannot be empty");
+
+        FullTextQuery {
+            text: self.text,
+            filter: self.filter,
+            columns: self.columns,
+            limit: self.limit,
+            offset: self.offset,
+            time_zone: self.time_zone,
+        }
+    }
+}
@@ -0,0 +1,10 @@
+-- 全文検索のテスト用データ
+-- 曲 2 は、曲名の並べ替え用の値に読み仮名を設定している
+
+INSERT INTO tracks (id, duration, path, title, title_order, artist, artist_order, album, album_order, composer, composer_order, memo, lyrics, rating) VALUES
+    (1, 180, '/music/01.mp3', 'Tokyo Night', 'tokyo night', 'Blue Band', 'blue band', 'Night Songs', 'night songs', '', '', '', '', 3),
+    (2, 180, '/music/02.mp3', '東京タワー', 'とうきょうたわー', 'カナタ', 'かなた', 'Tokyo', 'tokyo', '', '', '', '', 5),
+    (3, 180, '/music/03.mp3', 'Ocean', 'ocean', 'Tokyo Boys', 'tokyo boys', 'Sea', 'sea', '', '', '', '夜の東京を歩く', 4),
+    (4, 180, '/music/04.mp3', 'Rain', 'rain', 'ｶﾅﾀ', 'かなた', 'Weather', 'weather', 'Tokyo Composer', 'tokyo composer', 'night drive', '', 1),
+    (5, 180, '/music/05.mp3', '100% Love', '100% love', 'Other', 'other', 'Other', 'other', '', '', '', '', 0),
+    (6, 180, '/music/06.mp3', 'Unrelated', 'unrelated', 'Other', 'other', 'Other', 'other', '', '', '', '', 0);
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use sqlx::{PgPool, postgres::PgRow};
+
+use crate::{
+    filter::{FilterTarget, IntFilterRange},
+    track_query::{FullTextQuery, FullTextQueryBuilder, SelectColumn, TrackRow},
+};
+
+fn ids(rows: &[PgRow]) -> Vec<i32> {
+    rows.iter()
+        .map(|row| SelectColumn::row_id(row).unwrap())
+        .collect()
+}
+
+/// 関連度の高い順に取得する
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_full_text_query"))]
+async fn test_fetch(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let cases: [(&str, &[i32]); 13] = [
+        // 曲名 > アーティスト > アルバム > 作曲者 の順
+        ("tokyo", &[1, 3, 2, 4]),
+        ("TOKYO", &[1, 3, 2, 4]),
+        // 曲名と歌詞
+        ("東京", &[2, 3]),
+        // 並べ替え用の値 (読み仮名) でも検索できる
+        ("とうきょう", &[2]),
+        ("トウキョウ", &[2]),
+        // 半角カナのアーティストも含む (曲名の並べ替え用の値の順)
+        ("カナタ", &[4, 2]),
+        // 全ての語を含む曲のみ
+        ("tokyo night", &[1, 4]),
+        ("night\u{3000}tokyo", &[1, 4]),
+        ("かなた night", &[4]),
+        // LIKE の特殊文字はそのまま比較する
+        ("100%", &[5]),
+        ("_", &[]),
+        ("nothing", &[]),
+        ("  ", &[]),
+    ];
+
+    for (text, expected) in cases {
+        let rows = FullTextQueryBuilder::new(text)
+            .column(SelectColumn::Id)
+            .build()
+            .fetch(&mut tx)
+            .await?;
+        assert_eq!(ids(&rows), expected, "text: {text}");
+    }
+
+    Ok(())
+}
+
+/// 関連度は、語を含む項目の重みの合計
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_full_text_query"))]
+async fn test_rank(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let rows = FullTextQueryBuilder::new("tokyo night")
+        .column(SelectColumn::Id)
+        .build()
+        .fetch(&mut tx)
+        .await?;
+
+    let ranks = rows
+        .iter()
+        .map(FullTextQuery::row_rank)
+        .collect::<sqlx::Result<Vec<_>>>()?;
+    // 曲 1: 曲名 + 曲名、曲 4: 作曲者 + メモ
+    assert_eq!(ranks, vec![16.0, 1.5]);
+
+    Ok(())
+}
+
+/// フィルタと LIMIT で絞り込み、TrackRow として取得する
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_full_text_query"))]
+async fn test_fetch_rows_with_filter(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let rows = FullTextQueryBuilder::new("tokyo")
+        .filter(FilterTarget::Rating {
+            range: IntFilterRange::LargeEqual { value: 3 },
+        })
+        .column(SelectColumn::Id)
+        .column(SelectColumn::Title)
+        .limit(2)
+        .build()
+        .fetch_rows(&mut tx)
+        .await?;
+
+    assert_eq!(
+        rows,
+        vec![
+            TrackRow {
+                id: Some(1),
+                title: Some("Tokyo Night".to_owned()),
+                ..Default::default()
+            },
+            TrackRow {
+                id: Some(3),
+                title: Some("Ocean".to_owned()),
+                ..Default::default()
+            },
+        ]
+    );
+
+    Ok(())
+}
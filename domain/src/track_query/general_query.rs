@@ -0,0 +1,272 @@
+#[cfg(test)]
+mod tests;
+
+use sqlx::{PgTransaction, Postgres, QueryBuilder, postgres::PgRow};
+
+use crate::{
+    SortSpec, SortType,
+    filter::{FilterContext, FilterTimeZone, RootFilter},
+    sort_type::keys_order_query,
+    track_query::{
+        PreparedTrackQuery, QueryCursor, QueryPage, SelectColumn, TrackQueryError, TrackRow,
+        playlist_query, query_cursor,
+    },
+};
+
+/// プレイリストを介さない、曲の検索条件
+///
+/// `tracks` テーブルを直接検索し、DB への書き込みは行わない。
+/// フィルタの `InPlaylist` で参照しているプレイリストがリストアップされていなければ、
+/// playlist_tracks を更新せずに、検索時にその内容を評価する。
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrackQuery {
+    /// 絞り込みのフィルタ (None なら全ての曲)
+    filter: Option<RootFilter>,
+
+    /// 並べ替えの指定 (項目が空なら曲 ID 順)
+    sort: SortSpec,
+
+    /// 取得するカラムの指定
+    columns: Vec<SelectColumn>,
+
+    /// LIMIT (曲レコードの取得件数)
+    limit: Option<u32>,
+
+    /// OFFSET (曲レコードの取得開始位置)
+    offset: Option<u32>,
+
+    /// このカーソルより後の曲を取得する
+    cursor: Option<QueryCursor>,
+
+    /// フィルタの日付の判定に使うタイムゾーン (None ならローカルタイムゾーン)
+    time_zone: Option<FilterTimeZone>,
+}
+
+impl TrackQuery {
+    /// カラムを指定し、条件を満たす曲を検索
+    pub async fn fetch<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<Vec<PgRow>, TrackQueryError> {
+        self.prepare(tx).await?.fetch_all(tx).await
+    }
+
+    /// フィルタが参照するプレイリストの曲を評価し、曲を取得する準備をする
+    ///
+    /// 曲は `PreparedTrackQuery::fetch_stream` などで、一件ずつ取得できる。
+    pub async fn prepare<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<PreparedTrackQuery, TrackQueryError> {
+        self.prepare_internal(tx, false).await
+    }
+
+    /// カラムを指定し、条件を満たす曲を、次のページのカーソルと共に取得
+    ///
+    /// `limit` 件取得できた場合のみ、次のページのカーソルを返す。
+    pub async fn fetch_page<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<QueryPage, TrackQueryError> {
+        let rows = self.prepare_internal(tx, true).await?.fetch_all(tx).await?;
+
+        let next_cursor = match (self.limit, rows.last()) {
+            (Some(limit), Some(last)) if rows.len() == limit as usize => {
+                Some(query_cursor::read_cursor(
+                    last,
+                    &self.sort.cursor_name(),
+                    &self.sort.sort_keys(None),
+                )?)
+            }
+            _ => None,
+        };
+
+        Ok(QueryPage { rows, next_cursor })
+    }
+
+    /// 条件を満たす曲を検索する準備をする
+    ///
+    /// - with_cursor_columns: カーソルの作成に使う、並べ替えのキーのカラムも取得するか
+    async fn prepare_internal<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+        with_cursor_columns: bool,
+    ) -> Result<PreparedTrackQuery, TrackQueryError> {
+        //プレイリスト順は、プレイリストの曲の検索でのみ使用できる
+        if self.sort.uses_playlist_order() {
+            return Err(TrackQueryError::PlaylistOrderUnavailable);
+        }
+
+        let context = FilterContext::from_time_zone(self.time_zone);
+
+        let mut builder = QueryBuilder::<Postgres>::new("");
+
+        if let Some(filter) = &self.filter {
+            //不正な正規表現は、クエリの実行前にエラーとする
+            filter.validate_regex()?;
+
+            playlist_query::evaluate_referenced_playlists(tx, filter, context)
+                .await?
+                .push_with_clause(&mut builder);
+        }
+
+        let keys = self.sort.sort_keys(None);
+
+        let column_names: Vec<_> = self
+            .columns
+            .iter()
+            .map(SelectColumn::sql_column_name)
+            .collect();
+
+        builder.push("SELECT ").push(column_names.join(","));
+        if with_cursor_columns {
+            query_cursor::push_cursor_columns(&mut builder, &keys);
+        }
+        builder.push(" FROM tracks");
+
+        // アートワーク ID を取得する場合は、先頭のアートワークだけを取得できるように JOIN する
+        if self.columns.contains(&SelectColumn::ArtworkId) {
+            builder.push(
+                " LEFT JOIN track_artworks ON tracks.id = track_artworks.track_id AND track_artworks.order_index = 0",
+            );
+        }
+
+        let filter = self.filter.as_ref().filter(|f| f.has_condition());
+        if filter.is_some() || self.cursor.is_some() {
+            builder.push(" WHERE ");
+        }
+        if let Some(filter) = filter {
+            builder.push("(");
+            filter.push_where_expression(&mut builder, &context);
+            builder.push(")");
+        }
+        //カーソルが指定されていれば、カーソルより後の曲に絞り込む
+        if let Some(cursor) = &self.cursor {
+            if filter.is_some() {
+                builder.push(" AND ");
+            }
+            query_cursor::push_cursor_condition(
+                &mut builder,
+                cursor,
+                &self.sort.cursor_name(),
+                &keys,
+            )?;
+        }
+
+        builder.push(" ORDER BY ").push(keys_order_query(&keys));
+
+        // LIMIT, OFFSET が指定されていれば追加
+        if let Some(limit) = self.limit {
+            builder.push(format!(" LIMIT {limit}"));
+        }
+        if let Some(offset) = self.offset {
+            builder.push(format!(" OFFSET {offset}"));
+        }
+
+        Ok(PreparedTrackQuery::new(builder, self.columns.clone()))
+    }
+
+    /// 条件を満たす曲を、指定したカラムを読み取った `TrackRow` として取得
+    pub async fn fetch_rows<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<Vec<TrackRow>, TrackQueryError> {
+        let rows = self.fetch(tx).await?;
+
+        let tracks = rows
+            .iter()
+            .map(|row| TrackRow::from_row(row, &self.columns))
+            .collect::<sqlx::Result<Vec<_>>>()?;
+
+        Ok(tracks)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrackQueryBuilder {
+    filter: Option<RootFilter>,
+    sort: SortSpec,
+    columns: Vec<SelectColumn>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    cursor: Option<QueryCursor>,
+    time_zone: Option<FilterTimeZone>,
+}
+
+impl TrackQueryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 絞り込みのフィルタを指定
+    ///
+    /// 指定しなければ、全ての曲を対象とする
+    pub fn filter(mut self, filter: RootFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// 並び順を指定
+    ///
+    /// 指定しなければ、曲 ID 順とする
+    pub fn sort(mut self, sort_type: SortType, desc: bool) -> Self {
+        self.sort = sort_type.spec(desc);
+        self
+    }
+
+    /// 並べ替えの指定を、項目ごとに指定
+    ///
+    /// プレイリスト順の項目を含む場合、検索時に `TrackQueryError::PlaylistOrderUnavailable` となる
+    pub fn sort_spec(mut self, sort_spec: SortSpec) -> Self {
+        self.sort = sort_spec;
+        self
+    }
+
+    /// 取得するカラムを追加
+    ///
+    /// column は一つ以上の指定が必須
+    pub fn column(mut self, column: SelectColumn) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// `LIMIT` を指定 (曲レコードの取得件数)
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// `OFFSET` を指定 (曲レコードの取得開始位置)
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// カーソルを指定し、カーソルより後の曲を取得する
+    ///
+    /// カーソルは `TrackQuery::fetch_page` で、同じ並び順で取得したものを指定する。
+    pub fn after(mut self, cursor: QueryCursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// フィルタの日付の判定に使うタイムゾーンを指定 (`FilterContext::from_time_zone` を参照)
+    pub fn time_zone(mut self, time_zone: impl Into<FilterTimeZone>) -> Self {
+        self.time_zone = Some(time_zone.into());
+        self
+    }
+
+    pub fn build(self) -> TrackQuery {
+        assert!(!self.columns.is_empty(), "columns cannot be empty");
+
+        TrackQuery {
+            filter: self.filter,
+            sort: self.sort,
+            columns: self.columns,
+            limit: self.limit,
+            offset: self.offset,
+            cursor: self.cursor,
+            time_zone: self.time_zone,
+        }
+    }
+}
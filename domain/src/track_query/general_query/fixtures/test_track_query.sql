@@ -0,0 +1,37 @@
+-- TrackQuery のテスト用データ
+
+INSERT INTO tracks (id, duration, path, title, title_order, artist, artist_order, album, album_order, rating, created_at) VALUES
+    (1, 180, '/music/track1.mp3', 'Track C', 'Track C', 'Artist B', 'Artist B', 'Album A', 'Album A', 5, '2023-06-01 10:00:00'),
+    (2, 200, '/music/track2.mp3', 'Track A', 'Track A', 'Artist A', 'Artist A', 'Album B', 'Album B', 3, '2023-06-02 11:00:00'),
+    (3, 220, '/music/track3.mp3', 'Track B', 'Track B', 'Artist C', 'Artist C', 'Album C', 'Album C', 4, '2023-06-03 12:00:00'),
+    (4, 240, '/music/track4.mp3', 'Track D', 'Track D', 'Artist A', 'Artist A', 'Album D', 'Album D', 1, '2023-06-04 13:00:00');
+
+INSERT INTO artworks (id, hash, image, image_mini, mime_type) VALUES
+    (10, '\x00', '\x00', '\x00', 'image/jpeg'),
+    (11, '\x01', '\x01', '\x01', 'image/jpeg');
+
+INSERT INTO track_artworks (track_id, order_index, artwork_id, picture_type, description) VALUES
+    (3, 0, 10, 3, ''),
+    (3, 1, 11, 3, '');
+
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order, filter_json) VALUES
+    -- リストアップ済みの通常プレイリスト
+    (1, 'normal', 'Favorites', '[{"field": "playlist"}]', true, 0, NULL),
+    -- 未リストアップのフィルタプレイリスト
+    (2, 'filter', 'Rating 4+', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, 1,
+        '{"target": "rating", "range": {"op": "large_equal", "value": 4}}');
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    (1, 0, 2),
+    (1, 1, 3),
+    (1, 2, 4),
+
+    -- リストアップ前の古い内容
+    (2, 0, 4);
+
+INSERT INTO search_presets (id, order_index, name, filter_json) VALUES
+    (1, 0, 'artist a', '{"target": "artist", "range": {"op": "equal", "value": "Artist A"}}');
+
+INSERT INTO track_tags (track_id, tag_id) VALUES
+    (1, 5),
+    (1, 2);
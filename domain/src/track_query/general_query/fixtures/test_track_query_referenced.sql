@@ -0,0 +1,38 @@
+-- 他のプレイリストを参照するフィルタでの、TrackQuery のテスト用データ
+
+INSERT INTO tracks (id, duration, path, title, title_order, artist, artist_order, rating, created_at) VALUES
+    (1, 180, '/music/track1.mp3', 'Track A', 'Track A', 'Artist A', 'Artist A', 5, '2023-06-01 10:00:00'),
+    (2, 200, '/music/track2.mp3', 'Track B', 'Track B', 'Artist B', 'Artist B', 3, '2023-06-02 11:00:00'),
+    (3, 220, '/music/track3.mp3', 'Track C', 'Track C', 'Artist C', 'Artist C', 4, '2023-06-03 12:00:00'),
+    (4, 240, '/music/track4.mp3', 'Track D', 'Track D', 'Artist D', 'Artist D', 2, '2023-06-04 13:00:00');
+
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, listuped_date, parent_id, in_folder_order, filter_json) VALUES
+    -- 未リストアップのフォルダプレイリスト
+    (1, 'folder', 'Folder', '[{"field": "artist"}]', false, NULL, NULL, 0, NULL),
+    -- 1 の子の、未リストアップのフィルタプレイリスト
+    (2, 'filter', 'Rating 4+', '[{"field": "artist"}]', false, NULL, 1, 0,
+        '{"target": "rating", "range": {"op": "large_equal", "value": 4}}'),
+    -- 1 の子の通常プレイリスト
+    (3, 'normal', 'Favorites', '[{"field": "playlist"}]', true, NULL, 1, 1, NULL),
+    -- 2 を参照する、未リストアップのフィルタプレイリスト
+    (4, 'filter', 'In Rating 4+', '[{"field": "artist"}]', false, NULL, NULL, 1,
+        '{"target": "in_playlist", "range": {"op": "in", "value": 2}}'),
+    -- 基準日が古い、相対日付の条件を含むフィルタプレイリスト
+    (5, 'filter', 'Recent', '[{"field": "artist"}]', true, '2000-01-01', NULL, 2,
+        '{"target": "entry_date", "range": {"op": "within_days", "value": 100000}}'),
+    -- 5 を参照する、リストアップ済みのフィルタプレイリスト
+    (6, 'filter', 'In Recent', '[{"field": "artist"}]', true, NULL, NULL, 3,
+        '{"target": "in_playlist", "range": {"op": "in", "value": 5}}'),
+    -- 互いに参照
+    (7, 'filter', 'Cycle A', '[{"field": "artist"}]', false, NULL, NULL, 4,
+        '{"target": "in_playlist", "range": {"op": "in", "value": 8}}'),
+    (8, 'filter', 'Cycle B', '[{"field": "artist"}]', false, NULL, NULL, 5,
+        '{"target": "in_playlist", "range": {"op": "not_in", "value": 7}}');
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    (3, 0, 4),
+
+    -- リストアップ前の古い内容
+    (2, 0, 2),
+    (5, 0, 2),
+    (6, 0, 2);
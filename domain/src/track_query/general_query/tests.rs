@@ -0,0 +1,675 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate};
+use futures_util::TryStreamExt;
+use sqlx::{PgPool, PgTransaction, postgres::PgRow};
+use std::str::FromStr;
+
+use crate::{
+    SortField, SortSpec, SortSpecItem, SortType, SortTypeWithPlaylist,
+    filter::{FilterTarget, GroupOperand, IntFilterRange, PlaylistFilterRange, StringFilterRange},
+    path::LibraryTrackPath,
+    playlist::playlist_error::PlaylistError,
+    search_preset::search_preset_sqls,
+    track::TrackDuration,
+    track_query::{QueryCursor, SelectColumn, TrackQueryBuilder, TrackQueryError, TrackRow},
+};
+
+fn ids(rows: &[PgRow]) -> Vec<i32> {
+    rows.iter()
+        .map(|row| SelectColumn::row_id(row).unwrap())
+        .collect()
+}
+
+/// playlist_tracks に保存されている、プレイリストの曲 ID を確認
+async fn assert_stored_tracks(
+    tx: &mut PgTransaction<'_>,
+    playlist_id: i32,
+    expected: &[i32],
+) -> Result<()> {
+    let track_ids = sqlx::query_scalar!(
+        "SELECT track_id FROM playlist_tracks WHERE playlist_id = $1 ORDER BY track_id",
+        playlist_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+    assert_eq!(track_ids, expected);
+
+    Ok(())
+}
+
+/// TrackQuery::fetch() のテスト
+mod test_fetch {
+    use super::*;
+
+    /// 条件を指定しなければ、全ての曲を ID 順に取得
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_all(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new().column(SelectColumn::Id).build();
+        let rows = query.fetch(&mut tx).await?;
+
+        assert_eq!(ids(&rows), vec![1, 2, 3, 4]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_filter_and_sort(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let filter = FilterTarget::Rating {
+            range: IntFilterRange::LargeEqual { value: 3 },
+        };
+
+        let query = TrackQueryBuilder::new()
+            .filter(filter.clone())
+            .sort(SortType::TrackName, false)
+            .column(SelectColumn::Id)
+            .column(SelectColumn::Title)
+            .build();
+        let rows = query.fetch(&mut tx).await?;
+
+        assert_eq!(ids(&rows), vec![2, 3, 1]);
+        assert_eq!(SelectColumn::row_title(&rows[0])?, "Track A");
+
+        let query = TrackQueryBuilder::new()
+            .filter(filter)
+            .sort(SortType::Artist, true)
+            .column(SelectColumn::Id)
+            .build();
+        let rows = query.fetch(&mut tx).await?;
+
+        assert_eq!(ids(&rows), vec![3, 1, 2]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_limit_offset(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .sort(SortType::Artist, false)
+            .column(SelectColumn::Id)
+            .limit(2)
+            .offset(1)
+            .build();
+        let rows = query.fetch(&mut tx).await?;
+
+        assert_eq!(ids(&rows), vec![4, 1]);
+
+        Ok(())
+    }
+
+    /// 先頭のアートワークの ID を取得
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_artwork_id(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .column(SelectColumn::Id)
+            .column(SelectColumn::ArtworkId)
+            .build();
+        let rows = query.fetch(&mut tx).await?;
+
+        let artwork_ids: Vec<Option<i32>> = rows
+            .iter()
+            .map(|row| SelectColumn::row_artwork_id(row).unwrap())
+            .collect();
+        assert_eq!(artwork_ids, vec![None, None, Some(10), None]);
+
+        Ok(())
+    }
+
+    /// 空のグループのフィルタは、全ての曲を対象とする
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_empty_filter(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .filter(FilterTarget::FilterGroup {
+                op: GroupOperand::And,
+                children: vec![],
+            })
+            .column(SelectColumn::Id)
+            .build();
+        let rows = query.fetch(&mut tx).await?;
+
+        assert_eq!(ids(&rows), vec![1, 2, 3, 4]);
+
+        Ok(())
+    }
+
+    /// 参照しているフィルタプレイリストは、リストアップせずに評価して検索する
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_in_playlist(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .filter(FilterTarget::FilterGroup {
+                op: GroupOperand::And,
+                children: vec![
+                    FilterTarget::InPlaylist {
+                        range: PlaylistFilterRange::In { value: 1 },
+                    },
+                    FilterTarget::InPlaylist {
+                        range: PlaylistFilterRange::NotIn { value: 2 },
+                    },
+                ],
+            })
+            .column(SelectColumn::Id)
+            .build();
+        let rows = query.fetch(&mut tx).await?;
+
+        assert_eq!(ids(&rows), vec![2, 4]);
+
+        let listuped = sqlx::query_scalar!("SELECT listuped_flag FROM playlists WHERE id = 2")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert!(!listuped);
+
+        // playlist_tracks は、リストアップ前の古い内容のまま
+        assert_stored_tracks(&mut tx, 2, &[4]).await
+    }
+
+    /// プレイリストを参照しなければ、プレイリストを更新しない
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_without_writing(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .filter(FilterTarget::Rating {
+                range: IntFilterRange::LargeEqual { value: 4 },
+            })
+            .column(SelectColumn::Id)
+            .build();
+        query.fetch(&mut tx).await?;
+
+        let listuped = sqlx::query_scalar!("SELECT listuped_flag FROM playlists WHERE id = 2")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert!(!listuped);
+
+        Ok(())
+    }
+
+    /// 検索プリセットのフィルタで検索
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_search_preset(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let filter = search_preset_sqls::select_filter_by_name(&mut tx, "artist a")
+            .await?
+            .unwrap();
+        assert_eq!(
+            filter,
+            FilterTarget::Artist {
+                range: StringFilterRange::Equal {
+                    value: "Artist A".to_owned(),
+                    normalize: false,
+                },
+            }
+        );
+
+        let query = TrackQueryBuilder::new()
+            .filter(filter)
+            .column(SelectColumn::Id)
+            .build();
+        let rows = query.fetch(&mut tx).await?;
+
+        assert_eq!(ids(&rows), vec![2, 4]);
+
+        assert_eq!(
+            search_preset_sqls::select_filter_by_name(&mut tx, "nothing").await?,
+            None
+        );
+
+        Ok(())
+    }
+}
+
+/// フィルタが他のプレイリストを参照する場合の、TrackQuery のテスト
+mod test_fetch_referenced {
+    use super::*;
+
+    async fn fetch_in_playlist(pool: &PgPool, playlist_id: i32) -> Result<Vec<i32>> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .filter(FilterTarget::InPlaylist {
+                range: PlaylistFilterRange::In { value: playlist_id },
+            })
+            .column(SelectColumn::Id)
+            .build();
+        let rows = query.fetch(&mut tx).await?;
+
+        Ok(ids(&rows))
+    }
+
+    /// フォルダプレイリストは、未リストアップの子も評価した曲で検索する
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_referenced"))]
+    async fn test_folder(pool: PgPool) -> Result<()> {
+        assert_eq!(fetch_in_playlist(&pool, 1).await?, vec![1, 3, 4]);
+
+        let mut tx = pool.begin().await?;
+        assert_stored_tracks(&mut tx, 1, &[]).await?;
+        assert_stored_tracks(&mut tx, 2, &[2]).await
+    }
+
+    /// 参照先のフィルタプレイリストが、さらに参照しているプレイリストも評価する
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_referenced"))]
+    async fn test_nested_filter(pool: PgPool) -> Result<()> {
+        assert_eq!(fetch_in_playlist(&pool, 4).await?, vec![1, 3]);
+
+        let mut tx = pool.begin().await?;
+        assert_stored_tracks(&mut tx, 4, &[]).await
+    }
+
+    /// 相対日付の基準日が変わったプレイリストと、それに依存するプレイリストは、保存された曲を使わない
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_referenced"))]
+    async fn test_stale_relative_date(pool: PgPool) -> Result<()> {
+        assert_eq!(fetch_in_playlist(&pool, 5).await?, vec![1, 2, 3, 4]);
+        assert_eq!(fetch_in_playlist(&pool, 6).await?, vec![1, 2, 3, 4]);
+
+        let mut tx = pool.begin().await?;
+        assert_stored_tracks(&mut tx, 5, &[2]).await?;
+        assert_stored_tracks(&mut tx, 6, &[2]).await?;
+
+        let listuped_date = sqlx::query_scalar!("SELECT listuped_date FROM playlists WHERE id = 5")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert_eq!(listuped_date, NaiveDate::from_ymd_opt(2000, 1, 1));
+
+        Ok(())
+    }
+
+    /// 参照が循環していればエラー
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_referenced"))]
+    async fn test_cycle(pool: PgPool) -> Result<()> {
+        let result = fetch_in_playlist(&pool, 7).await;
+
+        match result.map_err(|e| e.downcast::<TrackQueryError>()) {
+            Err(Ok(TrackQueryError::Playlist(PlaylistError::PlaylistCycleDetected(ids)))) => {
+                assert_eq!(ids, vec![7, 8, 7]);
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+
+        Ok(())
+    }
+}
+
+/// TrackQuery::fetch_rows() のテスト
+mod test_fetch_rows {
+    use super::*;
+
+    /// 指定したカラムのみ値が入る
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_rows(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .filter(FilterTarget::Rating {
+                range: IntFilterRange::LargeEqual { value: 5 },
+            })
+            .column(SelectColumn::Id)
+            .column(SelectColumn::Path)
+            .column(SelectColumn::Artist)
+            .column(SelectColumn::Duration)
+            .column(SelectColumn::Rating)
+            .column(SelectColumn::TrackNumber)
+            .column(SelectColumn::ReleaseDate)
+            .column(SelectColumn::CreatedAt)
+            .column(SelectColumn::ArtworkId)
+            .column(SelectColumn::TagIds)
+            .build();
+        let rows = query.fetch_rows(&mut tx).await?;
+
+        assert_eq!(
+            rows,
+            vec![TrackRow {
+                id: Some(1),
+                path: Some(LibraryTrackPath::from_str("/music/track1.mp3")?),
+                artist: Some("Artist B".to_owned()),
+                duration: Some(TrackDuration::from_i32_millis(180)),
+                rating: Some(5),
+                track_number: Some(None),
+                release_date: Some(None),
+                created_at: Some(DateTime::parse_from_rfc3339("2023-06-01T10:00:00Z")?.to_utc()),
+                artwork_id: Some(None),
+                tag_ids: Some(vec![2, 5]),
+                ..Default::default()
+            }]
+        );
+
+        Ok(())
+    }
+
+    /// 全てのカラムを取得できる
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_rows_all_columns(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let columns = [
+            SelectColumn::Id,
+            SelectColumn::Path,
+            SelectColumn::Title,
+            SelectColumn::Artist,
+            SelectColumn::AlbumArtist,
+            SelectColumn::Album,
+            SelectColumn::Composer,
+            SelectColumn::Genre,
+            SelectColumn::Duration,
+            SelectColumn::TrackNumber,
+            SelectColumn::TrackMax,
+            SelectColumn::DiscNumber,
+            SelectColumn::DiscMax,
+            SelectColumn::ReleaseDate,
+            SelectColumn::Rating,
+            SelectColumn::OriginalTrack,
+            SelectColumn::SuggestTarget,
+            SelectColumn::Memo,
+            SelectColumn::MemoManage,
+            SelectColumn::Lyrics,
+            SelectColumn::TitleOrder,
+            SelectColumn::ArtistOrder,
+            SelectColumn::AlbumArtistOrder,
+            SelectColumn::AlbumOrder,
+            SelectColumn::ComposerOrder,
+            SelectColumn::GenreOrder,
+            SelectColumn::FolderId,
+            SelectColumn::CreatedAt,
+            SelectColumn::UpdatedAt,
+            SelectColumn::ArtworkId,
+            SelectColumn::TagIds,
+        ];
+        let query = columns
+            .iter()
+            .fold(TrackQueryBuilder::new(), |builder, column| {
+                builder.column(*column)
+            })
+            // アートワークのテーブルと共通のカラム名があっても、並べ替えられる
+            .sort(SortType::EntryDate, true)
+            .build();
+        let rows = query.fetch_rows(&mut tx).await?;
+
+        assert_eq!(rows.len(), 4);
+
+        let row = &rows[1];
+        assert_eq!(row.id, Some(3));
+        assert_eq!(row.title.as_deref(), Some("Track B"));
+        assert_eq!(row.album.as_deref(), Some("Album C"));
+        assert_eq!(row.album_artist.as_deref(), Some(""));
+        assert_eq!(row.release_date, Some(None::<NaiveDate>));
+        assert_eq!(row.suggest_target, Some(true));
+        assert_eq!(row.title_order.as_deref(), Some("Track B"));
+        assert_eq!(row.folder_id, Some(None));
+        assert_eq!(row.artwork_id, Some(Some(10)));
+        assert_eq!(row.tag_ids, Some(vec![]));
+        assert!(row.updated_at.is_some());
+
+        Ok(())
+    }
+}
+
+/// TrackQuery::prepare() で作成した Stream のテスト
+mod test_fetch_stream {
+    use super::*;
+
+    /// Stream で取得した曲が、fetch() と同じ順に並ぶ
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_stream(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .sort(SortType::Artist, true)
+            .column(SelectColumn::Id)
+            .build();
+
+        let expected = ids(&query.fetch(&mut tx).await?);
+
+        let prepared = query.prepare(&mut tx).await?;
+        let rows: Vec<PgRow> = prepared.fetch_stream(&mut tx).try_collect().await?;
+        assert_eq!(ids(&rows), expected);
+
+        // 同じ検索を再度実行できる
+        let rows: Vec<PgRow> = prepared.fetch_stream(&mut tx).try_collect().await?;
+        assert_eq!(ids(&rows), expected);
+
+        Ok(())
+    }
+
+    /// 指定したカラムを読み取った TrackRow を順に取得できる
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query"))]
+    async fn test_fetch_row_stream(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = TrackQueryBuilder::new()
+            .filter(FilterTarget::Rating {
+                range: IntFilterRange::LargeEqual { value: 4 },
+            })
+            .column(SelectColumn::Id)
+            .column(SelectColumn::Path)
+            .build();
+
+        let prepared = query.prepare(&mut tx).await?;
+        let mut stream = prepared.fetch_row_stream(&mut tx);
+
+        let mut paths = vec![];
+        while let Some(row) = stream.try_next().await? {
+            assert_eq!(row.title, None);
+            paths.push((row.id.unwrap(), row.path.unwrap()));
+        }
+
+        assert_eq!(
+            paths,
+            vec![
+                (1, LibraryTrackPath::from_str("/music/track1.mp3")?),
+                (3, LibraryTrackPath::from_str("/music/track3.mp3")?),
+            ]
+        );
+
+        Ok(())
+    }
+}
+
+/// TrackQuery::fetch_page() のテスト
+mod test_fetch_page {
+    use super::*;
+
+    const ALL_SORT_TYPES: [SortType; 14] = [
+        SortType::TrackName,
+        SortType::Artist,
+        SortType::Album,
+        SortType::Genre,
+        SortType::Composer,
+        SortType::Duration,
+        SortType::TrackIndex,
+        SortType::DiscIndex,
+        SortType::ReleaseDate,
+        SortType::Rating,
+        SortType::EntryDate,
+        SortType::Path,
+        SortType::Random { seed: 1 },
+        SortType::WeightedShuffle { seed: 1 },
+    ];
+
+    /// カーソルで順に取得した結果が、一度に取得した結果と一致する
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_cursor"))]
+    async fn test_pages_match_full_list(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        for sort_type in ALL_SORT_TYPES {
+            for desc in [false, true] {
+                let builder = || {
+                    TrackQueryBuilder::new()
+                        .sort(sort_type, desc)
+                        .column(SelectColumn::Id)
+                };
+
+                let expected = ids(&builder().build().fetch(&mut tx).await?);
+
+                let mut actual = vec![];
+                let mut cursor: Option<QueryCursor> = None;
+                loop {
+                    let mut page_builder = builder().limit(2);
+                    if let Some(cursor) = cursor {
+                        // 文字列を経由しても同じカーソルとして使える
+                        page_builder = page_builder.after(cursor.to_string().parse()?);
+                    }
+
+                    let page = page_builder.build().fetch_page(&mut tx).await?;
+                    actual.extend(ids(&page.rows));
+
+                    match page.next_cursor {
+                        Some(next) => cursor = Some(next),
+                        None => break,
+                    }
+                }
+
+                assert_eq!(actual, expected, "sort: {sort_type:?}, desc: {desc}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 方向が混在する並べ替えの指定でも、カーソルで順に取得した結果が一度に取得した結果と一致する
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_cursor"))]
+    async fn test_pages_match_full_list_mixed_direction(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let spec = SortSpec::new(vec![
+            SortSpecItem::new(SortField::Genre, false),
+            SortSpecItem::new(SortField::ReleaseDate, true),
+            SortSpecItem::new(SortField::DiscNumber, false),
+            SortSpecItem::new(SortField::Title, true),
+        ]);
+        let builder = || {
+            TrackQueryBuilder::new()
+                .sort_spec(spec.clone())
+                .column(SelectColumn::Id)
+        };
+
+        let expected = ids(&builder().build().fetch(&mut tx).await?);
+        assert_eq!(expected.len(), 7);
+
+        let mut actual = vec![];
+        let mut cursor: Option<QueryCursor> = None;
+        loop {
+            let mut page_builder = builder().limit(2);
+            if let Some(cursor) = cursor {
+                page_builder = page_builder.after(cursor);
+            }
+
+            let page = page_builder.build().fetch_page(&mut tx).await?;
+            actual.extend(ids(&page.rows));
+
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    /// プレイリスト以外の検索では、プレイリスト順を指定できない
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_cursor"))]
+    async fn test_playlist_order_unavailable(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let result = TrackQueryBuilder::new()
+            .sort_spec(SortTypeWithPlaylist::Playlist.spec(false))
+            .column(SelectColumn::Id)
+            .build()
+            .fetch(&mut tx)
+            .await;
+        assert!(matches!(
+            result,
+            Err(TrackQueryError::PlaylistOrderUnavailable)
+        ));
+
+        Ok(())
+    }
+
+    /// 並び順を指定しなければ、曲 ID 順のカーソルとなる
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_cursor"))]
+    async fn test_page_with_filter(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let builder = || {
+            TrackQueryBuilder::new()
+                .filter(FilterTarget::Rating {
+                    range: IntFilterRange::LargeEqual { value: 3 },
+                })
+                .column(SelectColumn::Id)
+                .limit(2)
+        };
+
+        let page = builder().build().fetch_page(&mut tx).await?;
+        assert_eq!(ids(&page.rows), vec![1, 2]);
+
+        let page = builder()
+            .after(page.next_cursor.unwrap())
+            .build()
+            .fetch_page(&mut tx)
+            .await?;
+        assert_eq!(ids(&page.rows), vec![3, 5]);
+
+        // 最後のページは、件数が limit に満たないためカーソルが無い
+        let page = builder()
+            .after(page.next_cursor.unwrap())
+            .build()
+            .fetch_page(&mut tx)
+            .await?;
+        assert_eq!(ids(&page.rows), Vec::<i32>::new());
+        assert!(page.next_cursor.is_none());
+
+        Ok(())
+    }
+
+    /// 異なる並び順のカーソルはエラー
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_track_query_cursor"))]
+    async fn test_cursor_mismatch(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let page = TrackQueryBuilder::new()
+            .sort(SortType::Artist, false)
+            .column(SelectColumn::Id)
+            .limit(2)
+            .build()
+            .fetch_page(&mut tx)
+            .await?;
+        let cursor = page.next_cursor.unwrap();
+
+        for (sort_type, desc) in [(SortType::Artist, true), (SortType::Album, false)] {
+            let result = TrackQueryBuilder::new()
+                .sort(sort_type, desc)
+                .column(SelectColumn::Id)
+                .after(cursor.clone())
+                .build()
+                .fetch(&mut tx)
+                .await;
+            assert!(matches!(result, Err(TrackQueryError::CursorMismatch)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        for s in ["", "0", "zz", "7b7d", "あい"] {
+            assert!(
+                matches!(
+                    s.parse::<QueryCursor>(),
+                    Err(TrackQueryError::InvalidCursor)
+                ),
+                "cursor: {s}"
+            );
+        }
+    }
+}
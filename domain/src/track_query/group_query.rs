@@ -0,0 +1,215 @@
+#[cfg(test)]
+mod tests;
+
+use std::time::Duration;
+
+use sqlx::{PgTransaction, Postgres, QueryBuilder, Row, postgres::PgRow};
+
+use crate::{
+    filter::{FilterContext, FilterTimeZone, RootFilter},
+    track::TrackDuration,
+    track_query::{TrackQueryError, playlist_query},
+};
+
+/// 曲をまとめる単位
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TrackGroupBy {
+    /// アルバム (アルバム名とアルバムアーティストの組)
+    Album,
+    Artist,
+    Genre,
+    Composer,
+}
+
+impl TrackGroupBy {
+    /// まとめるキーのカラム
+    fn name_column(&self) -> &'static str {
+        match self {
+            Self::Album => "tracks.album",
+            Self::Artist => "tracks.artist",
+            Self::Genre => "tracks.genre",
+            Self::Composer => "tracks.composer",
+        }
+    }
+
+    /// 並べ替え用のカラム
+    fn order_column(&self) -> &'static str {
+        match self {
+            Self::Album => "tracks.album_order",
+            Self::Artist => "tracks.artist_order",
+            Self::Genre => "tracks.genre_order",
+            Self::Composer => "tracks.composer_order",
+        }
+    }
+}
+
+/// 曲をまとめた単位ごとの集計結果
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TrackGroup {
+    /// まとめたキーの値 (アルバム名、アーティスト名など)
+    pub name: String,
+
+    /// アルバムアーティスト (アルバムでまとめた場合のみ Some)
+    pub album_artist: Option<String>,
+
+    /// 並べ替え用の文字列 (`*_order` カラムのうち最小のもの)
+    pub sort_key: String,
+
+    /// 曲数
+    pub track_count: i64,
+
+    /// 再生時間の合計
+    pub total_duration: TrackDuration,
+
+    /// リリース年の最小値 (リリース日のある曲が無ければ None)
+    pub min_year: Option<i32>,
+
+    /// リリース年の最大値 (リリース日のある曲が無ければ None)
+    pub max_year: Option<i32>,
+
+    /// 代表のアートワークの ID
+    ///
+    /// ディスク番号・トラック番号順で、先頭のアートワークがある最初の曲のもの
+    pub artwork_id: Option<i32>,
+}
+
+impl TrackGroup {
+    fn from_row(row: &PgRow, group_by: TrackGroupBy) -> sqlx::Result<Self> {
+        let total_millis: i64 = row.try_get("total_duration")?;
+
+        Ok(Self {
+            name: row.try_get("name")?,
+            album_artist: match group_by {
+                TrackGroupBy::Album => Some(row.try_get("album_artist")?),
+                _ => None,
+            },
+            sort_key: row.try_get("sort_key")?,
+            track_count: row.try_get("track_count")?,
+            total_duration: Duration::from_millis(total_millis as u64).into(),
+            min_year: row.try_get("min_year")?,
+            max_year: row.try_get("max_year")?,
+            artwork_id: row.try_get("artwork_id")?,
+        })
+    }
+}
+
+/// 曲をアルバムやアーティストなどでまとめて集計する検索条件
+///
+/// 結果は並べ替え用の文字列の順に並ぶ。
+/// キーの値が空文字列の曲も、空文字列の一つの単位としてまとめる。
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrackGroupQuery {
+    /// 曲をまとめる単位
+    group_by: TrackGroupBy,
+
+    /// 集計対象を絞り込むフィルタ (None なら全ての曲)
+    filter: Option<RootFilter>,
+
+    /// フィルタの日付の判定に使うタイムゾーン (None ならローカルタイムゾーン)
+    time_zone: Option<FilterTimeZone>,
+}
+
+impl TrackGroupQuery {
+    /// 曲をまとめて集計した結果を取得
+    pub async fn fetch<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<Vec<TrackGroup>, TrackQueryError> {
+        let context = FilterContext::from_time_zone(self.time_zone);
+
+        if let Some(filter) = &self.filter {
+            //不正な正規表現は、クエリの実行前にエラーとする
+            filter.validate_regex()?;
+
+            playlist_query::prepare_referenced_playlists(tx, filter, context).await?;
+        }
+
+        let name_column = self.group_by.name_column();
+
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT ");
+        builder.push(format!("{name_column} AS name, "));
+        if self.group_by == TrackGroupBy::Album {
+            builder.push("tracks.album_artist AS album_artist, ");
+        }
+        builder.push(format!(
+            "MIN({}) AS sort_key, ",
+            self.group_by.order_column()
+        ));
+        builder.push(
+            "COUNT(*) AS track_count, \
+            COALESCE(SUM(tracks.duration), 0)::BIGINT AS total_duration, \
+            MIN(EXTRACT(YEAR FROM tracks.release_date))::INTEGER AS min_year, \
+            MAX(EXTRACT(YEAR FROM tracks.release_date))::INTEGER AS max_year, \
+            (ARRAY_AGG(track_artworks.artwork_id ORDER BY tracks.disc_number, tracks.track_number, tracks.id) \
+                FILTER (WHERE track_artworks.artwork_id IS NOT NULL))[1] AS artwork_id",
+        );
+
+        // 先頭のアートワークは曲ごとに一つなので、JOIN しても曲数は変わらない
+        builder.push(
+            " FROM tracks LEFT JOIN track_artworks ON tracks.id = track_artworks.track_id AND track_artworks.order_index = 0",
+        );
+
+        if let Some(filter) = self.filter.as_ref().filter(|f| f.has_condition()) {
+            builder.push(" WHERE ");
+            filter.push_where_expression(&mut builder, &context);
+        }
+
+        builder.push(format!(" GROUP BY {name_column}"));
+        if self.group_by == TrackGroupBy::Album {
+            builder.push(", tracks.album_artist");
+        }
+
+        builder.push(format!(" ORDER BY sort_key, {name_column}"));
+        if self.group_by == TrackGroupBy::Album {
+            builder.push(", MIN(tracks.album_artist_order), tracks.album_artist");
+        }
+
+        let rows = builder.build().fetch_all(&mut **tx).await?;
+
+        let groups = rows
+            .iter()
+            .map(|row| TrackGroup::from_row(row, self.group_by))
+            .collect::<sqlx::Result<Vec<_>>>()?;
+
+        Ok(groups)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackGroupQueryBuilder {
+    group_by: TrackGroupBy,
+    filter: Option<RootFilter>,
+    time_zone: Option<FilterTimeZone>,
+}
+
+impl TrackGroupQueryBuilder {
+    pub fn new(group_by: TrackGroupBy) -> Self {
+        Self {
+            group_by,
+            filter: None,
+            time_zone: None,
+        }
+    }
+
+    /// 集計対象を絞り込むフィルタを指定
+    ///
+    /// 指定しなければ、全ての曲を対象とする
+    pub fn filter(mut self, filter: RootFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// フィルタの日付の判定に使うタイムゾーンを指定 (`FilterContext::from_time_zone` を参照)
+    pub fn time_zone(mut self, time_zone: impl Into<FilterTimeZone>) -> Self {
+        self.time_zone = Some(time_zone.into());
+        self
+    }
+
+    pub fn build(self) -> TrackGroupQuery {
+        TrackGroupQuery {
+            group_by: self.group_by,
+            filter: self.filter,
+            time_zone: self.time_zone,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+-- 曲をまとめて集計するテスト用データ
+-- アルバム X は、アルバムアーティストの異なる 2 つのアルバムがある
+
+INSERT INTO tracks (id, duration, path, title, title_order, artist, artist_order, album_artist, album_artist_order, album, album_order, genre, genre_order, composer, composer_order, track_number, disc_number, release_date, rating, created_at) VALUES
+    (1, 100, '/music/x/02.mp3', 'Track 1', 'track 1', 'Artist A', 'artist a', 'Album Artist A', 'album artist a', 'Album X', 'album x', 'Rock', 'rock', 'Composer C', 'composer c', 2, 1, '2020-01-01', 3, '2023-06-01 10:00:00'),
+    (2, 200, '/music/x/01.mp3', 'Track 2', 'track 2', 'Artist B', 'artist b', 'Album Artist A', 'album artist a', 'Album X', 'album x', 'Rock', 'rock', 'Composer C', 'composer c', 1, 1, '2022-05-01', 5, '2023-06-02 10:00:00'),
+    (3, 300, '/music/x2/01.mp3', 'Track 3', 'track 3', 'Artist A', 'artist a', 'Album Artist B', 'album artist b', 'Album X', 'album x', 'Pop', 'pop', 'Composer D', 'composer d', 1, 1, '2019-03-03', 4, '2023-06-03 10:00:00'),
+    (4, 400, '/music/y/01.mp3', 'Track 4', 'track 4', 'Artist B', 'artist b', 'Album Artist A', 'album artist a', 'Album Y', 'a album y', 'Pop', 'pop', '', '', NULL, NULL, NULL, 5, '2023-06-04 10:00:00'),
+    (5, 500, '/music/single.mp3', 'Track 5', 'track 5', 'Artist B', 'artist b', '', '', '', '', 'Jazz', 'jazz', '', '', NULL, NULL, NULL, 0, '2023-06-05 10:00:00');
+
+-- 曲 2 の先頭のアートワークが、アルバム X (Album Artist A) の代表となる
+INSERT INTO artworks (id, hash, image, image_mini, mime_type) VALUES
+    (20, '\x00', '\x00', '\x00', 'image/jpeg'),
+    (21, '\x01', '\x01', '\x01', 'image/jpeg'),
+    (30, '\x02', '\x02', '\x02', 'image/jpeg'),
+    (31, '\x03', '\x03', '\x03', 'image/jpeg');
+
+INSERT INTO track_artworks (track_id, order_index, artwork_id, picture_type, description) VALUES
+    (1, 1, 31, 3, ''),
+    (2, 0, 20, 3, ''),
+    (2, 1, 21, 3, ''),
+    (3, 0, 30, 3, '');
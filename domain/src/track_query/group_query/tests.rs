@@ -0,0 +1,174 @@
+use anyhow::Result;
+use chrono::{FixedOffset, NaiveDate};
+use sqlx::PgPool;
+
+use crate::{
+    filter::{DateFilterRange, FilterTarget, IntFilterRange},
+    track::TrackDuration,
+    track_query::{TrackGroup, TrackGroupBy, TrackGroupQueryBuilder},
+};
+
+fn group(
+    name: &str,
+    sort_key: &str,
+    track_count: i64,
+    total_millis: i32,
+    years: Option<(i32, i32)>,
+    artwork_id: Option<i32>,
+) -> TrackGroup {
+    TrackGroup {
+        name: name.to_owned(),
+        album_artist: None,
+        sort_key: sort_key.to_owned(),
+        track_count,
+        total_duration: TrackDuration::from_i32_millis(total_millis),
+        min_year: years.map(|(min, _)| min),
+        max_year: years.map(|(_, max)| max),
+        artwork_id,
+    }
+}
+
+fn album(album_artist: &str, group: TrackGroup) -> TrackGroup {
+    TrackGroup {
+        album_artist: Some(album_artist.to_owned()),
+        ..group
+    }
+}
+
+/// アルバム名とアルバムアーティストの組でまとめる
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_group_query"))]
+async fn test_group_by_album(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let groups = TrackGroupQueryBuilder::new(TrackGroupBy::Album)
+        .build()
+        .fetch(&mut tx)
+        .await?;
+
+    assert_eq!(
+        groups,
+        vec![
+            album("", group("", "", 1, 500, None, None)),
+            album(
+                "Album Artist A",
+                group("Album Y", "a album y", 1, 400, None, None)
+            ),
+            album(
+                "Album Artist A",
+                group("Album X", "album x", 2, 300, Some((2020, 2022)), Some(20))
+            ),
+            album(
+                "Album Artist B",
+                group("Album X", "album x", 1, 300, Some((2019, 2019)), Some(30))
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+/// アーティストでまとめる
+///
+/// 代表のアートワークは、ディスク番号・トラック番号順で最初の曲のもの
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_group_query"))]
+async fn test_group_by_artist(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let groups = TrackGroupQueryBuilder::new(TrackGroupBy::Artist)
+        .build()
+        .fetch(&mut tx)
+        .await?;
+
+    assert_eq!(
+        groups,
+        vec![
+            group("Artist A", "artist a", 2, 400, Some((2019, 2020)), Some(30)),
+            group(
+                "Artist B",
+                "artist b",
+                3,
+                1100,
+                Some((2022, 2022)),
+                Some(20)
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+/// フィルタで集計対象を絞り込む
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_group_query"))]
+async fn test_group_by_genre_with_filter(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let groups = TrackGroupQueryBuilder::new(TrackGroupBy::Genre)
+        .filter(FilterTarget::Rating {
+            range: IntFilterRange::LargeEqual { value: 4 },
+        })
+        .build()
+        .fetch(&mut tx)
+        .await?;
+
+    assert_eq!(
+        groups,
+        vec![
+            group("Pop", "pop", 2, 700, Some((2019, 2019)), Some(30)),
+            group("Rock", "rock", 1, 200, Some((2022, 2022)), Some(20)),
+        ]
+    );
+
+    Ok(())
+}
+
+/// アートワークと同名のカラムを持つ条件でも絞り込める
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_group_query"))]
+async fn test_group_by_composer_with_entry_date(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let groups = TrackGroupQueryBuilder::new(TrackGroupBy::Composer)
+        .filter(FilterTarget::EntryDate {
+            range: DateFilterRange::After {
+                value: NaiveDate::from_ymd_opt(2023, 6, 3).unwrap(),
+            },
+        })
+        .time_zone(FixedOffset::east_opt(0).unwrap())
+        .build()
+        .fetch(&mut tx)
+        .await?;
+
+    assert_eq!(
+        groups,
+        vec![
+            group("", "", 2, 900, None, None),
+            group(
+                "Composer D",
+                "composer d",
+                1,
+                300,
+                Some((2019, 2019)),
+                Some(30)
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+/// 該当する曲が無ければ空
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_group_query"))]
+async fn test_no_tracks(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let groups = TrackGroupQueryBuilder::new(TrackGroupBy::Album)
+        .filter(FilterTarget::Rating {
+            range: IntFilterRange::LargeEqual { value: 6 },
+        })
+        .build()
+        .fetch(&mut tx)
+        .await?;
+
+    assert_eq!(groups, vec![]);
+
+    Ok(())
+}
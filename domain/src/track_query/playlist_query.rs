@@ -3,17 +3,20 @@ mod playlist_model;
 #[cfg(test)]
 mod tests;
 
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use async_recursion::async_recursion;
-use sqlx::PgTransaction;
 use sqlx::postgres::PgRow;
+use sqlx::{PgTransaction, Postgres, QueryBuilder};
 
 use crate::{
-    SortTypeWithPlaylist,
-    playlist::{PlaylistType, playlist_error::PlaylistError, playlist_tracks_sqls},
+    SortSpec,
+    filter::{FilterContext, FilterTimeZone, RootFilter},
+    playlist::{PlaylistType, playlist_error::PlaylistError, playlist_sqls, playlist_tracks_sqls},
+    sort_type::keys_order_query,
     track_query::{
-        SelectColumn, TrackQueryError, playlist_query::playlist_model::QueryPlaylistModel,
+        PreparedTrackQuery, QueryCursor, QueryPage, SelectColumn, TrackQueryError, TrackRow,
+        playlist_query::playlist_model::QueryPlaylistModel, query_cursor,
     },
 };
 
@@ -31,70 +34,148 @@ pub struct PlaylistQuery {
 
     /// OFFSET (曲レコードの取得開始位置)
     offset: Option<u32>,
+
+    /// このカーソルより後の曲を取得する
+    cursor: Option<QueryCursor>,
+
+    /// フィルタの日付の判定に使うタイムゾーン (None ならローカルタイムゾーン)
+    time_zone: Option<FilterTimeZone>,
+
+    /// 並び順のランダムの項目に使う seed (None ならプレイリストに保存された seed)
+    random_seed: Option<i32>,
 }
 
+/// プレイリスト内の曲の位置のカラム
+const PLAYLIST_INDEX_COLUMN: &str = "playlist_tracks.order_index";
+
 impl PlaylistQuery {
     /// カラムを指定し、プレイリストに含まれる曲を検索
     pub async fn fetch<'c>(
         &self,
         tx: &mut PgTransaction<'c>,
     ) -> Result<Vec<PgRow>, TrackQueryError> {
-        let plist = QueryPlaylistModel::from_db(tx, self.playlist_id).await?;
+        self.prepare(tx).await?.fetch_all(tx).await
+    }
 
-        //リストアップされていなければ、まず playlist_tracks テーブルを更新する
-        if !plist.listuped_flag {
-            update_playlist_tracks(tx, &plist).await?;
-        }
+    /// プレイリストのリストアップを行い、曲を取得する準備をする
+    ///
+    /// 曲は `PreparedTrackQuery::fetch_stream` などで、一件ずつ取得できる。
+    pub async fn prepare<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<PreparedTrackQuery, TrackQueryError> {
+        let (query, _) = self.prepare_with_keys(tx, false).await?;
+        Ok(query)
+    }
 
-        let mut join_queries = vec!["JOIN tracks ON playlist_tracks.track_id = tracks.id"];
-        // アートワーク ID を取得する場合は、先頭のアートワークだけを取得できるように JOIN する
-        if self.columns.contains(&SelectColumn::ArtworkId) {
-            join_queries.push(
-            "LEFT JOIN track_artworks ON tracks.id = track_artworks.track_id AND track_artworks.order_index = 0"
-        )
-        }
+    /// カラムを指定し、プレイリストに含まれる曲を、次のページのカーソルと共に取得
+    ///
+    /// `limit` 件取得できた場合のみ、次のページのカーソルを返す。
+    /// カーソルはプレイリストの並び順に対して作成されるため、
+    /// 並び順が変わった後に使用すると `TrackQueryError::CursorMismatch` となる。
+    pub async fn fetch_page<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<QueryPage, TrackQueryError> {
+        let (query, sort_spec) = self.prepare_with_keys(tx, true).await?;
+        let rows = query.fetch_all(tx).await?;
+
+        let keys = sort_spec.sort_keys(Some(PLAYLIST_INDEX_COLUMN));
+        let next_cursor = match (self.limit, rows.last()) {
+            (Some(limit), Some(last)) if rows.len() == limit as usize => Some(
+                query_cursor::read_cursor(last, &sort_spec.cursor_name(), &keys)?,
+            ),
+            _ => None,
+        };
+
+        Ok(QueryPage { rows, next_cursor })
+    }
+
+    /// プレイリストに含まれる曲を検索する準備をし、プレイリストの並べ替えの指定と共に取得
+    ///
+    /// - with_cursor_columns: カーソルの作成に使う、並べ替えのキーのカラムも取得するか
+    async fn prepare_with_keys<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+        with_cursor_columns: bool,
+    ) -> Result<(PreparedTrackQuery, SortSpec), TrackQueryError> {
+        let context = FilterContext::from_time_zone(self.time_zone);
+        let plist = listup_playlist(tx, self.playlist_id, context).await?;
+
+        let sort_spec = match self.random_seed {
+            Some(seed) => plist.sort_spec.with_random_seed(seed),
+            None => plist.sort_spec,
+        };
+        let keys = sort_spec.sort_keys(Some(PLAYLIST_INDEX_COLUMN));
 
         let mut column_names: Vec<_> = self
             .columns
             .iter()
             .map(SelectColumn::sql_column_name)
             .collect();
-        //プレイリスト順なら、取得カラムを一つ追加
-        if plist.sort_type == SortTypeWithPlaylist::Playlist {
-            column_names.push("playlist_tracks.order_index");
+        //プレイリスト順を含むなら、取得カラムを一つ追加
+        if sort_spec.uses_playlist_order() {
+            column_names.push(PLAYLIST_INDEX_COLUMN);
+        }
+
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT ");
+        builder.push(column_names.join(","));
+        if with_cursor_columns {
+            query_cursor::push_cursor_columns(&mut builder, &keys);
+        }
+
+        builder.push(" FROM playlist_tracks JOIN tracks ON playlist_tracks.track_id = tracks.id");
+        // アートワーク ID を取得する場合は、先頭のアートワークだけを取得できるように JOIN する
+        if self.columns.contains(&SelectColumn::ArtworkId) {
+            builder.push(
+                " LEFT JOIN track_artworks ON tracks.id = track_artworks.track_id AND track_artworks.order_index = 0",
+            );
+        }
+
+        builder
+            .push(" WHERE playlist_tracks.playlist_id = ")
+            .push_bind(plist.id);
+
+        //カーソルが指定されていれば、カーソルより後の曲に絞り込む
+        if let Some(cursor) = &self.cursor {
+            builder.push(" AND ");
+            query_cursor::push_cursor_condition(
+                &mut builder,
+                cursor,
+                &sort_spec.cursor_name(),
+                &keys,
+            )?;
         }
 
-        let order_query = plist
-            .sort_type
-            .order_query(plist.sort_desc, "playlist_tracks.order_index");
+        builder.push(" ORDER BY ").push(keys_order_query(&keys));
 
         // LIMIT, OFFSET が指定されていれば追加
-        let limit_query = self
-            .limit
-            .map(|limit| format!("LIMIT {limit}"))
-            .unwrap_or_default();
-        let offset_query = self
-            .offset
-            .map(|offset| format!("OFFSET {offset}"))
-            .unwrap_or_default();
-
-        let sql = format!(
-            "
-            SELECT {}
-            FROM playlist_tracks
-            {}
-            WHERE playlist_tracks.playlist_id = $1
-            ORDER BY {order_query}
-            {limit_query} {offset_query}",
-            column_names.join(","),
-            join_queries.join("\n")
-        );
-        let list: Vec<_> = sqlx::query(&sql)
-            .bind(plist.id)
-            .fetch_all(&mut **tx)
-            .await?;
+        if let Some(limit) = self.limit {
+            builder.push(format!(" LIMIT {limit}"));
+        }
+        if let Some(offset) = self.offset {
+            builder.push(format!(" OFFSET {offset}"));
+        }
+
+        Ok((
+            PreparedTrackQuery::new(builder, self.columns.clone()),
+            sort_spec,
+        ))
+    }
+
+    /// プレイリストの曲を、指定したカラムを読み取った `TrackRow` として取得
+    pub async fn fetch_rows<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<Vec<TrackRow>, TrackQueryError> {
+        let rows = self.fetch(tx).await?;
+
+        let tracks = rows
+            .iter()
+            .map(|row| TrackRow::from_row(row, &self.columns))
+            .collect::<sqlx::Result<Vec<_>>>()?;
 
-        Ok(list)
+        Ok(tracks)
     }
 }
 
@@ -104,6 +185,9 @@ pub struct PlaylistQueryBuilder {
     columns: Vec<SelectColumn>,
     limit: Option<u32>,
     offset: Option<u32>,
+    cursor: Option<QueryCursor>,
+    time_zone: Option<FilterTimeZone>,
+    random_seed: Option<i32>,
 }
 
 impl PlaylistQueryBuilder {
@@ -113,6 +197,9 @@ impl PlaylistQueryBuilder {
             columns: Vec::default(),
             limit: None,
             offset: None,
+            cursor: None,
+            time_zone: None,
+            random_seed: None,
         }
     }
 
@@ -136,6 +223,29 @@ impl PlaylistQueryBuilder {
         self
     }
 
+    /// カーソルを指定し、カーソルより後の曲を取得する
+    ///
+    /// カーソルは `PlaylistQuery::fetch_page` で取得したものを指定する。
+    /// `offset` と異なり、後ろのページでも検索が遅くならない。
+    pub fn after(mut self, cursor: QueryCursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// フィルタの日付の判定に使うタイムゾーンを指定 (`FilterContext::from_time_zone` を参照)
+    pub fn time_zone(mut self, time_zone: impl Into<FilterTimeZone>) -> Self {
+        self.time_zone = Some(time_zone.into());
+        self
+    }
+
+    /// 並び順のランダムの項目に使う seed を、プレイリストに保存されたものの代わりに指定
+    ///
+    /// 取得するたびに異なる順序で曲を並べる場合に使用する。
+    pub fn random_seed(mut self, seed: i32) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
     pub fn build(self) -> PlaylistQuery {
         assert!(!self.columns.is_empty(), "columns cannot be empty");
 
@@ -144,17 +254,109 @@ impl PlaylistQueryBuilder {
             columns: self.columns,
             limit: self.limit,
             offset: self.offset,
+            cursor: self.cursor,
+            time_zone: self.time_zone,
+            random_seed: self.random_seed,
         }
     }
 }
 
+/// リストアップ処理の状態
+struct ListupState {
+    /// リストアップ中のプレイリストの ID (参照の循環の検出用)
+    visiting: Vec<i32>,
+
+    /// フィルタの条件の判定に使う情報
+    context: FilterContext,
+}
+
+/// 相対日付の条件を含むフィルタプレイリストのうち、基準日が変わったものを再度リストアップする
+///
+/// 内容が変わったプレイリストがあれば、それに依存するプレイリストも更新されるよう、
+/// 依存するプレイリストのリストアップ済みフラグを解除する。
+async fn refresh_relative_date_playlists<'c>(
+    tx: &mut PgTransaction<'c>,
+    state: &mut ListupState,
+) -> Result<(), TrackQueryError> {
+    let plists =
+        QueryPlaylistModel::from_db_stale_relative_date_filters(tx, state.context.today).await?;
+
+    let mut changed_ids = vec![];
+    for plist in plists {
+        if listup(tx, &plist, state).await? {
+            changed_ids.push(plist.id);
+        }
+    }
+
+    playlist_sqls::reset_dependent_listuped_flag(tx, &changed_ids).await?;
+
+    Ok(())
+}
+
+/// リストアップされていなければ、プレイリストの曲をリストアップする
+///
+/// フォルダプレイリストの子や、フィルタの `InPlaylist` で参照するプレイリストを
+/// 先にリストアップするため、再帰的に呼び出される。
+/// # Arguments
+/// - plist: 対象プレイリスト情報
+async fn listup_if_needed<'c>(
+    tx: &mut PgTransaction<'c>,
+    plist: &QueryPlaylistModel,
+    state: &mut ListupState,
+) -> Result<(), TrackQueryError> {
+    //リストアップ中のプレイリストを参照していたら、循環している
+    check_cycle(plist, &state.visiting)?;
+
+    if !plist.listuped_flag {
+        listup(tx, plist, state).await?;
+    }
+
+    Ok(())
+}
+
+/// プレイリストの曲をリストアップする
+/// # Returns
+/// 曲リストに変更があったか
+async fn listup<'c>(
+    tx: &mut PgTransaction<'c>,
+    plist: &QueryPlaylistModel,
+    state: &mut ListupState,
+) -> Result<bool, TrackQueryError> {
+    check_cycle(plist, &state.visiting)?;
+
+    state.visiting.push(plist.id);
+    let changed = update_playlist_tracks(tx, plist, state).await?;
+    state.visiting.pop();
+
+    Ok(changed)
+}
+
+/// リストアップ中のプレイリストを参照していないか確認
+///
+/// - visiting: リストアップ中のプレイリストの ID
+fn check_cycle(plist: &QueryPlaylistModel, visiting: &[i32]) -> Result<(), PlaylistError> {
+    if visiting.contains(&plist.id) {
+        let mut cycle = visiting.to_vec();
+        cycle.push(plist.id);
+        return Err(PlaylistError::PlaylistCycleDetected(cycle));
+    }
+    Ok(())
+}
+
 /// プレイリストの曲をリストアップし、playlist_trackテーブルを更新する
 /// # Arguments
 /// - plist: 対象プレイリスト情報
+/// # Returns
+/// 曲リストに変更があったか
+#[async_recursion]
 async fn update_playlist_tracks<'c>(
     tx: &mut PgTransaction<'c>,
     plist: &QueryPlaylistModel,
-) -> Result<(), TrackQueryError> {
+    state: &mut ListupState,
+) -> Result<bool, TrackQueryError> {
+    //古いリストから変更があったか
+    let mut changed = false;
+
     //通常プレイリストなら、リストアップ済みフラグを立てるのみ
     if plist.playlist_type != PlaylistType::Normal {
         //元々保存されていた曲リストを取得
@@ -165,33 +367,24 @@ async fn update_playlist_tracks<'c>(
                 .collect();
 
         let new_id_list = match plist.playlist_type {
-            PlaylistType::Filter => search_plist_tracks_filter(tx, plist).await?,
-            PlaylistType::Folder => search_plist_tracks_folder(tx, plist).await?,
+            PlaylistType::Filter => search_plist_tracks_filter(tx, plist, state).await?,
+            PlaylistType::Folder => search_plist_tracks_folder(tx, plist, state).await?,
             _ => unreachable!(),
         };
 
-        //PlaylistTrackテーブルを更新
-        playlist_tracks_sqls::delete_by_playlist_id(tx, plist.id).await?;
-        for (idx, track_id) in new_id_list.iter().enumerate() {
-            playlist_tracks_sqls::insert_playlist_track(tx, plist.id, *track_id, idx as i32)
-                .await?;
-        }
-
         //古いリストから変更があったか確認
-        let mut changed = false;
-        if old_id_list.len() != new_id_list.len() {
-            changed = true;
-        } else {
-            for id in new_id_list {
-                if !old_id_list.contains(&id) {
-                    changed = true;
-                    break;
-                }
-            }
-        }
+        changed = old_id_list.len() != new_id_list.len()
+            || new_id_list.iter().any(|id| !old_id_list.contains(id));
 
-        //変更があれば、DAP変更フラグを立てる
+        //変更があれば、PlaylistTrackテーブルを更新し、DAP変更フラグを立てる
+        //(変更がなければ、ページ取得の途中で曲の位置が変わらないよう、テーブルは更新しない)
         if changed {
+            playlist_tracks_sqls::delete_by_playlist_id(tx, plist.id).await?;
+            for (idx, track_id) in new_id_list.iter().enumerate() {
+                playlist_tracks_sqls::insert_playlist_track(tx, plist.id, *track_id, idx as i32)
+                    .await?;
+            }
+
             sqlx::query!(
                 "UPDATE playlists SET dap_changed = true WHERE id = $1",
                 plist.id,
@@ -201,37 +394,43 @@ async fn update_playlist_tracks<'c>(
         }
     }
 
+    //相対日付の条件を含むフィルタプレイリストは、基準日を保存する
+    let listuped_date = plist
+        .filter
+        .as_ref()
+        .is_some_and(|filter| filter.has_relative_date())
+        .then_some(state.context.today);
+
     //リストアップ済みに更新
     sqlx::query!(
-        "UPDATE playlists SET listuped_flag = $1 WHERE id = $2",
+        "UPDATE playlists SET listuped_flag = $1, listuped_date = $2 WHERE id = $3",
         true,
+        listuped_date,
         plist.id,
     )
     .execute(&mut **tx)
     .await?;
 
-    Ok(())
+    Ok(changed)
 }
 
 /// プレイリストの設定に基づき、曲リストを取得：フォルダプレイリスト
 /// # Arguments
 /// - plist: 対象プレイリスト情報
-#[async_recursion]
 async fn search_plist_tracks_folder<'c>(
     tx: &mut PgTransaction<'c>,
     plist: &QueryPlaylistModel,
+    state: &mut ListupState,
 ) -> Result<Vec<i32>, TrackQueryError> {
     //直下の子のプレイリストを取得
     let children = QueryPlaylistModel::from_db_by_parent(tx, plist.id).await?;
 
-    //子プレイリストの曲IDを追加していくSet
-    let mut add_track_ids = HashSet::<i32>::new();
+    //子プレイリストの曲IDを追加していくSet (曲 ID 順とする)
+    let mut add_track_ids = BTreeSet::<i32>::new();
 
     for child in children {
         //リストアップされていなければ、まず playlist_tracks テーブルを更新する
-        if !child.listuped_flag {
-            update_playlist_tracks(tx, &child).await?;
-        }
+        listup_if_needed(tx, &child, state).await?;
 
         //子プレイリストの曲リストを取得
         let child_tracks: Vec<i32> = sqlx::query_scalar!(
@@ -257,23 +456,285 @@ async fn search_plist_tracks_folder<'c>(
 }
 
 /// プレイリストの設定に基づき、曲リストを取得：フィルタプレイリスト
+/// # Arguments
+/// - plist: 対象プレイリスト情報
 async fn search_plist_tracks_filter<'c>(
     tx: &mut PgTransaction<'c>,
     plist: &QueryPlaylistModel,
+    state: &mut ListupState,
 ) -> Result<Vec<i32>, TrackQueryError> {
     let filter = plist
         .filter
         .as_ref()
         .ok_or(PlaylistError::FilterPlaylistHasNoFilter { plist_id: plist.id })?;
 
-    let mut query_base = "SELECT tracks.id FROM tracks".to_owned();
+    //不正な正規表現は、クエリの実行前にエラーとする
+    filter.validate_regex()?;
+
+    //フィルタで参照しているプレイリストを、先にリストアップする
+    listup_referenced_playlists(tx, filter, state).await?;
+
+    let mut query = QueryBuilder::new("SELECT tracks.id FROM tracks");
 
     //フィルタから条件を取得して追加
-    if let Some(query_where) = filter.where_expression() {
-        query_base = format!("{query_base} WHERE {query_where}");
+    if filter.has_condition() {
+        query.push(" WHERE ");
+        filter.push_where_expression(&mut query, &state.context);
     }
+    query.push(" ORDER BY tracks.id");
 
-    let list = sqlx::query_scalar(&query_base).fetch_all(&mut **tx).await?;
+    let list = query.build_query_scalar().fetch_all(&mut **tx).await?;
 
     Ok(list)
 }
+
+/// フィルタの `InPlaylist` で参照しているプレイリストを、リストアップされていなければリストアップする
+async fn listup_referenced_playlists<'c>(
+    tx: &mut PgTransaction<'c>,
+    filter: &RootFilter,
+    state: &mut ListupState,
+) -> Result<(), TrackQueryError> {
+    let mut referenced_ids = filter.referenced_playlist_ids();
+    referenced_ids.sort();
+    referenced_ids.dedup();
+    for referenced_id in referenced_ids {
+        let referenced = QueryPlaylistModel::from_db(tx, referenced_id).await?;
+        listup_if_needed(tx, &referenced, state).await?;
+    }
+
+    Ok(())
+}
+
+/// 検索対象のプレイリストを取得し、リストアップされていなければリストアップする
+pub(super) async fn listup_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    context: FilterContext,
+) -> Result<QueryPlaylistModel, TrackQueryError> {
+    let mut state = ListupState {
+        visiting: vec![],
+        context,
+    };
+
+    //相対日付の条件を含むフィルタプレイリストは、日付が変わると内容が変わりうるため先に更新する
+    refresh_relative_date_playlists(tx, &mut state).await?;
+
+    let plist = QueryPlaylistModel::from_db(tx, playlist_id).await?;
+
+    //リストアップされていなければ、まず playlist_tracks テーブルを更新する
+    listup_if_needed(tx, &plist, &mut state).await?;
+
+    Ok(plist)
+}
+
+/// プレイリスト以外の検索で、フィルタが参照しているプレイリストを準備する
+///
+/// 相対日付の条件を含むフィルタプレイリストの更新と、
+/// `InPlaylist` で参照しているプレイリストのリストアップを行う。
+/// プレイリストを参照していないフィルタでは何もしない。
+pub(crate) async fn prepare_referenced_playlists<'c>(
+    tx: &mut PgTransaction<'c>,
+    filter: &RootFilter,
+    context: FilterContext,
+) -> Result<(), TrackQueryError> {
+    if filter.referenced_playlist_ids().is_empty() {
+        return Ok(());
+    }
+
+    let mut state = ListupState {
+        visiting: vec![],
+        context,
+    };
+    refresh_relative_date_playlists(tx, &mut state).await?;
+    listup_referenced_playlists(tx, filter, &mut state).await
+}
+
+/// 書き込みを行わずに評価した、フィルタが参照しているプレイリストの曲
+pub(crate) struct ReferencedPlaylistTracks {
+    /// プレイリストの ID と、含まれる曲の ID
+    tracks: HashMap<i32, Vec<i32>>,
+}
+
+impl ReferencedPlaylistTracks {
+    /// 評価済みのプレイリストの曲から、指定したプレイリストの曲を取り出す
+    fn new(evaluated: &HashMap<i32, Vec<i32>>, playlist_ids: &[i32]) -> Self {
+        let tracks = playlist_ids
+            .iter()
+            .filter_map(|id| Some((*id, evaluated.get(id)?.clone())))
+            .collect();
+        Self { tracks }
+    }
+
+    /// プレイリストを参照していれば、検索クエリの先頭に `WITH` 句を追加する
+    ///
+    /// `WITH` 句の `playlist_tracks` は、そのクエリ内では同名のテーブルより優先されるため、
+    /// フィルタの `InPlaylist` の条件式は、テーブルではなく評価した曲で判定される。
+    pub(crate) fn push_with_clause(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        if self.tracks.is_empty() {
+            return;
+        }
+
+        let (playlist_ids, track_ids): (Vec<i32>, Vec<i32>) = self
+            .tracks
+            .iter()
+            .flat_map(|(playlist_id, track_ids)| {
+                track_ids.iter().map(|track_id| (*playlist_id, *track_id))
+            })
+            .unzip();
+
+        builder
+            .push("WITH playlist_tracks AS (SELECT * FROM unnest(")
+            .push_bind(playlist_ids)
+            .push("::integer[], ")
+            .push_bind(track_ids)
+            .push("::integer[]) AS t(playlist_id, track_id)) ");
+    }
+}
+
+/// 書き込みを行わない評価の状態
+struct EvaluateState {
+    /// 評価中のプレイリストの ID (参照の循環の検出用)
+    visiting: Vec<i32>,
+
+    /// フィルタの条件の判定に使う情報
+    context: FilterContext,
+
+    /// playlist_tracks に保存されている曲リストが、古くなっているプレイリストの ID
+    outdated_ids: HashSet<i32>,
+
+    /// 評価済みのプレイリストの ID と、含まれる曲の ID
+    tracks: HashMap<i32, Vec<i32>>,
+}
+
+/// 書き込みを行わずに、フィルタが `InPlaylist` で参照しているプレイリストの曲を評価する
+///
+/// リストアップ済みで内容が変わっていないプレイリストは、playlist_tracks に保存された曲を使う。
+/// リストアップされていないプレイリストや、相対日付の基準日が変わったフィルタプレイリスト
+/// (と、それに依存するプレイリスト) は、その場で条件を評価する。
+pub(crate) async fn evaluate_referenced_playlists<'c>(
+    tx: &mut PgTransaction<'c>,
+    filter: &RootFilter,
+    context: FilterContext,
+) -> Result<ReferencedPlaylistTracks, TrackQueryError> {
+    let mut referenced_ids = filter.referenced_playlist_ids();
+    if referenced_ids.is_empty() {
+        return Ok(ReferencedPlaylistTracks {
+            tracks: HashMap::new(),
+        });
+    }
+    referenced_ids.sort();
+    referenced_ids.dedup();
+
+    let stale_ids: Vec<i32> =
+        QueryPlaylistModel::from_db_stale_relative_date_filters(tx, context.today)
+            .await?
+            .into_iter()
+            .map(|plist| plist.id)
+            .collect();
+    let mut outdated_ids = playlist_sqls::select_dependent_ids(tx, &stale_ids).await?;
+    outdated_ids.extend(stale_ids);
+
+    let mut state = EvaluateState {
+        visiting: vec![],
+        context,
+        outdated_ids,
+        tracks: HashMap::new(),
+    };
+    for referenced_id in &referenced_ids {
+        evaluate_playlist(tx, *referenced_id, &mut state).await?;
+    }
+
+    Ok(ReferencedPlaylistTracks::new(
+        &state.tracks,
+        &referenced_ids,
+    ))
+}
+
+/// 書き込みを行わずに、プレイリストの曲を評価する
+///
+/// 評価した曲は `state.tracks` に追加する。
+#[async_recursion]
+async fn evaluate_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    playlist_id: i32,
+    state: &mut EvaluateState,
+) -> Result<(), TrackQueryError> {
+    if state.tracks.contains_key(&playlist_id) {
+        return Ok(());
+    }
+
+    let plist = QueryPlaylistModel::from_db(tx, playlist_id).await?;
+
+    //評価中のプレイリストを参照していたら、循環している
+    check_cycle(&plist, &state.visiting)?;
+
+    let track_ids = if plist.playlist_type == PlaylistType::Normal
+        || (plist.listuped_flag && !state.outdated_ids.contains(&plist.id))
+    {
+        playlist_tracks_sqls::select_track_id_by_playlist_id(tx, plist.id).await?
+    } else {
+        state.visiting.push(plist.id);
+        let track_ids = match plist.playlist_type {
+            PlaylistType::Filter => evaluate_filter_playlist(tx, &plist, state).await?,
+            PlaylistType::Folder => evaluate_folder_playlist(tx, &plist, state).await?,
+            PlaylistType::Normal => unreachable!(),
+        };
+        state.visiting.pop();
+        track_ids
+    };
+
+    state.tracks.insert(plist.id, track_ids);
+    Ok(())
+}
+
+/// 書き込みを行わずに、フィルタプレイリストの曲を評価する
+async fn evaluate_filter_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    plist: &QueryPlaylistModel,
+    state: &mut EvaluateState,
+) -> Result<Vec<i32>, TrackQueryError> {
+    let filter = plist
+        .filter
+        .as_ref()
+        .ok_or(PlaylistError::FilterPlaylistHasNoFilter { plist_id: plist.id })?;
+
+    //不正な正規表現は、クエリの実行前にエラーとする
+    filter.validate_regex()?;
+
+    //フィルタで参照しているプレイリストを、先に評価する
+    let mut referenced_ids = filter.referenced_playlist_ids();
+    referenced_ids.sort();
+    referenced_ids.dedup();
+    for referenced_id in &referenced_ids {
+        evaluate_playlist(tx, *referenced_id, state).await?;
+    }
+
+    let mut query = QueryBuilder::new("");
+    ReferencedPlaylistTracks::new(&state.tracks, &referenced_ids).push_with_clause(&mut query);
+    query.push("SELECT tracks.id FROM tracks");
+    if filter.has_condition() {
+        query.push(" WHERE ");
+        filter.push_where_expression(&mut query, &state.context);
+    }
+
+    let list = query.build_query_scalar().fetch_all(&mut **tx).await?;
+
+    Ok(list)
+}
+
+/// 書き込みを行わずに、フォルダプレイリストの曲 (子プレイリストの曲の和集合) を評価する
+async fn evaluate_folder_playlist<'c>(
+    tx: &mut PgTransaction<'c>,
+    plist: &QueryPlaylistModel,
+    state: &mut EvaluateState,
+) -> Result<Vec<i32>, TrackQueryError> {
+    let children = QueryPlaylistModel::from_db_by_parent(tx, plist.id).await?;
+
+    let mut track_ids = BTreeSet::<i32>::new();
+    for child in children {
+        evaluate_playlist(tx, child.id, state).await?;
+        track_ids.extend(&state.tracks[&child.id]);
+    }
+
+    Ok(track_ids.into_iter().collect())
+}
@@ -24,8 +24,8 @@ INSERT INTO tracks (
     (3, 220, '/music/track3.mp3', 'Track C', 'Track C', 'Artist B', 'Artist B', 'Album B', 'Album B', 'Jazz', 'Composer B', 'Composer B', 3, 1, '2023-03-01', 4, '2023-06-03 12:00:00');
 
 -- Artist ソートのプレイリスト
-INSERT INTO playlists (id, playlist_type, name, sort_type, sort_desc, listuped_flag, in_folder_order) VALUES
-    (1, 'normal', 'Test Playlist', 'artist', false, false, 0);
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order) VALUES
+    (1, 'normal', 'Test Playlist', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, 0);
 
 INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
     (1, 0, 1),  -- Artist A
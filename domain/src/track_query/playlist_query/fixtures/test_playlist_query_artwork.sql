@@ -35,8 +35,8 @@ INSERT INTO track_artworks (track_id, artwork_id, order_index, picture_type, des
     (1, 2, 1, 0, 'Back Cover'), -- track1 の2番目のアートワーク（テストでは使われない）
     (3, 2, 0, 0, 'Cover Art');  -- track3 の先頭アートワーク
 
-INSERT INTO playlists (id, playlist_type, name, sort_type, sort_desc, listuped_flag, in_folder_order) VALUES
-    (1, 'normal', 'Test Playlist', 'playlist', false, false, 0);
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order) VALUES
+    (1, 'normal', 'Test Playlist', '[{"field": "playlist"}]', false, 0);
 
 INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
     (1, 0, 1),
@@ -25,9 +25,9 @@ INSERT INTO tracks (
     (3, 220, '/music/track3.mp3', 'Track C', 'Track C', 'Artist C', 'Artist C', 'Album C', 'Album C', 'Jazz', 'Composer C', 'Composer C', 3, 1, '2023-03-01', 4, '2023-06-03 12:00:00'),
     (4, 240, '/music/track4.mp3', 'Track D', 'Track D', 'Artist D', 'Artist D', 'Album D', 'Album D', 'Jazz', 'Composer D', 'Composer D', 4, 1, '2023-04-01', 4, '2023-06-04 13:00:00');
 
-INSERT INTO playlists (id, playlist_type, name, sort_type, sort_desc, listuped_flag, in_folder_order) VALUES
-    (1, 'normal', 'Test Playlist', 'playlist', false, false, 0),
-    (2, 'normal', 'Test Playlist 2', 'playlist', false, false, 1);
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order) VALUES
+    (1, 'normal', 'Test Playlist', '[{"field": "playlist"}]', false, 0),
+    (2, 'normal', 'Test Playlist 2', '[{"field": "playlist"}]', false, 1);
 
 INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
     -- Playlist 1
@@ -0,0 +1,24 @@
+-- カーソルによるページ分割のテスト用データ
+-- 並べ替えのキーの値が同じ曲や、NULL のカラムを含む
+
+INSERT INTO tracks (id, duration, path, title, title_order, artist, artist_order, album, album_order, genre, composer_order, track_number, disc_number, release_date, rating, created_at) VALUES
+    (1, 180, '/music/a/01.mp3', 'Song', 'Song', 'A', 'A', 'X', 'X', 'Rock', 'C', 1, 1, '2020-01-01', 3, '2023-06-01 10:00:00'),
+    (2, 180, '/music/a/02.mp3', 'Song', 'Song', 'A', 'A', 'X', 'X', 'Rock', 'C', 2, 1, NULL, 3, '2023-06-01 10:00:00'),
+    (3, 200, '/music/b/01.mp3', 'Other', 'Other', 'A', 'A', 'Y', 'Y', 'Pop', 'C', NULL, NULL, '2020-01-01', 5, '2023-06-02 10:00:00'),
+    (4, 200, '/music/b/02.mp3', 'Song', 'Song', 'B', 'B', 'Y', 'Y', 'Pop', 'D', NULL, 2, NULL, 0, '2023-06-02 10:00:00'),
+    (5, 220, '/music/c/01.mp3', 'Last', 'Last', 'B', 'B', 'Z', 'Z', 'Jazz', 'D', 1, NULL, '2021-05-05', 5, '2023-06-03 10:00:00'),
+    (6, 220, '/music/c/02.mp3', 'Last', 'Last', 'C', 'C', 'Z', 'Z', 'Jazz', 'E', 3, 1, '2021-05-05', 1, '2023-06-03 10:00:00'),
+    (7, 240, '/music/c/03.mp3', 'Song', 'Song', 'C', 'C', 'Z', 'Z', 'Rock', 'E', 3, 1, NULL, 0, '2023-06-04 10:00:00');
+
+-- 同じ曲を複数含む通常プレイリスト
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order) VALUES
+    (1, 'normal', 'Duplicated', '[{"field": "playlist"}]', true, 0);
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    (1, 0, 5),
+    (1, 1, 2),
+    (1, 2, 7),
+    (1, 3, 2),
+    (1, 4, 1),
+    (1, 5, 5),
+    (1, 6, 4);
@@ -25,8 +25,8 @@ INSERT INTO tracks (
     (3, 220, '/music/track3.mp3', 'Track C', 'Track C', 'Artist C', 'Artist C', 'Album C', 'Album C', 'Jazz', 'Composer C', 'Composer C', 3, 1, '2023-03-01', 4, '2023-06-03 12:00:00');
 
 -- Filter プレイリスト（rating >= 4）
-INSERT INTO playlists (id, playlist_type, name, sort_type, sort_desc, listuped_flag, in_folder_order, filter_json) VALUES
-    (2, 'filter', 'High Rated Songs', 'artist', false, false, 0, '{
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order, filter_json) VALUES
+    (2, 'filter', 'High Rated Songs', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, 0, '{
         "target": "rating",
         "range": {
             "op": "large_equal",
@@ -22,5 +22,5 @@ INSERT INTO tracks (
     (1, 180, '/music/track1.mp3', 'Track A', 'Track A', 'Artist A', 'Artist A', 'Album A', 'Album A', 'Rock', 'Composer A', 'Composer A', 1, 1, '2023-01-01', 5, '2023-06-01 10:00:00');
 
 -- Filter プレイリストだが filter_json が null
-INSERT INTO playlists (id, playlist_type, name, sort_type, sort_desc, listuped_flag, in_folder_order, filter_json) VALUES
-    (2, 'filter', 'Broken Filter Playlist', 'artist', false, false, 0, NULL);
\ No newline at end of file
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order, filter_json) VALUES
+    (2, 'filter', 'Broken Filter Playlist', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, 0, NULL);
\ No newline at end of file
@@ -26,15 +26,15 @@ INSERT INTO tracks (
     (4, 240, '/music/track4.mp3', 'Track D', 'Track D', 'Artist D', 'Artist D', 'Album D', 'Album D', 'Blues', 'Composer D', 'Composer D', 4, 1, '2023-04-01', 2, '2023-06-04 13:00:00'),
     (5, 260, '/music/track5.mp3', 'Track E', 'Track E', 'Artist E', 'Artist E', 'Album E', 'Album E', 'Blues', 'Composer E', 'Composer E', 5, 1, '2023-05-01', 1, '2023-06-05 14:00:00');
 
-INSERT INTO playlists (id, playlist_type, name, sort_type, sort_desc, listuped_flag, parent_id, in_folder_order) VALUES
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, parent_id, in_folder_order) VALUES
     -- 親 Folder プレイリスト
-    (3, 'folder', 'Music Folder', 'artist', false, false, NULL, 0),
+    (3, 'folder', 'Music Folder', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, NULL, 0),
     -- 子プレイリスト 1
-    (4, 'normal', 'Child Playlist 1', 'playlist', false, true, 3, 1),
+    (4, 'normal', 'Child Playlist 1', '[{"field": "playlist"}]', true, 3, 1),
     -- 子プレイリスト 2
-    (5, 'normal', 'Child Playlist 2', 'playlist', false, true, 3, 2),
+    (5, 'normal', 'Child Playlist 2', '[{"field": "playlist"}]', true, 3, 2),
     -- 無関係なプレイリスト
-    (6, 'normal', 'Dummy Playlist', 'playlist', false, true, NULL, 1);
+    (6, 'normal', 'Dummy Playlist', '[{"field": "playlist"}]', true, NULL, 1);
 
 INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
     -- 子プレイリスト 1 の曲
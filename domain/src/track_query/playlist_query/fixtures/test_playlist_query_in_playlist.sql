@@ -0,0 +1,42 @@
+-- 他のプレイリストを参照するフィルタプレイリストのテスト用データ
+
+INSERT INTO tracks (id, duration, path, title, title_order, artist, artist_order, rating) VALUES
+    (1, 180, '/music/track1.mp3', 'Track A', 'Track A', 'Artist A', 'Artist A', 5),
+    (2, 200, '/music/track2.mp3', 'Track B', 'Track B', 'Artist B', 'Artist B', 3),
+    (3, 220, '/music/track3.mp3', 'Track C', 'Track C', 'Artist C', 'Artist C', 4),
+    (4, 240, '/music/track4.mp3', 'Track D', 'Track D', 'Artist D', 'Artist D', 2),
+    (5, 260, '/music/track5.mp3', 'Track E', 'Track E', 'Artist E', 'Artist E', 1);
+
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, parent_id, in_folder_order, filter_json) VALUES
+    -- 参照される通常プレイリスト
+    (1, 'normal', 'Favorites', '[{"field": "playlist"}]', true, NULL, 0, NULL),
+    -- 参照されるフィルタプレイリスト (未リストアップ)
+    (2, 'filter', 'Heard This Month', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, NULL, 1,
+        '{"target": "rating", "range": {"op": "large_equal", "value": 4}}'),
+    -- 1 に含まれ、2 に含まれない曲
+    (3, 'filter', 'Favorites Not Heard', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, NULL, 2,
+        '{"target": "group", "op": "and", "children": [
+            {"target": "in_playlist", "range": {"op": "in", "value": 1}},
+            {"target": "in_playlist", "range": {"op": "not_in", "value": 2}}
+        ]}'),
+    -- 自身を参照
+    (4, 'filter', 'Self Reference', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, NULL, 3,
+        '{"target": "in_playlist", "range": {"op": "in", "value": 4}}'),
+    -- 互いに参照
+    (5, 'filter', 'Cycle A', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, NULL, 4,
+        '{"target": "in_playlist", "range": {"op": "in", "value": 6}}'),
+    (6, 'filter', 'Cycle B', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, NULL, 5,
+        '{"target": "in_playlist", "range": {"op": "not_in", "value": 5}}'),
+    -- 親のフォルダプレイリストを参照
+    (7, 'folder', 'Cycle Folder', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, NULL, 6, NULL),
+    (8, 'filter', 'Cycle Child', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, 7, 0,
+        '{"target": "in_playlist", "range": {"op": "in", "value": 7}}');
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    (1, 0, 1),
+    (1, 1, 2),
+    (1, 2, 3),
+    (1, 3, 4),
+
+    -- リストアップ前の古い内容
+    (2, 0, 5);
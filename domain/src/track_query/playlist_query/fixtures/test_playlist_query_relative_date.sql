@@ -0,0 +1,23 @@
+-- 相対日付の条件を含むフィルタプレイリストのテスト用データ
+
+INSERT INTO tracks (id, duration, path, title, title_order, artist, artist_order, created_at) VALUES
+    (1, 180, '/music/track1.mp3', 'Track A', 'Track A', 'Artist A', 'Artist A', NOW() - interval '2 days'),
+    (2, 200, '/music/track2.mp3', 'Track B', 'Track B', 'Artist B', 'Artist B', NOW() - interval '30 days');
+
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, listuped_date, dap_changed, parent_id, in_folder_order, filter_json) VALUES
+    -- 最近追加した曲 (以前にリストアップ済み)
+    (1, 'filter', 'Recently Added', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, '2000-01-01', false, NULL, 0,
+        '{"target": "entry_date", "range": {"op": "within_days", "value": 7}}'),
+    -- 1 を参照するフィルタプレイリスト (以前にリストアップ済み)
+    (2, 'filter', 'Recently Added Ref', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', true, NULL, false, NULL, 1,
+        '{"target": "in_playlist", "range": {"op": "in", "value": 1}}'),
+    -- 1 に依存しないフィルタプレイリスト (以前にリストアップ済み)
+    (3, 'filter', 'All', '[{"field": "title"}]', true, NULL, false, NULL, 2,
+        '{"target": "rating", "range": {"op": "large_equal", "value": 0}}');
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    -- 以前のリストアップ時点の古い内容
+    (1, 0, 2),
+    (2, 0, 2),
+    (3, 0, 1),
+    (3, 1, 2);
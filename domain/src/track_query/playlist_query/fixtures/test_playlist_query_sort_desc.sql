@@ -24,8 +24,8 @@ INSERT INTO tracks (
     (3, 220, '/music/track3.mp3', 'Track C', 'Track C', 'Artist C', 'Artist C', 'Album C', 'Album C', 'Jazz', 'Composer C', 'Composer C', 3, 1, '2023-03-01', 4, '2023-06-03 12:00:00');
 
 -- プレイリスト順ソート（降順）のプレイリスト
-INSERT INTO playlists (id, playlist_type, name, sort_type, sort_desc, listuped_flag, in_folder_order) VALUES
-    (1, 'normal', 'Test Playlist', 'playlist', true, false, 0);
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order) VALUES
+    (1, 'normal', 'Test Playlist', '[{"field": "playlist", "desc": true}]', false, 0);
 
 -- order_index を意図的に順序を変えて設定
 INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
@@ -1,8 +1,9 @@
-use sqlx::PgTransaction;
+use chrono::NaiveDate;
+use sqlx::{PgTransaction, types::Json};
 
 use crate::{
-    SortTypeWithPlaylist,
-    filter::RootFilter,
+    SortSpec,
+    filter::{RootFilter, filter_document},
     playlist::{PlaylistType, playlist_error::PlaylistError},
 };
 
@@ -18,11 +19,8 @@ pub struct QueryPlaylistModel {
     /// PlaylistType::Filter で使うフィルタ
     pub filter: Option<RootFilter>,
 
-    /// ソート対象
-    pub sort_type: SortTypeWithPlaylist,
-
-    /// ソートが降順か
-    pub sort_desc: bool,
+    /// 並べ替えの指定
+    pub sort_spec: SortSpec,
 
     /// リスト内容がPlaylistTrackテーブルにリストアップ済みか
     ///
@@ -45,8 +43,7 @@ impl QueryPlaylistModel {
               id,
               playlist_type AS "playlist_type: PlaylistType",
               filter_json,
-              sort_type AS "sort_type: SortTypeWithPlaylist",
-              sort_desc,
+              sort_spec AS "sort_spec: Json<SortSpec>",
               listuped_flag
             FROM playlists
             WHERE id = $1
@@ -70,8 +67,7 @@ impl QueryPlaylistModel {
               id,
               playlist_type AS "playlist_type: PlaylistType",
               filter_json,
-              sort_type AS "sort_type: SortTypeWithPlaylist",
-              sort_desc,
+              sort_spec AS "sort_spec: Json<SortSpec>",
               listuped_flag
             FROM playlists
             WHERE parent_id = $1
@@ -87,6 +83,39 @@ impl QueryPlaylistModel {
 
         Ok(playlists)
     }
+
+    /// リストアップ済みのフィルタプレイリストのうち、相対日付の基準日が指定日と異なるものを取得
+    ///
+    /// 相対日付の条件を含むプレイリストのみ、リストアップ時に基準日 (`listuped_date`) を保存している。
+    /// フィルタの deserialize に失敗するプレイリストは、リストアップ時にエラーとなるため、ここでは無視する
+    pub async fn from_db_stale_relative_date_filters<'c>(
+        tx: &mut PgTransaction<'c>,
+        today: NaiveDate,
+    ) -> Result<Vec<QueryPlaylistModel>, PlaylistError> {
+        let playlists = sqlx::query_as!(
+            PlaylistRow,
+            r#"
+            SELECT
+              id,
+              playlist_type AS "playlist_type: PlaylistType",
+              filter_json,
+              sort_spec AS "sort_spec: Json<SortSpec>",
+              listuped_flag
+            FROM playlists
+            WHERE playlist_type = $1 AND listuped_flag AND listuped_date <> $2
+            ORDER BY id
+            "#,
+            PlaylistType::Filter as PlaylistType,
+            today,
+        )
+        .fetch_all(&mut **tx)
+        .await?
+        .into_iter()
+        .filter_map(|row| QueryPlaylistModel::try_from(row).ok())
+        .collect();
+
+        Ok(playlists)
+    }
 }
 
 /// QueryPlaylistModel についての、playlist テーブルのレコード
@@ -94,8 +123,7 @@ struct PlaylistRow {
     pub id: i32,
     pub playlist_type: PlaylistType,
     pub filter_json: Option<serde_json::Value>,
-    pub sort_type: SortTypeWithPlaylist,
-    pub sort_desc: bool,
+    pub sort_spec: Json<SortSpec>,
     pub listuped_flag: bool,
 }
 
@@ -107,14 +135,10 @@ impl TryFrom<PlaylistRow> for QueryPlaylistModel {
             id: row.id,
             playlist_type: row.playlist_type,
             filter: match row.filter_json {
-                Some(json) => Some(
-                    serde_json::from_value(json)
-                        .map_err(PlaylistError::FailedToDeserializeFilter)?,
-                ),
+                Some(json) => Some(filter_document::from_json(json)?),
                 None => None,
             },
-            sort_type: row.sort_type,
-            sort_desc: row.sort_desc,
+            sort_spec: row.sort_spec.0,
             listuped_flag: row.listuped_flag,
         })
     }
@@ -1,10 +1,12 @@
 use anyhow::Result;
-use sqlx::PgPool;
+use futures_util::TryStreamExt;
+use sqlx::{PgPool, PgTransaction, types::Json};
 use std::str::FromStr;
 
 use crate::{
+    SortField, SortSpec, SortSpecItem, SortType, SortTypeWithPlaylist,
     path::LibraryTrackPath,
-    track_query::{SelectColumn, playlist_query::PlaylistQueryBuilder},
+    track_query::{SelectColumn, TrackQueryError, TrackRow, playlist_query::PlaylistQueryBuilder},
 };
 
 /// PlaylistQuery::fetch() のテスト
@@ -178,6 +180,40 @@ mod test_fetch {
 
         Ok(())
     }
+
+    /// TrackRow として取得するテスト
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_query_basic"))]
+    async fn test_fetch_rows(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = PlaylistQueryBuilder::new(2)
+            .column(SelectColumn::Id)
+            .column(SelectColumn::Genre)
+            .column(SelectColumn::DiscNumber)
+            .build();
+
+        let rows = query.fetch_rows(&mut tx).await?;
+
+        assert_eq!(
+            rows,
+            vec![
+                TrackRow {
+                    id: Some(2),
+                    genre: Some("Pop".to_owned()),
+                    disc_number: Some(Some(1)),
+                    ..Default::default()
+                },
+                TrackRow {
+                    id: Some(4),
+                    genre: Some("Jazz".to_owned()),
+                    disc_number: Some(Some(1)),
+                    ..Default::default()
+                },
+            ]
+        );
+
+        Ok(())
+    }
 }
 
 /// プレイリストタイプ別のテスト
@@ -454,3 +490,423 @@ mod test_error_handling {
         Ok(())
     }
 }
+
+/// 他のプレイリストを参照するフィルタのテスト
+mod test_in_playlist {
+    use super::*;
+    use crate::{playlist::playlist_error::PlaylistError, track_query::TrackQueryError};
+
+    /// 参照先のプレイリストを先にリストアップしてから検索する
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_query_in_playlist")
+    )]
+    async fn test_refresh_referenced_playlist(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = PlaylistQueryBuilder::new(3)
+            .column(SelectColumn::Id)
+            .build();
+
+        let rows = query.fetch(&mut tx).await?;
+        let ids: Vec<i32> = rows
+            .iter()
+            .map(|row| SelectColumn::row_id(row).unwrap())
+            .collect();
+
+        assert_eq!(ids, vec![2, 4]);
+
+        // 参照先のフィルタプレイリストもリストアップ済みになる
+        let listuped_flag = sqlx::query_scalar!("SELECT listuped_flag FROM playlists WHERE id = 2")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert!(listuped_flag);
+
+        Ok(())
+    }
+
+    async fn assert_cycle_detected(pool: PgPool, playlist_id: i32, expected: &[i32]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = PlaylistQueryBuilder::new(playlist_id)
+            .column(SelectColumn::Id)
+            .build();
+
+        let result = query.fetch(&mut tx).await;
+
+        match result {
+            Err(TrackQueryError::Playlist(PlaylistError::PlaylistCycleDetected(ids))) => {
+                assert_eq!(ids, expected);
+            }
+            _ => panic!("unexpected result: {result:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_query_in_playlist")
+    )]
+    async fn test_self_reference(pool: PgPool) -> Result<()> {
+        assert_cycle_detected(pool, 4, &[4, 4]).await
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_query_in_playlist")
+    )]
+    async fn test_mutual_reference(pool: PgPool) -> Result<()> {
+        assert_cycle_detected(pool, 5, &[5, 6, 5]).await
+    }
+
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_query_in_playlist")
+    )]
+    async fn test_reference_parent_folder(pool: PgPool) -> Result<()> {
+        assert_cycle_detected(pool, 7, &[7, 8, 7]).await
+    }
+}
+
+/// 相対日付の条件を含むフィルタプレイリストのテスト
+mod test_relative_date {
+    use chrono::FixedOffset;
+
+    use super::*;
+    use crate::filter::FilterContext;
+
+    /// リストアップ済みでも、検索時の日付で再度リストアップする
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_query_relative_date")
+    )]
+    async fn test_refresh_relative_date_playlist(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = PlaylistQueryBuilder::new(1)
+            .column(SelectColumn::Id)
+            .time_zone(FixedOffset::east_opt(9 * 3600).unwrap())
+            .build();
+
+        let rows = query.fetch(&mut tx).await?;
+        let ids: Vec<i32> = rows
+            .iter()
+            .map(|row| SelectColumn::row_id(row).unwrap())
+            .collect();
+        assert_eq!(ids, vec![1]);
+
+        let dap_changed = sqlx::query_scalar!("SELECT dap_changed FROM playlists WHERE id = 1")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert!(dap_changed);
+
+        Ok(())
+    }
+
+    /// 内容が変わったら、参照しているプレイリストも再度リストアップする
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_query_relative_date")
+    )]
+    async fn test_refresh_referencing_playlist(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let query = PlaylistQueryBuilder::new(2)
+            .column(SelectColumn::Id)
+            .build();
+
+        let rows = query.fetch(&mut tx).await?;
+        let ids: Vec<i32> = rows
+            .iter()
+            .map(|row| SelectColumn::row_id(row).unwrap())
+            .collect();
+        assert_eq!(ids, vec![1]);
+
+        // 内容が変わったプレイリストに依存しないプレイリストは、リストアップし直さない
+        let listuped_flag = sqlx::query_scalar!("SELECT listuped_flag FROM playlists WHERE id = 3")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert!(listuped_flag);
+
+        Ok(())
+    }
+
+    /// 基準日が変わっていなければ、再度リストアップしない
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_query_relative_date")
+    )]
+    async fn test_skip_same_date(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let time_zone = FixedOffset::east_opt(9 * 3600).unwrap();
+        sqlx::query!(
+            "UPDATE playlists SET listuped_date = $1 WHERE id = 1",
+            FilterContext::now(time_zone).today,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let query = PlaylistQueryBuilder::new(1)
+            .column(SelectColumn::Id)
+            .time_zone(time_zone)
+            .build();
+
+        let rows = query.fetch(&mut tx).await?;
+        let ids: Vec<i32> = rows
+            .iter()
+            .map(|row| SelectColumn::row_id(row).unwrap())
+            .collect();
+        assert_eq!(ids, vec![2]);
+
+        let row = sqlx::query!("SELECT dap_changed, listuped_flag FROM playlists WHERE id = 2")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert!(!row.dap_changed);
+        assert!(row.listuped_flag);
+
+        Ok(())
+    }
+
+    /// リストアップ時に、相対日付の基準日を保存する
+    #[sqlx::test(
+        migrator = "crate::MIGRATOR",
+        fixtures("test_playlist_query_relative_date")
+    )]
+    async fn test_save_listuped_date(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let time_zone = FixedOffset::east_opt(9 * 3600).unwrap();
+        PlaylistQueryBuilder::new(2)
+            .column(SelectColumn::Id)
+            .time_zone(time_zone)
+            .build()
+            .fetch(&mut tx)
+            .await?;
+
+        let rows = sqlx::query!("SELECT id, listuped_date FROM playlists ORDER BY id")
+            .fetch_all(&mut *tx)
+            .await?;
+        let dates: Vec<_> = rows
+            .into_iter()
+            .map(|row| (row.id, row.listuped_date))
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                (1, Some(FilterContext::now(time_zone).today)),
+                (2, None),
+                (3, None)
+            ]
+        );
+
+        Ok(())
+    }
+}
+
+/// PlaylistQuery::fetch_page() のテスト
+mod test_fetch_page {
+    use super::*;
+
+    /// 同じ曲を複数含むプレイリストでも、カーソルで順に取得した結果が一度に取得した結果と一致する
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_query_cursor"))]
+    async fn test_pages_match_full_list(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let mut specs = vec![];
+        for sort_type in [
+            SortTypeWithPlaylist::Playlist,
+            SortType::Artist.into(),
+            SortType::ReleaseDate.into(),
+            SortType::Path.into(),
+            SortType::Random { seed: 5 }.into(),
+            SortType::WeightedShuffle { seed: 5 }.into(),
+        ] {
+            specs.push(sort_type.spec(false));
+            specs.push(sort_type.spec(true));
+        }
+        // 方向が混在する指定
+        specs.push(SortSpec::new(vec![
+            SortSpecItem::new(SortField::ReleaseDate, true),
+            SortSpecItem::new(SortField::AlbumArtist, false),
+            SortSpecItem::new(SortField::Rating, true),
+            SortSpecItem::new(SortField::TrackNumber, false),
+        ]));
+        specs.push(SortSpec::new(vec![
+            SortSpecItem::new(SortField::Title, false),
+            SortSpecItem::new(SortField::Playlist, true),
+        ]));
+
+        for spec in specs {
+            sqlx::query("UPDATE playlists SET sort_spec = $1 WHERE id = 1")
+                .bind(Json(&spec))
+                .execute(&mut *tx)
+                .await?;
+
+            let expected: Vec<i32> = PlaylistQueryBuilder::new(1)
+                .column(SelectColumn::Id)
+                .build()
+                .fetch(&mut tx)
+                .await?
+                .iter()
+                .map(|row| SelectColumn::row_id(row).unwrap())
+                .collect();
+            assert_eq!(expected.len(), 7);
+
+            let mut actual = vec![];
+            let mut cursor = None;
+            loop {
+                let mut builder = PlaylistQueryBuilder::new(1)
+                    .column(SelectColumn::Id)
+                    .limit(3);
+                if let Some(cursor) = cursor {
+                    builder = builder.after(cursor);
+                }
+
+                let page = builder.build().fetch_page(&mut tx).await?;
+                actual.extend(
+                    page.rows
+                        .iter()
+                        .map(|row| SelectColumn::row_id(row).unwrap()),
+                );
+
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+
+            assert_eq!(actual, expected, "sort: {spec:?}");
+        }
+
+        Ok(())
+    }
+
+    /// プレイリストの並び順が変わった後のカーソルはエラー
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_query_cursor"))]
+    async fn test_cursor_after_sort_changed(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let page = PlaylistQueryBuilder::new(1)
+            .column(SelectColumn::Id)
+            .limit(3)
+            .build()
+            .fetch_page(&mut tx)
+            .await?;
+        assert_eq!(
+            page.rows
+                .iter()
+                .map(|row| SelectColumn::row_id(row).unwrap())
+                .collect::<Vec<_>>(),
+            vec![5, 2, 7]
+        );
+
+        sqlx::query("UPDATE playlists SET sort_spec = $1 WHERE id = 1")
+            .bind(Json(SortTypeWithPlaylist::Playlist.spec(true)))
+            .execute(&mut *tx)
+            .await?;
+
+        let result = PlaylistQueryBuilder::new(1)
+            .column(SelectColumn::Id)
+            .after(page.next_cursor.unwrap())
+            .build()
+            .fetch(&mut tx)
+            .await;
+        assert!(matches!(result, Err(TrackQueryError::CursorMismatch)));
+
+        Ok(())
+    }
+
+    /// random_seed を指定すると、保存された seed の代わりに使用する
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_query_cursor"))]
+    async fn test_random_seed(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        async fn ids<'c>(
+            tx: &mut PgTransaction<'c>,
+            builder: PlaylistQueryBuilder,
+        ) -> Result<Vec<i32>> {
+            Ok(builder
+                .column(SelectColumn::Id)
+                .build()
+                .fetch(tx)
+                .await?
+                .iter()
+                .map(|row| SelectColumn::row_id(row).unwrap())
+                .collect())
+        }
+
+        let spec_of = |seed: i32| {
+            SortSpec::new(vec![
+                SortSpecItem::new(SortField::Rating, true),
+                SortSpecItem::new(SortField::Random { seed }, false),
+            ])
+        };
+
+        sqlx::query("UPDATE playlists SET sort_spec = $1 WHERE id = 1")
+            .bind(Json(spec_of(1)))
+            .execute(&mut *tx)
+            .await?;
+        let stored = ids(&mut tx, PlaylistQueryBuilder::new(1)).await?;
+        let overridden = ids(&mut tx, PlaylistQueryBuilder::new(1).random_seed(2)).await?;
+
+        // 保存された指定の seed だけを置き換えた並び順と一致する
+        sqlx::query("UPDATE playlists SET sort_spec = $1 WHERE id = 1")
+            .bind(Json(spec_of(2)))
+            .execute(&mut *tx)
+            .await?;
+        assert_eq!(
+            overridden,
+            ids(&mut tx, PlaylistQueryBuilder::new(1)).await?
+        );
+        assert_ne!(stored, overridden);
+
+        Ok(())
+    }
+}
+
+/// PlaylistQuery::prepare() で作成した Stream のテスト
+mod test_fetch_stream {
+    use super::*;
+
+    /// prepare() の時点でリストアップが済み、その後 Stream で曲を取得できる
+    #[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_playlist_query_filter"))]
+    async fn test_listup_before_stream(pool: PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!("UPDATE playlists SET dap_changed = false WHERE id = 2")
+            .execute(&mut *tx)
+            .await?;
+
+        let query = PlaylistQueryBuilder::new(2)
+            .column(SelectColumn::Id)
+            .column(SelectColumn::Path)
+            .build();
+        let prepared = query.prepare(&mut tx).await?;
+
+        // 曲の取得前に、リストアップの結果を確認できる
+        let flags = sqlx::query!("SELECT listuped_flag, dap_changed FROM playlists WHERE id = 2")
+            .fetch_one(&mut *tx)
+            .await?;
+        assert!(flags.listuped_flag);
+        assert!(flags.dap_changed);
+
+        let mut stream = prepared.fetch_row_stream(&mut tx);
+        let mut paths = vec![];
+        while let Some(row) = stream.try_next().await? {
+            paths.push(row.path.unwrap());
+        }
+
+        // artist 順
+        assert_eq!(
+            paths,
+            vec![
+                LibraryTrackPath::from_str("/music/track1.mp3")?,
+                LibraryTrackPath::from_str("/music/track3.mp3")?,
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use futures_util::{StreamExt, TryStreamExt, stream::BoxStream};
+use sqlx::{
+    Execute, PgTransaction, Postgres, QueryBuilder,
+    postgres::{PgArguments, PgRow},
+};
+
+use crate::track_query::{SelectColumn, TrackQueryError, TrackRow};
+
+/// 実行の準備が済んだ曲の検索
+///
+/// `PlaylistQuery::prepare` または `TrackQuery::prepare` で作成する。
+/// プレイリストのリストアップなどは作成時に済んでいるため、
+/// 曲の取得前にリストアップの結果 (`dap_changed` など) を確認できる。
+///
+/// 実行のたびにクエリを作成するため、同じ検索を何度でも実行できる。
+pub struct PreparedTrackQuery {
+    /// 実行する SQL
+    sql: String,
+
+    /// SQL にバインドする値
+    arguments: PgArguments,
+
+    /// 取得するカラムの指定
+    columns: Vec<SelectColumn>,
+}
+
+impl PreparedTrackQuery {
+    pub(super) fn new(
+        mut builder: QueryBuilder<'static, Postgres>,
+        columns: Vec<SelectColumn>,
+    ) -> Self {
+        //QueryBuilder で組み立てたクエリからは、引数を常に取り出せる
+        let arguments = builder
+            .build()
+            .take_arguments()
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+
+        Self {
+            sql: builder.into_sql(),
+            arguments,
+            columns,
+        }
+    }
+
+    /// 検索結果の行を、DB から届いた順に取得する Stream
+    ///
+    /// 全ての行をメモリに保持しないため、曲数が多くてもメモリ使用量が増えない。
+    pub fn fetch_stream<'a, 'c>(
+        &'a self,
+        tx: &'a mut PgTransaction<'c>,
+    ) -> BoxStream<'a, Result<PgRow, TrackQueryError>> {
+        sqlx::query_with(&self.sql, self.arguments.clone())
+            .fetch(&mut **tx)
+            .map_err(TrackQueryError::from)
+            .boxed()
+    }
+
+    /// 検索結果を、指定したカラムを読み取った `TrackRow` として取得する Stream
+    pub fn fetch_row_stream<'a, 'c>(
+        &'a self,
+        tx: &'a mut PgTransaction<'c>,
+    ) -> BoxStream<'a, Result<TrackRow, TrackQueryError>> {
+        let columns = &self.columns;
+        sqlx::query_with(&self.sql, self.arguments.clone())
+            .fetch(&mut **tx)
+            .map(move |row| Ok(TrackRow::from_row(&row?, columns)?))
+            .boxed()
+    }
+
+    /// 検索結果の全ての行を取得
+    pub(super) async fn fetch_all<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<Vec<PgRow>, TrackQueryError> {
+        let list = sqlx::query_with(&self.sql, self.arguments.clone())
+            .fetch_all(&mut **tx)
+            .await?;
+        Ok(list)
+    }
+}
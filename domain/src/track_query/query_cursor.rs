@@ -0,0 +1,214 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder, Row, postgres::PgRow};
+
+use crate::{SortKey, SortKeyKind, track_query::TrackQueryError};
+
+/// 検索結果の続きを取得するためのカーソル
+///
+/// 前のページの最後の曲の、並べ替えのキーの値を保持する。
+/// 文字列に変換して受け渡しし、内容は不透明なものとして扱う。
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct QueryCursor {
+    /// 並び順の名前
+    sort: String,
+
+    /// 並べ替えのキーの値
+    values: Vec<CursorValue>,
+}
+
+/// カーソルに保持する、並べ替えのキーの値
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CursorValue {
+    Bool(bool),
+    SmallInt(i16),
+    Int(i32),
+    Text(String),
+    Date(NaiveDate),
+    DateTime(DateTime<Utc>),
+}
+
+/// カーソルで取得した検索結果の 1 ページ
+#[derive(Debug)]
+pub struct QueryPage {
+    /// 曲のレコード
+    pub rows: Vec<PgRow>,
+
+    /// 次のページを取得するためのカーソル
+    ///
+    /// 最後のページであれば None
+    pub next_cursor: Option<QueryCursor>,
+}
+
+impl QueryCursor {
+    /// 指定した並び順のカーソルか確認し、キーの値を取得
+    fn values_for(&self, sort: &str, keys: &[SortKey]) -> Result<&[CursorValue], TrackQueryError> {
+        let matches_kind = |value: &CursorValue, kind: SortKeyKind| {
+            matches!(
+                (value, kind),
+                (CursorValue::Bool(_), SortKeyKind::Bool)
+                    | (CursorValue::SmallInt(_), SortKeyKind::SmallInt)
+                    | (CursorValue::Int(_), SortKeyKind::Int)
+                    | (CursorValue::Text(_), SortKeyKind::Text)
+                    | (CursorValue::Date(_), SortKeyKind::Date)
+                    | (CursorValue::DateTime(_), SortKeyKind::DateTime)
+            )
+        };
+
+        if self.sort != sort
+            || self.values.len() != keys.len()
+            || !self
+                .values
+                .iter()
+                .zip(keys)
+                .all(|(value, key)| matches_kind(value, key.kind))
+        {
+            return Err(TrackQueryError::CursorMismatch);
+        }
+
+        Ok(&self.values)
+    }
+}
+
+impl fmt::Display for QueryCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        //JSON を16進数の文字列にする
+        let json = serde_json::to_vec(self).map_err(|_| fmt::Error)?;
+        for b in json {
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for QueryCursor {
+    type Err = TrackQueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.len().is_multiple_of(2) || !s.is_ascii() {
+            return Err(TrackQueryError::InvalidCursor);
+        }
+
+        let bytes = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| TrackQueryError::InvalidCursor)?;
+
+        serde_json::from_slice(&bytes).map_err(|_| TrackQueryError::InvalidCursor)
+    }
+}
+
+/// 並べ替えのキーの値を取得するカラムを、SELECT 句に追加
+///
+/// 先頭の `,` も追加する
+pub(super) fn push_cursor_columns(builder: &mut QueryBuilder<'_, Postgres>, keys: &[SortKey]) {
+    for (i, key) in keys.iter().enumerate() {
+        builder.push(format!(", {} AS {}", key.expression, cursor_column_name(i)));
+    }
+}
+
+/// カーソルより後の曲に絞り込む条件式を、QueryBuilder に追加
+///
+/// 全てのキーの並び順の方向が同じなら、行値の比較で判定する。
+/// 方向が混在する場合は、先頭のキーから順に比較する条件式に展開する。
+pub(super) fn push_cursor_condition(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    cursor: &QueryCursor,
+    sort: &str,
+    keys: &[SortKey],
+) -> Result<(), TrackQueryError> {
+    let values = cursor.values_for(sort, keys)?;
+
+    let Some(first) = keys.first() else {
+        return Ok(());
+    };
+
+    if keys.iter().all(|key| key.desc == first.desc) {
+        builder.push("(");
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                builder.push(", ");
+            }
+            builder.push(&key.expression);
+        }
+        builder.push(if first.desc { ") < (" } else { ") > (" });
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                builder.push(", ");
+            }
+            push_bind_value(builder, value);
+        }
+        builder.push(")");
+    } else {
+        // (k1 > v1) OR (k1 = v1 AND k2 < v2) OR ...
+        builder.push("(");
+        for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+            if i > 0 {
+                builder.push(" OR ");
+            }
+            builder.push("(");
+            for (prev_key, prev_value) in keys[..i].iter().zip(values) {
+                builder.push(format!("{} = ", prev_key.expression));
+                push_bind_value(builder, prev_value);
+                builder.push(" AND ");
+            }
+            builder.push(format!(
+                "{} {} ",
+                key.expression,
+                if key.desc { "<" } else { ">" }
+            ));
+            push_bind_value(builder, value);
+            builder.push(")");
+        }
+        builder.push(")");
+    }
+
+    Ok(())
+}
+
+/// キーの値をバインドする
+fn push_bind_value(builder: &mut QueryBuilder<'_, Postgres>, value: &CursorValue) {
+    match value {
+        CursorValue::Bool(v) => builder.push_bind(*v),
+        CursorValue::SmallInt(v) => builder.push_bind(*v),
+        CursorValue::Int(v) => builder.push_bind(*v),
+        CursorValue::Text(v) => builder.push_bind(v.clone()),
+        CursorValue::Date(v) => builder.push_bind(*v),
+        CursorValue::DateTime(v) => builder.push_bind(*v),
+    };
+}
+
+/// 取得した行の並べ替えのキーの値から、カーソルを作成
+///
+/// `push_cursor_columns` で追加したカラムから値を読み取る
+pub(super) fn read_cursor(row: &PgRow, sort: &str, keys: &[SortKey]) -> sqlx::Result<QueryCursor> {
+    let values = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let name = cursor_column_name(i);
+            Ok(match key.kind {
+                SortKeyKind::Bool => CursorValue::Bool(row.try_get(name.as_str())?),
+                SortKeyKind::SmallInt => CursorValue::SmallInt(row.try_get(name.as_str())?),
+                SortKeyKind::Int => CursorValue::Int(row.try_get(name.as_str())?),
+                SortKeyKind::Text => CursorValue::Text(row.try_get(name.as_str())?),
+                SortKeyKind::Date => CursorValue::Date(row.try_get(name.as_str())?),
+                SortKeyKind::DateTime => CursorValue::DateTime(row.try_get(name.as_str())?),
+            })
+        })
+        .collect::<sqlx::Result<Vec<_>>>()?;
+
+    Ok(QueryCursor {
+        sort: sort.to_owned(),
+        values,
+    })
+}
+
+/// 並べ替えのキーの値を取得するカラムの名前
+fn cursor_column_name(index: usize) -> String {
+    format!("cursor_key_{index}")
+}
@@ -3,12 +3,45 @@ use sqlx::{Row, postgres::PgRow};
 use crate::path::LibraryTrackPath;
 
 /// `tracks` テーブルからの検索時に取得するカラム
+///
+/// 取得した行は、カラムごとの `row_*` 関数か、`TrackRow::from_row` で読み取る。
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum SelectColumn {
     Id,
     Path,
     Title,
+    Artist,
+    AlbumArtist,
+    Album,
+    Composer,
+    Genre,
+    Duration,
+    TrackNumber,
+    TrackMax,
+    DiscNumber,
+    DiscMax,
+    ReleaseDate,
+    Rating,
+    OriginalTrack,
+    SuggestTarget,
+    Memo,
+    MemoManage,
+    Lyrics,
+    TitleOrder,
+    ArtistOrder,
+    AlbumArtistOrder,
+    AlbumOrder,
+    ComposerOrder,
+    GenreOrder,
+    FolderId,
+    CreatedAt,
+    UpdatedAt,
+
+    /// 先頭のアートワークの ID
     ArtworkId,
+
+    /// 曲に付けられたタグの ID (タグ ID 順)
+    TagIds,
 }
 
 impl SelectColumn {
@@ -32,12 +65,80 @@ impl SelectColumn {
         row.try_get("artwork_id")
     }
 
+    /// 取得した行での、このカラムの名前
+    pub fn row_column_name(&self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Path => "path",
+            Self::Title => "title",
+            Self::Artist => "artist",
+            Self::AlbumArtist => "album_artist",
+            Self::Album => "album",
+            Self::Composer => "composer",
+            Self::Genre => "genre",
+            Self::Duration => "duration",
+            Self::TrackNumber => "track_number",
+            Self::TrackMax => "track_max",
+            Self::DiscNumber => "disc_number",
+            Self::DiscMax => "disc_max",
+            Self::ReleaseDate => "release_date",
+            Self::Rating => "rating",
+            Self::OriginalTrack => "original_track",
+            Self::SuggestTarget => "suggest_target",
+            Self::Memo => "memo",
+            Self::MemoManage => "memo_manage",
+            Self::Lyrics => "lyrics",
+            Self::TitleOrder => "title_order",
+            Self::ArtistOrder => "artist_order",
+            Self::AlbumArtistOrder => "album_artist_order",
+            Self::AlbumOrder => "album_order",
+            Self::ComposerOrder => "composer_order",
+            Self::GenreOrder => "genre_order",
+            Self::FolderId => "folder_id",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+            Self::ArtworkId => "artwork_id",
+            Self::TagIds => "tag_ids",
+        }
+    }
+
+    /// SELECT 句で使用する式
     pub(super) fn sql_column_name(&self) -> &'static str {
         match self {
             Self::Id => "tracks.id",
             Self::Path => "tracks.path",
             Self::Title => "tracks.title",
+            Self::Artist => "tracks.artist",
+            Self::AlbumArtist => "tracks.album_artist",
+            Self::Album => "tracks.album",
+            Self::Composer => "tracks.composer",
+            Self::Genre => "tracks.genre",
+            Self::Duration => "tracks.duration",
+            Self::TrackNumber => "tracks.track_number",
+            Self::TrackMax => "tracks.track_max",
+            Self::DiscNumber => "tracks.disc_number",
+            Self::DiscMax => "tracks.disc_max",
+            Self::ReleaseDate => "tracks.release_date",
+            Self::Rating => "tracks.rating",
+            Self::OriginalTrack => "tracks.original_track",
+            Self::SuggestTarget => "tracks.suggest_target",
+            Self::Memo => "tracks.memo",
+            Self::MemoManage => "tracks.memo_manage",
+            Self::Lyrics => "tracks.lyrics",
+            Self::TitleOrder => "tracks.title_order",
+            Self::ArtistOrder => "tracks.artist_order",
+            Self::AlbumArtistOrder => "tracks.album_artist_order",
+            Self::AlbumOrder => "tracks.album_order",
+            Self::ComposerOrder => "tracks.composer_order",
+            Self::GenreOrder => "tracks.genre_order",
+            Self::FolderId => "tracks.folder_id",
+            Self::CreatedAt => "tracks.created_at",
+            Self::UpdatedAt => "tracks.updated_at",
             Self::ArtworkId => "track_artworks.artwork_id",
+            //行ごとに集約するため、JOIN ではなくサブクエリで取得する
+            Self::TagIds => {
+                "ARRAY(SELECT track_tags.tag_id FROM track_tags WHERE track_tags.track_id = tracks.id ORDER BY track_tags.tag_id) AS tag_ids"
+            }
         }
     }
 }
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests;
+
+use std::time::Duration;
+
+use sqlx::{PgTransaction, Postgres, QueryBuilder, Row};
+
+use crate::{
+    filter::{FilterContext, FilterTimeZone, RootFilter},
+    track::TrackDuration,
+    track_query::{TrackQueryError, playlist_query},
+};
+
+/// 曲の集計結果
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TrackSummary {
+    /// 曲数
+    pub track_count: i64,
+
+    /// 再生時間の合計
+    pub total_duration: TrackDuration,
+
+    /// ファイルサイズの合計 (バイト)
+    ///
+    /// ファイルサイズは DB に保存していないため、現状は常に None
+    pub total_file_size: Option<u64>,
+
+    /// アルバム数
+    ///
+    /// アルバム名とアルバムアーティストの組を一つのアルバムとして数える。
+    /// アルバム名が空の曲は数えない。
+    pub album_count: i64,
+}
+
+/// 集計対象
+#[derive(Debug, PartialEq, Clone)]
+enum SummaryTarget {
+    /// プレイリストの曲
+    Playlist(i32),
+
+    /// フィルタの条件を満たす曲
+    Filter(RootFilter),
+}
+
+/// 曲数や再生時間の合計を、一つの集計 SQL で取得する検索条件
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrackSummaryQuery {
+    /// 集計対象
+    target: SummaryTarget,
+
+    /// フィルタの日付の判定に使うタイムゾーン (None ならローカルタイムゾーン)
+    time_zone: Option<FilterTimeZone>,
+}
+
+impl TrackSummaryQuery {
+    /// 集計結果を取得
+    ///
+    /// プレイリストが対象の場合、リストアップされていなければ先にリストアップする。
+    pub async fn fetch<'c>(
+        &self,
+        tx: &mut PgTransaction<'c>,
+    ) -> Result<TrackSummary, TrackQueryError> {
+        let context = FilterContext::from_time_zone(self.time_zone);
+
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT COUNT(*) AS track_count, \
+            COALESCE(SUM(tracks.duration), 0)::BIGINT AS total_duration, \
+            COUNT(DISTINCT (tracks.album, tracks.album_artist)) FILTER (WHERE tracks.album <> '') AS album_count",
+        );
+
+        match &self.target {
+            SummaryTarget::Playlist(playlist_id) => {
+                playlist_query::listup_playlist(tx, *playlist_id, context).await?;
+
+                builder
+                    .push(
+                        " FROM playlist_tracks JOIN tracks ON playlist_tracks.track_id = tracks.id",
+                    )
+                    .push(" WHERE playlist_tracks.playlist_id = ")
+                    .push_bind(*playlist_id);
+            }
+            SummaryTarget::Filter(filter) => {
+                //不正な正規表現は、クエリの実行前にエラーとする
+                filter.validate_regex()?;
+
+                playlist_query::prepare_referenced_playlists(tx, filter, context).await?;
+
+                builder.push(" FROM tracks");
+                if filter.has_condition() {
+                    builder.push(" WHERE ");
+                    filter.push_where_expression(&mut builder, &context);
+                }
+            }
+        }
+
+        let row = builder.build().fetch_one(&mut **tx).await?;
+        let total_millis: i64 = row.try_get("total_duration")?;
+
+        Ok(TrackSummary {
+            track_count: row.try_get("track_count")?,
+            total_duration: Duration::from_millis(total_millis as u64).into(),
+            total_file_size: None,
+            album_count: row.try_get("album_count")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackSummaryQueryBuilder {
+    target: SummaryTarget,
+    time_zone: Option<FilterTimeZone>,
+}
+
+impl TrackSummaryQueryBuilder {
+    /// プレイリストの曲を集計する
+    pub fn playlist(playlist_id: i32) -> Self {
+        Self {
+            target: SummaryTarget::Playlist(playlist_id),
+            time_zone: None,
+        }
+    }
+
+    /// フィルタの条件を満たす曲を集計する
+    pub fn filter(filter: RootFilter) -> Self {
+        Self {
+            target: SummaryTarget::Filter(filter),
+            time_zone: None,
+        }
+    }
+
+    /// フィルタの日付の判定に使うタイムゾーンを指定 (`FilterContext::from_time_zone` を参照)
+    pub fn time_zone(mut self, time_zone: impl Into<FilterTimeZone>) -> Self {
+        self.time_zone = Some(time_zone.into());
+        self
+    }
+
+    pub fn build(self) -> TrackSummaryQuery {
+        TrackSummaryQuery {
+            target: self.target,
+            time_zone: self.time_zone,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+-- 曲の集計のテスト用データ
+
+INSERT INTO tracks (id, duration, path, title, album_artist, album, rating) VALUES
+    (1, 1000, '/music/a/01.mp3', 'Track 1', 'Artist X', 'Album A', 5),
+    (2, 2000, '/music/a/02.mp3', 'Track 2', 'Artist X', 'Album A', 3),
+    (3, 3000, '/music/a2/01.mp3', 'Track 3', 'Artist Y', 'Album A', 4),
+    (4, 4000, '/music/single.mp3', 'Track 4', 'Artist X', '', 5),
+    (5, 5000, '/music/b/01.mp3', 'Track 5', 'Artist X', 'Album B', 1);
+
+-- 通常プレイリストは同じ曲を複数含む
+-- フィルタプレイリスト (rating >= 4) はリストアップされていない
+INSERT INTO playlists (id, playlist_type, name, sort_spec, listuped_flag, in_folder_order, filter_json) VALUES
+    (1, 'normal', 'Normal', '[{"field": "playlist"}]', true, 0, NULL),
+    (2, 'filter', 'High Rated', '[{"field": "artist"}, {"field": "album"}, {"field": "disc_number"}, {"field": "track_number"}, {"field": "title"}]', false, 1, '{
+        "target": "rating",
+        "range": {
+            "op": "large_equal",
+            "value": 4
+        }
+    }');
+
+INSERT INTO playlist_tracks (playlist_id, order_index, track_id) VALUES
+    (1, 0, 2),
+    (1, 1, 5),
+    (1, 2, 2);
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+use crate::{
+    filter::{FilterTarget, GroupOperand, IntFilterRange, StringFilterRange},
+    track::TrackDuration,
+    track_query::{TrackSummary, TrackSummaryQueryBuilder},
+};
+
+fn summary(track_count: i64, total_millis: i32, album_count: i64) -> TrackSummary {
+    TrackSummary {
+        track_count,
+        total_duration: TrackDuration::from_i32_millis(total_millis),
+        total_file_size: None,
+        album_count,
+    }
+}
+
+/// 通常プレイリストは、重複した曲もそれぞれ数える
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_summary_query"))]
+async fn test_normal_playlist(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let result = TrackSummaryQueryBuilder::playlist(1)
+        .build()
+        .fetch(&mut tx)
+        .await?;
+    assert_eq!(result, summary(3, 9000, 2));
+
+    Ok(())
+}
+
+/// リストアップされていないプレイリストは、リストアップしてから集計する
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_summary_query"))]
+async fn test_filter_playlist(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let result = TrackSummaryQueryBuilder::playlist(2)
+        .build()
+        .fetch(&mut tx)
+        .await?;
+    assert_eq!(result, summary(3, 8000, 2));
+
+    let listuped_flag = sqlx::query_scalar!("SELECT listuped_flag FROM playlists WHERE id = 2")
+        .fetch_one(&mut *tx)
+        .await?;
+    assert!(listuped_flag);
+
+    Ok(())
+}
+
+/// フィルタの条件を満たす曲を集計する
+#[sqlx::test(migrator = "crate::MIGRATOR", fixtures("test_summary_query"))]
+async fn test_filter(pool: PgPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let cases = [
+        (
+            FilterTarget::Rating {
+                range: IntFilterRange::LargeEqual { value: 4 },
+            },
+            summary(3, 8000, 2),
+        ),
+        // 条件の無いフィルタは全ての曲
+        (
+            FilterTarget::FilterGroup {
+                op: GroupOperand::And,
+                children: vec![],
+            },
+            summary(5, 15000, 3),
+        ),
+        // 該当する曲が無い
+        (
+            FilterTarget::Album {
+                range: StringFilterRange::Equal {
+                    value: "nothing".to_owned(),
+                    normalize: false,
+                },
+            },
+            summary(0, 0, 0),
+        ),
+    ];
+
+    for (filter, expected) in cases {
+        let result = TrackSummaryQueryBuilder::filter(filter.clone())
+            .build()
+            .fetch(&mut tx)
+            .await?;
+        assert_eq!(result, expected, "filter: {filter:?}");
+    }
+
+    Ok(())
+}
@@ -1,10 +1,25 @@
-use crate::playlist::playlist_error::PlaylistError;
+use crate::{filter::FilterError, playlist::playlist_error::PlaylistError};
 
 #[derive(thiserror::Error, Debug)]
 pub enum TrackQueryError {
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+
     #[error(transparent)]
     Playlist(#[from] PlaylistError),
 
     #[error(transparent)]
     Sqlx(#[from] sqlx::Error),
+
+    /// カーソルの文字列が不正
+    #[error("カーソルが不正です")]
+    InvalidCursor,
+
+    /// カーソルが、検索の並び順と異なる並び順で作成されている
+    #[error("カーソルの並び順が、検索の並び順と一致しません")]
+    CursorMismatch,
+
+    /// プレイリスト以外の検索で、プレイリスト順の並べ替えが指定された
+    #[error("プレイリスト順の並べ替えは、プレイリストの曲の検索でのみ使用できます")]
+    PlaylistOrderUnavailable,
 }
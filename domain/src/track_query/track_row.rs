@@ -0,0 +1,98 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{Row, postgres::PgRow};
+
+use crate::{path::LibraryTrackPath, track::TrackDuration, track_query::SelectColumn};
+
+/// 検索で取得した曲の 1 行分のデータ
+///
+/// 取得したカラムのフィールドのみ Some となる。
+/// NULL になりうるカラムは、取得して値が無い場合に `Some(None)` となる。
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct TrackRow {
+    pub id: Option<i32>,
+    pub path: Option<LibraryTrackPath>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album_artist: Option<String>,
+    pub album: Option<String>,
+    pub composer: Option<String>,
+    pub genre: Option<String>,
+    pub duration: Option<TrackDuration>,
+    pub track_number: Option<Option<i32>>,
+    pub track_max: Option<Option<i32>>,
+    pub disc_number: Option<Option<i32>>,
+    pub disc_max: Option<Option<i32>>,
+    pub release_date: Option<Option<NaiveDate>>,
+    pub rating: Option<i16>,
+    pub original_track: Option<String>,
+    pub suggest_target: Option<bool>,
+    pub memo: Option<String>,
+    pub memo_manage: Option<String>,
+    pub lyrics: Option<String>,
+    pub title_order: Option<String>,
+    pub artist_order: Option<String>,
+    pub album_artist_order: Option<String>,
+    pub album_order: Option<String>,
+    pub composer_order: Option<String>,
+    pub genre_order: Option<String>,
+    pub folder_id: Option<Option<i32>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+
+    /// 先頭のアートワークの ID
+    pub artwork_id: Option<Option<i32>>,
+
+    /// 曲に付けられたタグの ID (タグ ID 順)
+    pub tag_ids: Option<Vec<i32>>,
+}
+
+impl TrackRow {
+    /// 指定したカラムの値を、PgRow から読み取る
+    ///
+    /// columns: 検索時に指定したカラム
+    pub fn from_row(row: &PgRow, columns: &[SelectColumn]) -> sqlx::Result<Self> {
+        let mut track = Self::default();
+
+        for column in columns {
+            let name = column.row_column_name();
+
+            match column {
+                SelectColumn::Id => track.id = Some(row.try_get(name)?),
+                SelectColumn::Path => track.path = Some(row.try_get(name)?),
+                SelectColumn::Title => track.title = Some(row.try_get(name)?),
+                SelectColumn::Artist => track.artist = Some(row.try_get(name)?),
+                SelectColumn::AlbumArtist => track.album_artist = Some(row.try_get(name)?),
+                SelectColumn::Album => track.album = Some(row.try_get(name)?),
+                SelectColumn::Composer => track.composer = Some(row.try_get(name)?),
+                SelectColumn::Genre => track.genre = Some(row.try_get(name)?),
+                SelectColumn::Duration => track.duration = Some(row.try_get(name)?),
+                SelectColumn::TrackNumber => track.track_number = Some(row.try_get(name)?),
+                SelectColumn::TrackMax => track.track_max = Some(row.try_get(name)?),
+                SelectColumn::DiscNumber => track.disc_number = Some(row.try_get(name)?),
+                SelectColumn::DiscMax => track.disc_max = Some(row.try_get(name)?),
+                SelectColumn::ReleaseDate => track.release_date = Some(row.try_get(name)?),
+                SelectColumn::Rating => track.rating = Some(row.try_get(name)?),
+                SelectColumn::OriginalTrack => track.original_track = Some(row.try_get(name)?),
+                SelectColumn::SuggestTarget => track.suggest_target = Some(row.try_get(name)?),
+                SelectColumn::Memo => track.memo = Some(row.try_get(name)?),
+                SelectColumn::MemoManage => track.memo_manage = Some(row.try_get(name)?),
+                SelectColumn::Lyrics => track.lyrics = Some(row.try_get(name)?),
+                SelectColumn::TitleOrder => track.title_order = Some(row.try_get(name)?),
+                SelectColumn::ArtistOrder => track.artist_order = Some(row.try_get(name)?),
+                SelectColumn::AlbumArtistOrder => {
+                    track.album_artist_order = Some(row.try_get(name)?)
+                }
+                SelectColumn::AlbumOrder => track.album_order = Some(row.try_get(name)?),
+                SelectColumn::ComposerOrder => track.composer_order = Some(row.try_get(name)?),
+                SelectColumn::GenreOrder => track.genre_order = Some(row.try_get(name)?),
+                SelectColumn::FolderId => track.folder_id = Some(row.try_get(name)?),
+                SelectColumn::CreatedAt => track.created_at = Some(row.try_get(name)?),
+                SelectColumn::UpdatedAt => track.updated_at = Some(row.try_get(name)?),
+                SelectColumn::ArtworkId => track.artwork_id = Some(row.try_get(name)?),
+                SelectColumn::TagIds => track.tag_ids = Some(row.try_get(name)?),
+            }
+        }
+
+        Ok(track)
+    }
+}
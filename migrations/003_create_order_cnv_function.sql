@@ -0,0 +1,60 @@
+-- 文字列を並べ替え用の値に変換する関数
+--
+-- domain クレートの string_order_cnv::cnv と同じ変換を行う。
+-- *_order カラムが無い項目 (memo 等) を、表記ゆれを無視して比較するために使用する。
+-- 変換マップを変更する際は、両方を合わせて更新すること。
+CREATE FUNCTION order_cnv(value TEXT) RETURNS TEXT AS $$
+DECLARE
+    result TEXT := value;
+BEGIN
+    -- 半角カタカナの濁音・半濁音は、濁点・半濁点とまとめて変換
+    result := replace(result, 'ｶﾞ', 'が');
+    result := replace(result, 'ｷﾞ', 'ぎ');
+    result := replace(result, 'ｸﾞ', 'ぐ');
+    result := replace(result, 'ｹﾞ', 'げ');
+    result := replace(result, 'ｺﾞ', 'ご');
+    result := replace(result, 'ｻﾞ', 'ざ');
+    result := replace(result, 'ｼﾞ', 'じ');
+    result := replace(result, 'ｽﾞ', 'ず');
+    result := replace(result, 'ｾﾞ', 'ぜ');
+    result := replace(result, 'ｿﾞ', 'ぞ');
+    result := replace(result, 'ﾀﾞ', 'だ');
+    result := replace(result, 'ﾁﾞ', 'ぢ');
+    result := replace(result, 'ﾂﾞ', 'づ');
+    result := replace(result, 'ﾃﾞ', 'で');
+    result := replace(result, 'ﾄﾞ', 'ど');
+    result := replace(result, 'ﾊﾞ', 'ば');
+    result := replace(result, 'ﾊﾟ', 'ぱ');
+    result := replace(result, 'ﾋﾞ', 'び');
+    result := replace(result, 'ﾋﾟ', 'ぴ');
+    result := replace(result, 'ﾌﾞ', 'ぶ');
+    result := replace(result, 'ﾌﾟ', 'ぷ');
+    result := replace(result, 'ﾍﾞ', 'べ');
+    result := replace(result, 'ﾍﾟ', 'ぺ');
+    result := replace(result, 'ﾎﾞ', 'ぼ');
+    result := replace(result, 'ﾎﾟ', 'ぽ');
+
+    -- アルファベット大文字→小文字
+    result := translate(
+        result,
+        'ABCDEFGHIJKLMNOPQRSTUVWXYZ',
+        'abcdefghijklmnopqrstuvwxyz'
+    );
+
+    -- カタカナ→ひらがな
+    result := translate(
+        result,
+        'ァィゥェォアイウエオカキクケコガギグゲゴサシスセソザジズゼゾタチツテトッダヂヅデドナニヌネノハヒフヘホバビブベボパピプペポマミムメモャュョヤユヨラリルレロヮワヰヱヲンヽヾ',
+        'ぁぃぅぇぉあいうえおかきくけこがぎぐげごさしすせそざじずぜぞたちつてとっだぢづでどなにぬねのはひふへほばびぶべぼぱぴぷぺぽまみむめもゃゅょやゆよらりるれろゎわゐゑをんゝゞ'
+    );
+
+    -- 半角カタカナ→ひらがな (濁点・半濁点が続かないもの)
+    result := translate(
+        result,
+        'ｧｨｩｪｫｱｲｳｴｵｯﾅﾆﾇﾈﾉﾏﾐﾑﾒﾓｬｭｮﾔﾕﾖﾗﾘﾙﾚﾛﾜｦﾝｶｷｸｹｺｻｼｽｾｿﾀﾁﾂﾃﾄﾊﾋﾌﾍﾎ',
+        'ぁぃぅぇぉあいうえおっなにぬねのまみむめもゃゅょやゆよらりるれろわをんかきくけこさしすせそたちつてとはひふへほ'
+    );
+
+    RETURN result;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE STRICT;
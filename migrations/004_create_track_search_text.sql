@@ -0,0 +1,18 @@
+-- 曲の全文検索用のカラムとインデックス
+--
+-- 日本語は空白で単語に区切られないため、tsvector ではなく pg_trgm の部分一致 (LIKE) で検索する。
+-- 各項目を order_cnv() で変換した値と、*_order カラム (読み仮名が入ることもある) の両方を含め、
+-- 表記ゆれを無視して、元の表記でも読みでも検索できるようにする。
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE tracks ADD COLUMN search_text TEXT NOT NULL GENERATED ALWAYS AS (
+    order_cnv(title) || ' ' || title_order || ' ' ||
+    order_cnv(artist) || ' ' || artist_order || ' ' ||
+    order_cnv(album_artist) || ' ' || album_artist_order || ' ' ||
+    order_cnv(album) || ' ' || album_order || ' ' ||
+    order_cnv(composer) || ' ' || composer_order || ' ' ||
+    order_cnv(memo) || ' ' || order_cnv(lyrics)
+) STORED;
+
+CREATE INDEX tracks_search_text_idx ON tracks USING gin (search_text gin_trgm_ops);
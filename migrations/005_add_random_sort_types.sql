@@ -0,0 +1,8 @@
+-- ランダムの並び順を追加
+--
+-- ランダムの並び順の seed は、sort_seed カラムに保存する (他の並び順では使用しない)
+
+ALTER TYPE sort_type_with_playlist ADD VALUE 'random';
+ALTER TYPE sort_type_with_playlist ADD VALUE 'weighted_shuffle';
+
+ALTER TABLE playlists ADD COLUMN sort_seed INTEGER NOT NULL DEFAULT 0;
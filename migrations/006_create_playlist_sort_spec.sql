@@ -0,0 +1,45 @@
+-- プレイリストの並び順を、並べ替えの項目と方向の配列 (sort_spec) で保存する
+--
+-- 既存の並び順の種類 (sort_type, sort_desc, sort_seed) は、同じ並び順となる項目の配列に変換する
+
+ALTER TABLE playlists ADD COLUMN sort_spec JSONB;
+
+UPDATE playlists SET sort_spec = (
+    SELECT COALESCE(
+        jsonb_agg(
+            jsonb_build_object('field', t.field, 'desc', playlists.sort_desc)
+                || CASE WHEN t.field IN ('random', 'weighted_shuffle')
+                    THEN jsonb_build_object('seed', playlists.sort_seed)
+                    ELSE '{}'::jsonb
+                END
+            ORDER BY t.ord
+        ),
+        '[]'::jsonb
+    )
+    FROM unnest(CASE playlists.sort_type
+        WHEN 'playlist' THEN ARRAY['playlist']
+        WHEN 'track_name' THEN ARRAY['title']
+        WHEN 'artist' THEN ARRAY['artist', 'album', 'disc_number', 'track_number', 'title']
+        WHEN 'album' THEN ARRAY['album', 'artist', 'disc_number', 'track_number', 'title']
+        WHEN 'genre' THEN ARRAY['genre', 'artist', 'album', 'disc_number', 'track_number', 'title']
+        WHEN 'composer' THEN ARRAY['composer', 'artist', 'album', 'disc_number', 'track_number', 'title']
+        WHEN 'duration' THEN ARRAY['duration', 'title']
+        WHEN 'track_index' THEN ARRAY['track_number', 'artist', 'album', 'disc_number', 'title']
+        WHEN 'disc_index' THEN ARRAY['disc_number', 'artist', 'album', 'track_number', 'title']
+        WHEN 'release_date' THEN ARRAY['release_date', 'artist', 'album', 'disc_number', 'track_number', 'title']
+        WHEN 'rating' THEN ARRAY['rating', 'artist', 'album', 'disc_number', 'track_number', 'title']
+        WHEN 'entry_date' THEN ARRAY['entry_date', 'path']
+        WHEN 'path' THEN ARRAY['path']
+        WHEN 'random' THEN ARRAY['random']
+        WHEN 'weighted_shuffle' THEN ARRAY['weighted_shuffle']
+    END) WITH ORDINALITY AS t(field, ord)
+);
+
+ALTER TABLE playlists ALTER COLUMN sort_spec SET NOT NULL;
+
+ALTER TABLE playlists
+    DROP COLUMN sort_type,
+    DROP COLUMN sort_desc,
+    DROP COLUMN sort_seed;
+
+DROP TYPE sort_type_with_playlist;
@@ -0,0 +1,9 @@
+-- 相対日付の条件を含むフィルタプレイリストの、リストアップ時の基準日を保存する
+--
+-- 基準日が変わったプレイリストだけを再度リストアップするために使用する。
+-- 相対日付の条件を含まないプレイリストでは NULL とする。
+
+ALTER TABLE playlists ADD COLUMN listuped_date DATE;
+
+-- 既存のリストアップ結果は基準日が不明なため、リストアップし直す
+UPDATE playlists SET listuped_flag = false WHERE playlist_type IN ('filter', 'folder');